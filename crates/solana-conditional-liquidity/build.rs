@@ -0,0 +1,80 @@
+//! Generates the well-known origin catalog in `origin_identity` from `origins.csv`.
+
+use std::{collections::HashSet, env, fmt::Write, fs, path::Path};
+
+const ORIGINS_FILE: &str = "origins.csv";
+
+const PLATFORMS: &[(&str, &str)] = &[
+    ("web", "Web"),
+    ("mobile", "Mobile"),
+    ("extension", "Extension"),
+    ("desktop", "Desktop"),
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed={ORIGINS_FILE}");
+
+    let origins = fs::read_to_string(ORIGINS_FILE)
+        .unwrap_or_else(|err| panic!("failed to read {ORIGINS_FILE}: {err}"));
+
+    let mut ids = HashSet::new();
+    let mut names = HashSet::from(["UNKNOWN".to_string()]);
+    let mut constants = String::new();
+    let mut catalog = String::new();
+
+    for (line_idx, line) in origins.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fail = |msg: &str| -> ! { panic!("{ORIGINS_FILE}:{}: {msg}", line_idx + 1) };
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [id, name, vendor, platform] = fields[..] else {
+            fail("expected 4 columns: id, name, vendor, platform");
+        };
+
+        let id: u16 = id.parse().unwrap_or_else(|_| fail("id is not a u16"));
+        if id == 0 {
+            fail("id 0 is reserved for UNKNOWN");
+        }
+        if !ids.insert(id) {
+            fail("duplicate id");
+        }
+
+        let is_constant_name = name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            && name.starts_with(|c: char| c.is_ascii_uppercase());
+        if !is_constant_name {
+            fail("name must be an uppercase constant name");
+        }
+        if !names.insert(name.to_string()) {
+            fail("duplicate name");
+        }
+
+        if vendor.is_empty() {
+            fail("vendor must be non-empty");
+        }
+
+        let Some((_, platform)) = PLATFORMS.iter().find(|(key, _)| *key == platform) else {
+            fail("unknown platform");
+        };
+
+        writeln!(constants, "/// {vendor} {}", platform.to_lowercase()).unwrap();
+        writeln!(constants, "pub const {name}: Origin = Origin({id});").unwrap();
+        writeln!(
+            catalog,
+            "    OriginInfo {{ origin: {name}, name: \"{name}\", vendor: {vendor:?}, platform: \
+             Platform::{platform} }},"
+        )
+        .unwrap();
+    }
+
+    let generated = format!(
+        "{constants}\n/// All well-known origins, excluding [`UNKNOWN`]\npub const CATALOG: \
+         &[OriginInfo] = &[\n{catalog}];\n"
+    );
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("origin_identity.rs");
+    fs::write(out_path, generated).unwrap();
+}
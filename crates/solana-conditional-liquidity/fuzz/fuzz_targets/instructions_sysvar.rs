@@ -0,0 +1,62 @@
+#![no_main]
+
+use std::{cell::RefCell, rc::Rc};
+
+use libfuzzer_sys::fuzz_target;
+use solana_conditional_liquidity::{caller_context, verify_attestation, Registry};
+use solana_program::{
+    account_info::AccountInfo,
+    pubkey::Pubkey,
+    sysvar::{self, instructions},
+};
+
+fuzz_target!(|sysvar_data: Vec<u8>| {
+    // A valid registry, so the fuzzer reaches the registry check for any signature it finds
+    let mut registry = Registry {
+        registered_segmenters: [Pubkey::default(); 64],
+    };
+    registry.registered_segmenters[63] = Pubkey::new_from_array([1; 32]);
+    let mut registry_data = Registry::DISCRIMINATOR.to_vec();
+    registry_data.extend_from_slice(bytemuck::bytes_of(&registry));
+
+    let registry_key = Pubkey::new_from_array([2; 32]);
+    let mut registry_lamports = 0;
+    let registry = AccountInfo {
+        key: &registry_key,
+        is_signer: false,
+        is_writable: false,
+        lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+        data: Rc::new(RefCell::new(&mut registry_data)),
+        owner: &Registry::PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    let mut sysvar_data = sysvar_data;
+    let mut sysvar_lamports = 0;
+    let instructions_sysvar = AccountInfo {
+        key: &instructions::ID,
+        is_signer: false,
+        is_writable: false,
+        lamports: Rc::new(RefCell::new(&mut sysvar_lamports)),
+        data: Rc::new(RefCell::new(&mut sysvar_data)),
+        owner: &sysvar::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    let _ = verify_attestation(
+        &Pubkey::default(),
+        &Pubkey::default(),
+        0,
+        &registry,
+        &instructions_sysvar,
+    );
+    let _ = caller_context(
+        &Pubkey::default(),
+        &Pubkey::new_from_array([1; 32]),
+        2,
+        &instructions_sysvar,
+        &[Pubkey::default()],
+    );
+});
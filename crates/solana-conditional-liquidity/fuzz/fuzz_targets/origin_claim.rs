@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use solana_conditional_liquidity::OriginClaim;
+
+fuzz_target!(|data: &[u8]| {
+    // Decoding must never panic, and anything that decodes must re-encode to the same bytes
+    if let Ok((claim, rest)) = OriginClaim::decode(data) {
+        let encoded = claim.encode();
+        assert_eq!(encoded.len(), claim.encoded_len());
+        assert_eq!(&data[..data.len() - rest.len()], &encoded[..]);
+    }
+});
@@ -0,0 +1,117 @@
+#![no_main]
+
+use std::{cell::RefCell, rc::Rc};
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use solana_conditional_liquidity::{
+    check_segmenter, is_invoked_by_segmenter, verify_origin, HeartbeatTracker, Origin, Registry,
+    ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+};
+use solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    owned_by_registry_program: bool,
+    origin_verifying_registry: bool,
+    segmenter_is_signer: bool,
+    segmenter_key: [u8; 32],
+    claimed_origin: u16,
+    /// Prepended with the registry discriminator when set, so the fuzzer reaches deserialization
+    with_discriminator: bool,
+    registry_data: Vec<u8>,
+    current_slot: u64,
+    heartbeat_tracker_owned_by_registry_program: bool,
+    /// Prepended with the heartbeat tracker discriminator when set
+    with_heartbeat_tracker_discriminator: bool,
+    heartbeat_tracker_data: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let mut registry_data = if input.with_discriminator {
+        let mut data = Registry::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&input.registry_data);
+        data
+    } else {
+        input.registry_data
+    };
+
+    let _ = Registry::try_deserialize(&registry_data);
+
+    let registry_key = if input.origin_verifying_registry {
+        ORIGIN_VERIFYING_SEGMENTER_REGISTRY
+    } else {
+        Pubkey::new_from_array([1; 32])
+    };
+    let registry_owner = if input.owned_by_registry_program {
+        Registry::PROGRAM_ID
+    } else {
+        Pubkey::default()
+    };
+    let mut registry_lamports = 0;
+    let registry = AccountInfo {
+        key: &registry_key,
+        is_signer: false,
+        is_writable: false,
+        lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+        data: Rc::new(RefCell::new(&mut registry_data)),
+        owner: &registry_owner,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    let segmenter_key = Pubkey::new_from_array(input.segmenter_key);
+    let owner = Pubkey::default();
+    let mut segmenter_lamports = 0;
+    let mut segmenter_data = [];
+    let segmenter = AccountInfo {
+        key: &segmenter_key,
+        is_signer: input.segmenter_is_signer,
+        is_writable: false,
+        lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+        data: Rc::new(RefCell::new(&mut segmenter_data)),
+        owner: &owner,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    let (heartbeat_tracker_key, _) = HeartbeatTracker::find_address(&registry_key);
+    let heartbeat_tracker_owner = if input.heartbeat_tracker_owned_by_registry_program {
+        Registry::PROGRAM_ID
+    } else {
+        Pubkey::default()
+    };
+    let mut heartbeat_tracker_data = if input.with_heartbeat_tracker_discriminator {
+        let mut data = HeartbeatTracker::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&input.heartbeat_tracker_data);
+        data
+    } else {
+        input.heartbeat_tracker_data
+    };
+    let mut heartbeat_tracker_lamports = 0;
+    let heartbeat_tracker = AccountInfo {
+        key: &heartbeat_tracker_key,
+        is_signer: false,
+        is_writable: false,
+        lamports: Rc::new(RefCell::new(&mut heartbeat_tracker_lamports)),
+        data: Rc::new(RefCell::new(&mut heartbeat_tracker_data)),
+        owner: &heartbeat_tracker_owner,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    let _ = HeartbeatTracker::try_deserialize(&heartbeat_tracker.data.borrow());
+    let _ = check_segmenter(
+        input.current_slot,
+        &registry,
+        &heartbeat_tracker,
+        &segmenter,
+    );
+    let _ = is_invoked_by_segmenter(
+        input.current_slot,
+        &registry,
+        &heartbeat_tracker,
+        &segmenter,
+    );
+    let _ = verify_origin(Origin(input.claimed_origin), &registry, &segmenter);
+});
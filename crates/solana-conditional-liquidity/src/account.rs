@@ -0,0 +1,428 @@
+use std::ops::Deref;
+
+use solana_program::pubkey::Pubkey;
+
+/// A read-only view of an account. This lets the checks in this crate work natively on the
+/// `AccountInfo` types of different program frameworks.
+///
+/// Implemented for `solana_program`'s `AccountInfo` and, with the `pinocchio` feature, for
+/// pinocchio's `AccountInfo`.
+pub trait AccountView {
+    /// A shared borrow of the account data
+    type Data<'a>: Deref<Target = [u8]>
+    where
+        Self: 'a;
+
+    /// The public key of the account
+    fn key(&self) -> &Pubkey;
+
+    /// The program that owns the account
+    fn owner(&self) -> &Pubkey;
+
+    /// Whether the account signed the transaction
+    fn is_signer(&self) -> bool;
+
+    /// Borrows the account data, returning `None` if it is already mutably borrowed
+    fn try_borrow_data(&self) -> Option<Self::Data<'_>>;
+}
+
+impl<'info> AccountView for solana_program::account_info::AccountInfo<'info> {
+    type Data<'a> = std::cell::Ref<'a, [u8]> where Self: 'a;
+
+    fn key(&self) -> &Pubkey {
+        self.key
+    }
+
+    fn owner(&self) -> &Pubkey {
+        self.owner
+    }
+
+    fn is_signer(&self) -> bool {
+        self.is_signer
+    }
+
+    fn try_borrow_data(&self) -> Option<Self::Data<'_>> {
+        let data = self.data.try_borrow().ok()?;
+        Some(std::cell::Ref::map(data, |data| &**data))
+    }
+}
+
+#[cfg(feature = "pinocchio")]
+impl AccountView for pinocchio::account_info::AccountInfo {
+    type Data<'a> = pinocchio::account_info::Ref<'a, [u8]>;
+
+    fn key(&self) -> &Pubkey {
+        bytemuck::cast_ref(pinocchio::account_info::AccountInfo::key(self))
+    }
+
+    fn owner(&self) -> &Pubkey {
+        // SAFETY: The returned reference is only invalidated by `assign`, which is itself unsafe and
+        // can't be called while the reference borrows `self`
+        bytemuck::cast_ref(unsafe { pinocchio::account_info::AccountInfo::owner(self) })
+    }
+
+    fn is_signer(&self) -> bool {
+        pinocchio::account_info::AccountInfo::is_signer(self)
+    }
+
+    fn try_borrow_data(&self) -> Option<Self::Data<'_>> {
+        pinocchio::account_info::AccountInfo::try_borrow_data(self).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::{account_info::AccountInfo, system_program};
+
+    use crate::{
+        check_segmenter, origin_identity,
+        test_utils::{registry_data, SEGMENTER},
+        verify_origin, HeartbeatTracker, Origin, Registry, SegmenterProof, SegmenterRejection,
+        VerifyOriginResult, ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+    };
+
+    use super::*;
+
+    enum TestAccount {
+        Account {
+            key: Pubkey,
+            owner: Pubkey,
+            is_signer: bool,
+            data: Vec<u8>,
+        },
+        /// The same account as the account at the given index
+        Duplicate(usize),
+    }
+
+    /// Exposes test accounts as the `AccountInfo` type of a program framework
+    trait Backend: 'static {
+        type Account<'a>: AccountView;
+
+        fn with_accounts(
+            accounts: Vec<TestAccount>,
+            f: &mut dyn for<'a> FnMut(&[Self::Account<'a>]),
+        );
+
+        fn with_data_borrowed_mut(account: &Self::Account<'_>, f: &mut dyn FnMut());
+    }
+
+    struct SolanaProgram;
+
+    impl Backend for SolanaProgram {
+        type Account<'a> = AccountInfo<'a>;
+
+        fn with_accounts(
+            accounts: Vec<TestAccount>,
+            f: &mut dyn for<'a> FnMut(&[Self::Account<'a>]),
+        ) {
+            let mut storage: Vec<_> = accounts
+                .iter()
+                .filter_map(|account| match account {
+                    TestAccount::Account {
+                        key,
+                        owner,
+                        is_signer,
+                        data,
+                    } => Some((*key, *owner, *is_signer, 0u64, data.clone())),
+                    TestAccount::Duplicate(_) => None,
+                })
+                .collect();
+
+            let mut storage = storage.iter_mut();
+            let mut infos: Vec<AccountInfo> = vec![];
+            for account in &accounts {
+                let info = match account {
+                    TestAccount::Account { .. } => {
+                        let (key, owner, is_signer, lamports, data) = storage.next().unwrap();
+                        AccountInfo::new(key, *is_signer, false, lamports, data, owner, false, 0)
+                    }
+                    TestAccount::Duplicate(original) => infos[*original].clone(),
+                };
+                infos.push(info);
+            }
+
+            f(&infos);
+        }
+
+        fn with_data_borrowed_mut(account: &Self::Account<'_>, f: &mut dyn FnMut()) {
+            let _held = account.data.borrow_mut();
+            f();
+        }
+    }
+
+    #[cfg(feature = "pinocchio")]
+    struct Pinocchio;
+
+    #[cfg(feature = "pinocchio")]
+    impl Backend for Pinocchio {
+        type Account<'a> = pinocchio::account_info::AccountInfo;
+
+        fn with_accounts(
+            accounts: Vec<TestAccount>,
+            f: &mut dyn for<'a> FnMut(&[Self::Account<'a>]),
+        ) {
+            const MAX_ACCOUNTS: usize = 8;
+            const MAX_PERMITTED_DATA_INCREASE: usize = 10 * 1024;
+
+            // Serialize the accounts the way the runtime serializes program input
+            let mut input = vec![];
+            input.extend_from_slice(&(accounts.len() as u64).to_le_bytes());
+            for account in &accounts {
+                match account {
+                    TestAccount::Account {
+                        key,
+                        owner,
+                        is_signer,
+                        data,
+                    } => {
+                        input.extend_from_slice(&[u8::MAX, *is_signer as u8, 0, 0]);
+                        input.extend_from_slice(&[0; 4]);
+                        input.extend_from_slice(key.as_ref());
+                        input.extend_from_slice(owner.as_ref());
+                        input.extend_from_slice(&0u64.to_le_bytes());
+                        input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+                        input.extend_from_slice(data);
+                        input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+                        input.resize(input.len().next_multiple_of(8), 0);
+                        input.extend_from_slice(&0u64.to_le_bytes());
+                    }
+                    TestAccount::Duplicate(original) => {
+                        input.extend_from_slice(&(*original as u64).to_le_bytes());
+                    }
+                }
+            }
+            input.extend_from_slice(&0u64.to_le_bytes());
+            input.extend_from_slice(Registry::PROGRAM_ID.as_ref());
+
+            let mut aligned_input = vec![0u64; input.len().div_ceil(8)];
+            bytemuck::cast_slice_mut::<u64, u8>(&mut aligned_input)[..input.len()]
+                .copy_from_slice(&input);
+
+            let mut infos = [const { std::mem::MaybeUninit::uninit() }; MAX_ACCOUNTS];
+            // SAFETY: The input is aligned and laid out the way the runtime lays it out
+            let (_, count, _) = unsafe {
+                pinocchio::entrypoint::deserialize::<MAX_ACCOUNTS>(
+                    aligned_input.as_mut_ptr().cast(),
+                    &mut infos,
+                )
+            };
+            assert_eq!(count, accounts.len());
+            // SAFETY: `deserialize` initialized the first `count` account infos
+            let infos: Vec<_> = infos[..count]
+                .iter()
+                .map(|info| unsafe { info.assume_init_ref() }.clone())
+                .collect();
+            f(&infos);
+        }
+
+        fn with_data_borrowed_mut(account: &Self::Account<'_>, f: &mut dyn FnMut()) {
+            let _held = account.try_borrow_mut_data().unwrap();
+            f();
+        }
+    }
+
+    fn registry(key: Pubkey) -> TestAccount {
+        TestAccount::Account {
+            key,
+            owner: Registry::PROGRAM_ID,
+            is_signer: false,
+            data: registry_data(),
+        }
+    }
+
+    fn segmenter(key: Pubkey, is_signer: bool) -> TestAccount {
+        TestAccount::Account {
+            key,
+            owner: system_program::id(),
+            is_signer,
+            data: vec![],
+        }
+    }
+
+    /// The heartbeat tracker of the registry at `registry_key`, which wasn't created
+    fn heartbeat_tracker(registry_key: Pubkey) -> TestAccount {
+        TestAccount::Account {
+            key: HeartbeatTracker::find_address(&registry_key).0,
+            owner: system_program::id(),
+            is_signer: false,
+            data: vec![],
+        }
+    }
+
+    /// Checks the segmenter at index 1 against the registry at index 0, with the heartbeat tracker
+    /// of the registry at `registry_key`
+    fn check<B: Backend>(
+        registry_key: Pubkey,
+        mut accounts: Vec<TestAccount>,
+        expected: Result<SegmenterProof, SegmenterRejection>,
+    ) {
+        accounts.push(heartbeat_tracker(registry_key));
+        B::with_accounts(accounts, &mut |accounts| {
+            assert_eq!(
+                check_segmenter(0, &accounts[0], &accounts[2], &accounts[1]),
+                expected
+            );
+        });
+    }
+
+    fn test_check_segmenter<B: Backend>() {
+        let registry_key = Pubkey::new_unique();
+
+        // Signed and exists in registry
+        check::<B>(
+            registry_key,
+            vec![registry(registry_key), segmenter(SEGMENTER, true)],
+            Ok(SegmenterProof {
+                registry: registry_key,
+                segmenter: SEGMENTER,
+            }),
+        );
+
+        // Didn't sign but exists in registry
+        check::<B>(
+            registry_key,
+            vec![registry(registry_key), segmenter(SEGMENTER, false)],
+            Err(SegmenterRejection::SegmenterNotSigner),
+        );
+
+        // Signed but doesn't exist in registry
+        check::<B>(
+            registry_key,
+            vec![
+                registry(registry_key),
+                segmenter(Pubkey::new_unique(), true),
+            ],
+            Err(SegmenterRejection::SegmenterNotRegistered),
+        );
+
+        // Registry isn't owned by the segmenter registry program
+        check::<B>(
+            registry_key,
+            vec![
+                TestAccount::Account {
+                    key: registry_key,
+                    owner: Pubkey::new_unique(),
+                    is_signer: false,
+                    data: registry_data(),
+                },
+                segmenter(SEGMENTER, true),
+            ],
+            Err(SegmenterRejection::InvalidRegistryOwner),
+        );
+
+        // Registry data has the wrong discriminator
+        let mut data = registry_data();
+        data[0] ^= 0xff;
+        check::<B>(
+            registry_key,
+            vec![
+                TestAccount::Account {
+                    key: registry_key,
+                    owner: Registry::PROGRAM_ID,
+                    is_signer: false,
+                    data,
+                },
+                segmenter(SEGMENTER, true),
+            ],
+            Err(SegmenterRejection::InvalidRegistryDiscriminator),
+        );
+
+        // Registry data is truncated
+        let mut data = registry_data();
+        data.truncate(100);
+        check::<B>(
+            registry_key,
+            vec![
+                TestAccount::Account {
+                    key: registry_key,
+                    owner: Registry::PROGRAM_ID,
+                    is_signer: false,
+                    data,
+                },
+                segmenter(SEGMENTER, true),
+            ],
+            Err(SegmenterRejection::InvalidRegistryData),
+        );
+
+        // The same registry account passed twice, with the caller holding a mutable borrow of it
+        B::with_accounts(
+            vec![
+                registry(registry_key),
+                segmenter(SEGMENTER, true),
+                TestAccount::Duplicate(0),
+                heartbeat_tracker(registry_key),
+            ],
+            &mut |accounts| {
+                B::with_data_borrowed_mut(&accounts[2], &mut || {
+                    assert_eq!(
+                        check_segmenter(0, &accounts[0], &accounts[3], &accounts[1]),
+                        Err(SegmenterRejection::RegistryAlreadyBorrowed)
+                    );
+                });
+                assert!(check_segmenter(0, &accounts[0], &accounts[3], &accounts[1]).is_ok());
+            },
+        );
+    }
+
+    fn test_verify_origin<B: Backend>() {
+        // Origin-verifying registry
+        B::with_accounts(
+            vec![
+                registry(ORIGIN_VERIFYING_SEGMENTER_REGISTRY),
+                segmenter(SEGMENTER, true),
+                segmenter(SEGMENTER, false),
+            ],
+            &mut |accounts| {
+                assert_eq!(
+                    verify_origin(Origin(1), &accounts[0], &accounts[1]),
+                    VerifyOriginResult::InvokedBySegmenter(Origin(1))
+                );
+                assert_eq!(
+                    verify_origin(Origin(1), &accounts[0], &accounts[2]),
+                    VerifyOriginResult::NotInvokedBySegmenter
+                );
+            },
+        );
+
+        // Non-origin-verifying registry
+        B::with_accounts(
+            vec![
+                registry(Pubkey::new_unique()),
+                segmenter(SEGMENTER, true),
+                segmenter(Pubkey::new_unique(), true),
+            ],
+            &mut |accounts| {
+                assert_eq!(
+                    verify_origin(Origin(1), &accounts[0], &accounts[1]),
+                    VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN)
+                );
+                assert_eq!(
+                    verify_origin(Origin(1), &accounts[0], &accounts[2]),
+                    VerifyOriginResult::NotInvokedBySegmenter
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_check_segmenter_solana_program() {
+        test_check_segmenter::<SolanaProgram>();
+    }
+
+    #[test]
+    fn test_verify_origin_solana_program() {
+        test_verify_origin::<SolanaProgram>();
+    }
+
+    #[cfg(feature = "pinocchio")]
+    #[test]
+    fn test_check_segmenter_pinocchio() {
+        test_check_segmenter::<Pinocchio>();
+    }
+
+    #[cfg(feature = "pinocchio")]
+    #[test]
+    fn test_verify_origin_pinocchio() {
+        test_verify_origin::<Pinocchio>();
+    }
+}
@@ -0,0 +1,394 @@
+//! Account types for checking segmenters from Anchor programs. Enabled with the `anchor` feature.
+//!
+//! Examples
+//!
+//! ```
+//! use anchor_lang::prelude::*;
+//! use solana_conditional_liquidity::anchor::*;
+//! use solana_conditional_liquidity::{Origin, VerifyOriginResult};
+//!
+//! #[derive(Accounts)]
+//! pub struct Swap<'info> {
+//!     pub segmented: SegmentedInvocation<'info>,
+//!     // ...
+//! }
+//!
+//! fn swap(ctx: Context<Swap>, claimed_origin: u16) {
+//!     let VerifyOriginResult::InvokedBySegmenter(origin) =
+//!         ctx.accounts.segmented.verify_origin(Origin(claimed_origin))
+//!     else {
+//!         // The invocation wasn't signed by a segmenter
+//!         return;
+//!     };
+//!
+//!     // Branch on the origin here
+//! }
+//! # fn main() {}
+//! ```
+
+use std::{cell::Ref, ops::Deref};
+
+use anchor_lang::{prelude::*, Discriminator, ZeroCopy};
+
+use crate::{
+    check_segmenter, verify_attribution, verify_origin, Origin, OriginClaim, Registry,
+    SegmenterProof, SegmenterRejection, VerifyAttributionResult, VerifyOriginResult,
+};
+
+pub use invocation::{SegmentedInvocation, SegmentedInvocationBumps};
+
+/// A read-only segmenter registry account, for use as `AccountLoader<'info, SegmenterRegistry>`.
+/// Anchor checks that the account is owned by the segmenter registry program and has the registry
+/// discriminator. Borrow the registry with [`SegmenterRegistry::load`]. Writes to the account are
+/// never persisted.
+///
+/// `Account<'info, SegmenterRegistry>` also works, but copies the 2048-byte registry onto the
+/// stack, which can overflow the 4KB stack frame of an instruction with other large accounts. Use
+/// `Box<Account<'info, SegmenterRegistry>>` if you need an `Account`.
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(transparent)]
+pub struct SegmenterRegistry(Registry);
+
+impl SegmenterRegistry {
+    /// Borrows the registry of `account` without copying it. Unlike [`AccountLoader::load`],
+    /// returns an error instead of panicking if the account data is too short.
+    pub fn load<'a>(account: &'a AccountLoader<'_, Self>) -> Result<Ref<'a, Self>> {
+        let data = account.as_ref().try_borrow_data()?;
+        if data.get(..8) != Some(&Registry::DISCRIMINATOR[..]) {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        Ref::filter_map(data, |data| {
+            let data = data.get(8..8 + std::mem::size_of::<Self>())?;
+            bytemuck::try_from_bytes(data).ok()
+        })
+        .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+
+    /// Whether `segmenter` signed the invocation and is registered in this registry. Can be used as
+    /// an account constraint:
+    ///
+    /// ```
+    /// use anchor_lang::prelude::*;
+    /// use solana_conditional_liquidity::anchor::SegmenterRegistry;
+    ///
+    /// #[error_code]
+    /// pub enum ErrorCode {
+    ///     NotInvokedBySegmenter,
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct SegmentedOnly<'info> {
+    ///     #[account(
+    ///         constraint = SegmenterRegistry::load(&registry)?.is_invoked_by(&segmenter)
+    ///             @ ErrorCode::NotInvokedBySegmenter,
+    ///     )]
+    ///     pub registry: AccountLoader<'info, SegmenterRegistry>,
+    ///     pub segmenter: Signer<'info>,
+    /// }
+    /// # fn main() {}
+    /// ```
+    pub fn is_invoked_by(&self, segmenter: &AccountInfo<'_>) -> bool {
+        segmenter.is_signer && self.0.is_segmenter_registered(segmenter.key)
+    }
+}
+
+impl Deref for SegmenterRegistry {
+    type Target = Registry;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Owner for SegmenterRegistry {
+    fn owner() -> Pubkey {
+        Registry::PROGRAM_ID
+    }
+}
+
+impl Discriminator for SegmenterRegistry {
+    const DISCRIMINATOR: [u8; 8] = Registry::DISCRIMINATOR;
+}
+
+impl ZeroCopy for SegmenterRegistry {}
+
+impl AccountSerialize for SegmenterRegistry {}
+
+impl AccountDeserialize for SegmenterRegistry {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        if buf.get(..8) != Some(&Registry::DISCRIMINATOR[..]) {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let data = buf
+            .get(8..8 + std::mem::size_of::<Registry>())
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        let registry = bytemuck::try_pod_read_unaligned(data)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+        Ok(Self(registry))
+    }
+}
+
+#[cfg(feature = "idl-build")]
+impl anchor_lang::IdlBuild for SegmenterRegistry {}
+
+impl SegmentedInvocation<'_> {
+    /// Checks whether the invocation was signed by a segmenter in the registry that is active as of
+    /// `current_slot` according to the registry's `heartbeat_tracker`. See [`check_segmenter`].
+    pub fn check_segmenter(
+        &self,
+        current_slot: u64,
+        heartbeat_tracker: &AccountInfo<'_>,
+    ) -> std::result::Result<SegmenterProof, SegmenterRejection> {
+        check_segmenter(
+            current_slot,
+            &self.registry.to_account_info(),
+            heartbeat_tracker,
+            &self.segmenter.to_account_info(),
+        )
+    }
+
+    /// Checks whether the invocation was signed by a segmenter in the registry that is active as of
+    /// `current_slot` according to the registry's `heartbeat_tracker`. See
+    /// [`is_invoked_by_segmenter`](crate::is_invoked_by_segmenter).
+    pub fn is_invoked_by_segmenter(
+        &self,
+        current_slot: u64,
+        heartbeat_tracker: &AccountInfo<'_>,
+    ) -> bool {
+        self.check_segmenter(current_slot, heartbeat_tracker)
+            .is_ok()
+    }
+
+    /// Checks whether the invocation was signed by a segmenter and verifies the origin of the
+    /// invocation. See [`verify_origin`].
+    pub fn verify_origin(&self, claimed_origin: Origin) -> VerifyOriginResult {
+        verify_origin(
+            claimed_origin,
+            &self.registry.to_account_info(),
+            &self.segmenter.to_account_info(),
+        )
+    }
+
+    /// Checks whether the invocation was signed by a segmenter and verifies the origin and referral
+    /// of the claim. See [`verify_attribution`].
+    pub fn verify_attribution(
+        &self,
+        claim: &OriginClaim,
+        current_slot: u64,
+    ) -> VerifyAttributionResult {
+        verify_attribution(
+            claim,
+            current_slot,
+            &self.registry.to_account_info(),
+            &self.segmenter.to_account_info(),
+        )
+    }
+}
+
+// Anchor generates the client modules of `#[derive(Accounts)]` structs as `pub(crate)`, but programs
+// that use `SegmentedInvocation` as a composite field need to name them. Derive in a private module
+// and re-export them publicly.
+mod invocation {
+    use anchor_lang::prelude::*;
+
+    use super::SegmenterRegistry;
+
+    /// The accounts needed to check whether an invocation was signed by a segmenter. The segmenter
+    /// doesn't need to sign; check the result with [`SegmentedInvocation::verify_origin`] or
+    /// [`SegmentedInvocation::is_invoked_by_segmenter`].
+    #[derive(Accounts)]
+    pub struct SegmentedInvocation<'info> {
+        /// The registry to check the segmenter against. Borrowed rather than copied, so it doesn't
+        /// take up stack space.
+        #[account(
+            constraint = SegmenterRegistry::load(&registry).is_ok()
+                @ ErrorCode::AccountDidNotDeserialize,
+        )]
+        pub registry: AccountLoader<'info, SegmenterRegistry>,
+
+        /// CHECK: May be any account. It is only treated as a segmenter if it signed the invocation
+        /// and is registered in the registry.
+        pub segmenter: UncheckedAccount<'info>,
+    }
+}
+
+#[doc(hidden)]
+pub mod __client_accounts_segmented_invocation {
+    pub use super::invocation::__client_accounts_segmented_invocation::*;
+}
+
+#[doc(hidden)]
+pub mod __cpi_client_accounts_segmented_invocation {
+    pub use super::invocation::__cpi_client_accounts_segmented_invocation::*;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use anchor_lang::{solana_program::system_program, Bumps};
+
+    use crate::{
+        origin_identity,
+        test_utils::{heartbeat_tracker, registry_data, SEGMENTER},
+        ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+    };
+
+    use super::*;
+
+    /// Anchor requires the account infos to live as long as their data, so leak them
+    fn account(key: Pubkey, owner: Pubkey, is_signer: bool, data: Vec<u8>) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            false,
+            Box::leak(Box::new(0)),
+            data.leak(),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )
+    }
+
+    fn try_accounts(accounts: Vec<AccountInfo<'static>>) -> Result<SegmentedInvocation<'static>> {
+        let mut bumps = <SegmentedInvocation as Bumps>::Bumps::default();
+        SegmentedInvocation::try_accounts(
+            &Pubkey::new_unique(),
+            &mut &*accounts.leak(),
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+    }
+
+    #[test]
+    fn test_segmented_invocation() {
+        let registry = account(
+            ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            Registry::PROGRAM_ID,
+            false,
+            registry_data(),
+        );
+        let segmenter = account(SEGMENTER, system_program::ID, true, vec![]);
+        let heartbeat_tracker = heartbeat_tracker(&ORIGIN_VERIFYING_SEGMENTER_REGISTRY);
+
+        // Signed and exists in registry
+        let invocation = try_accounts(vec![registry.clone(), segmenter.clone()]).unwrap();
+        assert!(invocation.is_invoked_by_segmenter(0, &heartbeat_tracker));
+        assert_eq!(
+            invocation.check_segmenter(0, &heartbeat_tracker),
+            Ok(SegmenterProof {
+                registry: ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+                segmenter: SEGMENTER,
+            })
+        );
+        assert_eq!(
+            invocation.verify_origin(Origin(1)),
+            VerifyOriginResult::InvokedBySegmenter(Origin(1))
+        );
+
+        // Didn't sign but exists in registry
+        let unsigned_segmenter = account(SEGMENTER, system_program::ID, false, vec![]);
+        let invocation = try_accounts(vec![registry.clone(), unsigned_segmenter]).unwrap();
+        assert!(!invocation.is_invoked_by_segmenter(0, &heartbeat_tracker));
+        assert_eq!(
+            invocation.verify_origin(Origin(1)),
+            VerifyOriginResult::NotInvokedBySegmenter
+        );
+
+        // Signed but doesn't exist in registry
+        let unregistered_segmenter =
+            account(Pubkey::new_unique(), system_program::ID, true, vec![]);
+        let invocation = try_accounts(vec![registry, unregistered_segmenter]).unwrap();
+        assert!(!invocation.is_invoked_by_segmenter(0, &heartbeat_tracker));
+        assert_eq!(
+            invocation.check_segmenter(0, &heartbeat_tracker),
+            Err(SegmenterRejection::SegmenterNotRegistered)
+        );
+
+        // Signed and exists in a registry that isn't origin-verifying
+        let other_registry = account(
+            Pubkey::new_unique(),
+            Registry::PROGRAM_ID,
+            false,
+            registry_data(),
+        );
+        let invocation = try_accounts(vec![other_registry, segmenter]).unwrap();
+        assert_eq!(
+            invocation.verify_origin(Origin(1)),
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN)
+        );
+    }
+
+    #[test]
+    fn test_segmented_invocation_invalid_registry() {
+        let segmenter = account(SEGMENTER, system_program::ID, true, vec![]);
+
+        // Not owned by the segmenter registry program
+        let wrong_owner = Pubkey::new_unique();
+        let registry = account(Pubkey::new_unique(), wrong_owner, false, registry_data());
+        assert_eq!(
+            try_accounts(vec![registry, segmenter.clone()]).err(),
+            Some(
+                Error::from(ErrorCode::AccountOwnedByWrongProgram)
+                    .with_account_name("registry")
+                    .with_pubkeys((wrong_owner, Registry::PROGRAM_ID))
+            )
+        );
+
+        // Wrong discriminator
+        let mut data = registry_data();
+        data[0] ^= 0xff;
+        let registry = account(Pubkey::new_unique(), Registry::PROGRAM_ID, false, data);
+        assert_eq!(
+            try_accounts(vec![registry, segmenter.clone()]).err(),
+            Some(
+                Error::from(ErrorCode::AccountDiscriminatorMismatch).with_account_name("registry")
+            )
+        );
+
+        // Truncated
+        let mut data = registry_data();
+        data.truncate(100);
+        let registry = account(Pubkey::new_unique(), Registry::PROGRAM_ID, false, data);
+        assert_eq!(
+            try_accounts(vec![registry, segmenter]).err(),
+            Some(Error::from(ErrorCode::AccountDidNotDeserialize).with_account_name("registry"))
+        );
+    }
+
+    #[test]
+    fn test_load_registry() {
+        let segmenter = account(SEGMENTER, system_program::ID, true, vec![]);
+        let registry = Box::leak(Box::new(account(
+            Pubkey::new_unique(),
+            Registry::PROGRAM_ID,
+            false,
+            registry_data(),
+        )));
+        let loader = AccountLoader::<SegmenterRegistry>::try_from(registry).unwrap();
+        assert!(SegmenterRegistry::load(&loader)
+            .unwrap()
+            .is_invoked_by(&segmenter));
+
+        // Anchor's loader only checks the discriminator, so a truncated registry must be rejected
+        // by `load` instead of panicking
+        let mut data = registry_data();
+        data.truncate(100);
+        let registry = Box::leak(Box::new(account(
+            Pubkey::new_unique(),
+            Registry::PROGRAM_ID,
+            false,
+            data,
+        )));
+        let loader = AccountLoader::<SegmenterRegistry>::try_from(registry).unwrap();
+        assert_eq!(
+            SegmenterRegistry::load(&loader).err(),
+            Some(ErrorCode::AccountDidNotDeserialize.into())
+        );
+    }
+}
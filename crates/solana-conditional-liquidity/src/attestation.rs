@@ -0,0 +1,422 @@
+use solana_program::{ed25519_program, instruction::Instruction, pubkey::Pubkey};
+
+use crate::{
+    introspection::{ed25519_instruction_data, find_ed25519_signature},
+    origin_identity,
+    registry::check_registered,
+    AccountView, Origin, Registry, ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+};
+
+/// An attestation a segmenter signs for a single user's invocation of a DEX program, verified with
+/// the Ed25519 precompile instead of a transaction signature. This lets the user sign the
+/// transaction last, without the segmenter co-signing it.
+///
+/// The signed message is [`Attestation::DOMAIN`], a version byte, then the program id, user, origin
+/// (little-endian `u16`), expiry slot and nonce (little-endian `u64`s).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attestation {
+    /// The DEX program the attestation is for
+    pub program_id: Pubkey,
+    /// The user the attestation is for
+    pub user: Pubkey,
+    pub origin: Origin,
+    /// The last slot in which the attestation is valid
+    pub expiry_slot: u64,
+    /// Distinguishes attestations that are otherwise equal. DEXs that must not accept an
+    /// attestation twice should track the nonces they've seen. Nonce trackers only keep the most
+    /// recent [`NonceTracker::WINDOW`](crate::NonceTracker::WINDOW) nonces, so a segmenter must
+    /// issue increasing nonces, and the nonces of its unexpired attestations must stay within
+    /// `WINDOW` of each other. Otherwise an older attestation is rejected once a newer one slides
+    /// the window past it.
+    pub nonce: u64,
+}
+
+impl Attestation {
+    /// Prefixes every attestation message, so that a segmenter's attestation signatures can't be
+    /// mistaken for signatures over anything else
+    pub const DOMAIN: [u8; 16] = *b"segmenter-attest";
+    pub const VERSION: u8 = 1;
+    pub const MESSAGE_LEN: usize = 16 + 1 + 32 + 32 + 2 + 8 + 8;
+
+    /// The message the segmenter signs
+    pub fn to_message(&self) -> [u8; Self::MESSAGE_LEN] {
+        let mut message = [0; Self::MESSAGE_LEN];
+        let fields: [&[u8]; 7] = [
+            &Self::DOMAIN,
+            &[Self::VERSION],
+            self.program_id.as_ref(),
+            self.user.as_ref(),
+            &self.origin.0.to_le_bytes(),
+            &self.expiry_slot.to_le_bytes(),
+            &self.nonce.to_le_bytes(),
+        ];
+        let mut offset = 0;
+        for field in fields {
+            message[offset..offset + field.len()].copy_from_slice(field);
+            offset += field.len();
+        }
+        message
+    }
+
+    /// Parses a signed message. Returns `None` if it isn't an attestation message of this version.
+    pub fn from_message(message: &[u8]) -> Option<Self> {
+        if message.len() != Self::MESSAGE_LEN {
+            return None;
+        }
+        let (domain, rest) = message.split_at(16);
+        let (version, rest) = rest.split_at(1);
+        if domain != Self::DOMAIN || version != [Self::VERSION] {
+            return None;
+        }
+
+        let (program_id, rest) = rest.split_at(32);
+        let (user, rest) = rest.split_at(32);
+        let (origin, rest) = rest.split_at(2);
+        let (expiry_slot, nonce) = rest.split_at(8);
+        Some(Self {
+            program_id: Pubkey::try_from(program_id).ok()?,
+            user: Pubkey::try_from(user).ok()?,
+            origin: Origin(u16::from_le_bytes(origin.try_into().ok()?)),
+            expiry_slot: u64::from_le_bytes(expiry_slot.try_into().ok()?),
+            nonce: u64::from_le_bytes(nonce.try_into().ok()?),
+        })
+    }
+
+    /// Builds the Ed25519 precompile instruction that verifies the segmenter's `signature` over
+    /// [`Attestation::to_message`]. Add it to the same transaction as the DEX instruction.
+    pub fn ed25519_instruction(&self, segmenter: &Pubkey, signature: &[u8; 64]) -> Instruction {
+        Instruction {
+            program_id: ed25519_program::ID,
+            accounts: vec![],
+            data: ed25519_instruction_data(segmenter, signature, &self.to_message()),
+        }
+    }
+}
+
+/// An attestation that was signed by a segmenter in the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedAttestation {
+    /// The segmenter that signed the attestation
+    pub segmenter: Pubkey,
+    /// The attested origin, or [`origin_identity::UNKNOWN`] if the registry isn't the
+    /// [`ORIGIN_VERIFYING_SEGMENTER_REGISTRY`]
+    pub origin: Origin,
+    pub expiry_slot: u64,
+    pub nonce: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyAttestationResult {
+    /// The transaction contains an attestation for the program and user, signed by a segmenter
+    AttestedBySegmenter(VerifiedAttestation),
+    /// The transaction doesn't contain a valid attestation for the program and user
+    NotAttestedBySegmenter,
+}
+
+/// Checks whether the transaction contains an [`Attestation`] for `program_id` and `user` that was
+/// signed by a segmenter in the registry and hasn't expired as of `current_slot`. This is an
+/// alternative to [`verify_origin`](crate::verify_origin) for when the segmenter doesn't sign the
+/// transaction. The attestation signatures are verified by Ed25519 precompile instructions in the
+/// transaction, found through the Instructions sysvar account.
+///
+/// The caller must check that `user` signed the transaction. An attestation only names the user,
+/// so without that check anyone who sees it can put it in a transaction of their own and invoke the
+/// DEX as the attested user.
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::{verify_attestation, VerifyAttestationResult};
+/// use solana_program::{account_info::AccountInfo, clock::Clock, pubkey::Pubkey, sysvar::Sysvar};
+///
+/// fn handler(
+///     program_id: &Pubkey,
+///     user: &AccountInfo<'_>,
+///     registry: &AccountInfo<'_>,
+///     instructions_sysvar: &AccountInfo<'_>,
+/// ) {
+///     if !user.is_signer {
+///         // The attestation only counts for a user who signed the transaction
+///         return;
+///     }
+///
+///     let current_slot = Clock::get().unwrap().slot;
+///     let VerifyAttestationResult::AttestedBySegmenter(attestation) =
+///         verify_attestation(program_id, user.key, current_slot, registry, instructions_sysvar)
+///     else {
+///         // No segmenter attested the invocation
+///         return;
+///     };
+///
+///     // Branch on attestation.origin here
+/// }
+/// ```
+pub fn verify_attestation(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    current_slot: u64,
+    registry: &impl AccountView,
+    instructions_sysvar: &impl AccountView,
+) -> VerifyAttestationResult {
+    if *registry.owner() != Registry::PROGRAM_ID {
+        return VerifyAttestationResult::NotAttestedBySegmenter;
+    }
+
+    let verified = find_ed25519_signature(instructions_sysvar, |segmenter, message| {
+        let attestation = Attestation::from_message(message)?;
+        let is_valid = attestation.program_id == *program_id
+            && attestation.user == *user
+            && current_slot <= attestation.expiry_slot
+            && check_registered(registry, &segmenter).is_ok();
+        is_valid.then_some((segmenter, attestation))
+    });
+    let Some((segmenter, attestation)) = verified else {
+        return VerifyAttestationResult::NotAttestedBySegmenter;
+    };
+
+    let origin = if *registry.key() == ORIGIN_VERIFYING_SEGMENTER_REGISTRY {
+        attestation.origin
+    } else {
+        origin_identity::UNKNOWN
+    };
+    VerifyAttestationResult::AttestedBySegmenter(VerifiedAttestation {
+        segmenter,
+        origin,
+        expiry_slot: attestation.expiry_slot,
+        nonce: attestation.nonce,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::{
+        account_info::AccountInfo,
+        sysvar::{
+            self,
+            instructions::{self, construct_instructions_data, BorrowedInstruction},
+        },
+    };
+
+    use crate::test_utils::{registry_data, SEGMENTER};
+
+    use super::*;
+
+    /// Serializes the Instructions sysvar for a transaction with the given instructions
+    fn instructions_data(instructions: &[Instruction]) -> Vec<u8> {
+        let borrowed = instructions
+            .iter()
+            .map(|instruction| BorrowedInstruction {
+                program_id: &instruction.program_id,
+                accounts: vec![],
+                data: &instruction.data,
+            })
+            .collect::<Vec<_>>();
+        construct_instructions_data(&borrowed)
+    }
+
+    fn attestation() -> Attestation {
+        Attestation {
+            program_id: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            origin: origin_identity::DFLOW_WEB,
+            expiry_slot: 100,
+            nonce: 42,
+        }
+    }
+
+    #[test]
+    fn test_attestation_message() {
+        let attestation = attestation();
+        let message = attestation.to_message();
+        assert_eq!(&message[..16], b"segmenter-attest");
+        assert_eq!(message[16], Attestation::VERSION);
+        assert_eq!(Attestation::from_message(&message), Some(attestation));
+
+        assert_eq!(Attestation::from_message(&message[1..]), None);
+        let mut wrong_version = message;
+        wrong_version[16] = 2;
+        assert_eq!(Attestation::from_message(&wrong_version), None);
+        let mut wrong_domain = message;
+        wrong_domain[0] ^= 1;
+        assert_eq!(Attestation::from_message(&wrong_domain), None);
+    }
+
+    #[test]
+    fn test_verify_attestation() {
+        let attestation = attestation();
+        let dex_instruction = Instruction {
+            program_id: attestation.program_id,
+            accounts: vec![],
+            data: vec![],
+        };
+
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let verify = |instructions: &[Instruction], user: &Pubkey, current_slot: u64| {
+            let mut sysvar_data = instructions_data(instructions);
+            let mut sysvar_lamports = 0;
+            let instructions_sysvar = AccountInfo {
+                key: &instructions::ID,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut sysvar_lamports)),
+                data: Rc::new(RefCell::new(&mut sysvar_data)),
+                owner: &sysvar::ID,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+            verify_attestation(
+                &attestation.program_id,
+                user,
+                current_slot,
+                &registry,
+                &instructions_sysvar,
+            )
+        };
+
+        // Signed by a registered segmenter
+        let ed25519_instruction = attestation.ed25519_instruction(&SEGMENTER, &[7; 64]);
+        let transaction = [ed25519_instruction.clone(), dex_instruction.clone()];
+        let verified = VerifyAttestationResult::AttestedBySegmenter(VerifiedAttestation {
+            segmenter: SEGMENTER,
+            origin: origin_identity::DFLOW_WEB,
+            expiry_slot: 100,
+            nonce: 42,
+        });
+        assert_eq!(verify(&transaction, &attestation.user, 100), verified);
+
+        // The attestation may come after the DEX instruction
+        let reordered = [dex_instruction.clone(), ed25519_instruction];
+        assert_eq!(verify(&reordered, &attestation.user, 0), verified);
+
+        // Expired
+        assert_eq!(
+            verify(&transaction, &attestation.user, 101),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+
+        // For another user
+        assert_eq!(
+            verify(&transaction, &Pubkey::new_unique(), 0),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+
+        // For another program
+        let other_program = Attestation {
+            program_id: Pubkey::new_unique(),
+            ..attestation
+        };
+        let transaction = [
+            other_program.ed25519_instruction(&SEGMENTER, &[7; 64]),
+            dex_instruction.clone(),
+        ];
+        assert_eq!(
+            verify(&transaction, &attestation.user, 0),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+
+        // Signed by an unregistered key
+        let transaction = [
+            attestation.ed25519_instruction(&Pubkey::new_unique(), &[7; 64]),
+            dex_instruction.clone(),
+        ];
+        assert_eq!(
+            verify(&transaction, &attestation.user, 0),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+
+        // The attestation data in an instruction that isn't the Ed25519 precompile
+        let fake_precompile = Instruction {
+            program_id: Pubkey::new_unique(),
+            ..attestation.ed25519_instruction(&SEGMENTER, &[7; 64])
+        };
+        assert_eq!(
+            verify(&[fake_precompile, dex_instruction], &attestation.user, 0),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+    }
+
+    #[test]
+    fn test_verify_attestation_invalid_accounts() {
+        let attestation = attestation();
+        let mut sysvar_data =
+            instructions_data(&[attestation.ed25519_instruction(&SEGMENTER, &[7; 64])]);
+
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &Pubkey::new_unique(),
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+        let mut sysvar_lamports = 0;
+        let instructions_sysvar = AccountInfo {
+            key: &instructions::ID,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut sysvar_lamports)),
+            data: Rc::new(RefCell::new(&mut sysvar_data)),
+            owner: &sysvar::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+        let verify = |registry: &AccountInfo, instructions_sysvar: &AccountInfo| {
+            verify_attestation(
+                &attestation.program_id,
+                &attestation.user,
+                0,
+                registry,
+                instructions_sysvar,
+            )
+        };
+
+        // A registry that isn't origin-verifying doesn't verify the origin
+        assert_eq!(
+            verify(&registry, &instructions_sysvar),
+            VerifyAttestationResult::AttestedBySegmenter(VerifiedAttestation {
+                segmenter: SEGMENTER,
+                origin: origin_identity::UNKNOWN,
+                expiry_slot: 100,
+                nonce: 42,
+            })
+        );
+
+        // A fake Instructions sysvar
+        let fake_sysvar = AccountInfo {
+            key: &Pubkey::new_unique(),
+            ..instructions_sysvar.clone()
+        };
+        assert_eq!(
+            verify(&registry, &fake_sysvar),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+
+        // A registry that isn't owned by the segmenter registry program
+        let fake_registry = AccountInfo {
+            owner: &sysvar::ID,
+            ..registry.clone()
+        };
+        assert_eq!(
+            verify(&fake_registry, &instructions_sysvar),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    origin_identity, registry::check_registered_signer, AccountView, FlowScore, Origin,
+    OriginClaim, ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+};
+
+/// The origin, referral and flow score a segmenter attested for an invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attribution {
+    pub origin: Origin,
+    /// The referral or frontend fee account to pay, if the segmenter attested one
+    pub referral: Option<Pubkey>,
+    /// The flow score of the invocation, if the segmenter attested one. Map it to a policy tier with
+    /// [`FlowScore::policy_tier`].
+    pub score: Option<FlowScore>,
+}
+
+impl Attribution {
+    /// Attribution when the origin, referral and score are unknown or could not be verified
+    pub const UNVERIFIED: Self = Self {
+        origin: origin_identity::UNKNOWN,
+        referral: None,
+        score: None,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyAttributionResult {
+    /// The invocation was signed by a segmenter
+    InvokedBySegmenter(Attribution),
+    /// The invocation was not signed by a segmenter
+    NotInvokedBySegmenter,
+}
+
+/// Checks whether the invocation was signed by a segmenter and verifies the origin, referral and
+/// flow score of the claim. The same trust rules as
+/// [`verify_origin_claim`](crate::verify_origin_claim) apply to all of them: if the registry isn't
+/// the [`ORIGIN_VERIFYING_SEGMENTER_REGISTRY`] or the claim has expired, the attribution is
+/// [`Attribution::UNVERIFIED`].
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::{verify_attribution, OriginClaim, VerifyAttributionResult};
+/// use solana_program::{account_info::AccountInfo, clock::Clock, sysvar::Sysvar};
+///
+/// fn handler(
+///     registry: &AccountInfo<'_>,
+///     segmenter: &AccountInfo<'_>,
+///     referral_fee_account: &AccountInfo<'_>,
+///     claim: &OriginClaim,
+/// ) {
+///     let current_slot = Clock::get().unwrap().slot;
+///     let VerifyAttributionResult::InvokedBySegmenter(attribution) =
+///         verify_attribution(claim, current_slot, registry, segmenter)
+///     else {
+///         // The invocation wasn't signed by a segmenter
+///         return;
+///     };
+///
+///     if attribution.referral == Some(*referral_fee_account.key) {
+///         // Pay the frontend's fee to the referral fee account here
+///     }
+/// }
+/// ```
+pub fn verify_attribution(
+    claim: &OriginClaim,
+    current_slot: u64,
+    registry: &impl AccountView,
+    segmenter: &impl AccountView,
+) -> VerifyAttributionResult {
+    if check_registered_signer(registry, segmenter).is_err() {
+        return VerifyAttributionResult::NotInvokedBySegmenter;
+    }
+
+    if registry.key() != &ORIGIN_VERIFYING_SEGMENTER_REGISTRY || claim.is_expired(current_slot) {
+        return VerifyAttributionResult::InvokedBySegmenter(Attribution::UNVERIFIED);
+    }
+
+    // If one of the DFlow segmenters signed the invocation, we can trust the whole claim
+    VerifyAttributionResult::InvokedBySegmenter(Attribution {
+        origin: claim.origin,
+        referral: claim.referral,
+        score: claim.score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::{account_info::AccountInfo, system_program};
+
+    use crate::{
+        test_utils::{registry_data, SEGMENTER},
+        Registry,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_verify_attribution() {
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut segmenter_lamports = 0;
+        let mut segmenter_data = [];
+        let segmenter = AccountInfo {
+            key: &SEGMENTER,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+            data: Rc::new(RefCell::new(&mut segmenter_data)),
+            owner: &system_program::id(),
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let referral = Pubkey::new_unique();
+        let claim = OriginClaim {
+            origin: Origin(3),
+            expiry_slot: Some(100),
+            referral: Some(referral),
+            score: Some(FlowScore(10)),
+        };
+
+        // Signed, origin-verifying registry and not expired
+        assert_eq!(
+            verify_attribution(&claim, 100, &registry, &segmenter),
+            VerifyAttributionResult::InvokedBySegmenter(Attribution {
+                origin: Origin(3),
+                referral: Some(referral),
+                score: Some(FlowScore(10)),
+            })
+        );
+
+        // No referral or score in the claim
+        let claim_without_referral = OriginClaim {
+            referral: None,
+            score: None,
+            ..claim
+        };
+        assert_eq!(
+            verify_attribution(&claim_without_referral, 100, &registry, &segmenter),
+            VerifyAttributionResult::InvokedBySegmenter(Attribution {
+                origin: Origin(3),
+                referral: None,
+                score: None,
+            })
+        );
+
+        // Expired
+        assert_eq!(
+            verify_attribution(&claim, 101, &registry, &segmenter),
+            VerifyAttributionResult::InvokedBySegmenter(Attribution::UNVERIFIED)
+        );
+
+        // Not signed by the segmenter
+        let unsigned_segmenter = AccountInfo {
+            is_signer: false,
+            ..segmenter.clone()
+        };
+        assert_eq!(
+            verify_attribution(&claim, 100, &registry, &unsigned_segmenter),
+            VerifyAttributionResult::NotInvokedBySegmenter
+        );
+    }
+
+    #[test]
+    fn test_verify_attribution_non_verifying_registry() {
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &Pubkey::new_unique(),
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut segmenter_lamports = 0;
+        let mut segmenter_data = [];
+        let segmenter = AccountInfo {
+            key: &SEGMENTER,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+            data: Rc::new(RefCell::new(&mut segmenter_data)),
+            owner: &system_program::id(),
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        // The referral and score are downgraded to none along with the origin
+        let claim = OriginClaim {
+            referral: Some(Pubkey::new_unique()),
+            score: Some(FlowScore(10)),
+            ..OriginClaim::new(Origin(3))
+        };
+        assert_eq!(
+            verify_attribution(&claim, 0, &registry, &segmenter),
+            VerifyAttributionResult::InvokedBySegmenter(Attribution::UNVERIFIED)
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{directory::config_address, Registry};
+
+const MAX_ITEMS: usize = 64;
+
+/// A change to a registry or to the deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    AddSegmenter,
+    RemoveSegmenter,
+    /// The admin of the deployment was changed, recorded in the deployment's audit log
+    ChangeAdmin,
+    /// The registry was listed in the directory with an authority
+    SetDirectoryAuthority,
+    /// The metadata of the registry was created with an authority
+    SetMetadataAuthority,
+}
+
+/// A change recorded in an audit log
+#[derive(Pod, Zeroable, Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct AuditEntry {
+    /// The slot of the change
+    pub slot: u64,
+    /// The account that made the change
+    pub actor: Pubkey,
+    /// The segmenter that was added or removed, or the new admin or authority
+    pub key: Pubkey,
+    /// The [`AuditAction`] as a `u8`. Use [`AuditEntry::action`] for the action.
+    pub action: u8,
+    pub padding: [u8; 7],
+}
+
+impl AuditEntry {
+    /// The action of the entry, `None` if it is an action this version doesn't know about
+    pub fn action(&self) -> Option<AuditAction> {
+        match self.action {
+            0 => Some(AuditAction::AddSegmenter),
+            1 => Some(AuditAction::RemoveSegmenter),
+            2 => Some(AuditAction::ChangeAdmin),
+            3 => Some(AuditAction::SetDirectoryAuthority),
+            4 => Some(AuditAction::SetMetadataAuthority),
+            _ => None,
+        }
+    }
+}
+
+/// The audit log of a registry, owned by the segmenter registry program, which records the most
+/// recent changes to its segmenters and authorities in a ring buffer. Admin changes are recorded in
+/// the deployment's audit log, at [`AuditLog::find_deployment_address`].
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct AuditLog {
+    /// The registry, or the config for the deployment's audit log
+    pub registry: Pubkey,
+    /// The number of entries ever appended
+    pub head: u64,
+    pub entries: [AuditEntry; MAX_ITEMS],
+}
+
+impl AuditLog {
+    pub const DISCRIMINATOR: [u8; 8] = [230, 207, 176, 233, 170, 130, 101, 244];
+    pub const SEED: &'static [u8] = b"audit_log";
+
+    /// The address of the audit log of `registry`
+    pub fn find_address(registry: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, registry.as_ref()], &Registry::PROGRAM_ID)
+    }
+
+    /// The address of the audit log of the deployment, which records admin changes
+    pub fn find_deployment_address() -> (Pubkey, u8) {
+        Self::find_address(&config_address())
+    }
+
+    /// Deserializes audit log account data, including the discriminator. Returns `None` if the data
+    /// isn't an audit log. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+
+    /// The entries still in the log, from oldest to newest. Once more than 64 entries have been
+    /// appended, only the most recent 64 are kept.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &AuditEntry> + ExactSizeIterator {
+        let len = self.head.min(MAX_ITEMS as u64);
+        let oldest = self.head - len;
+        (0..len as usize)
+            .map(move |i| &self.entries[((oldest + i as u64) % MAX_ITEMS as u64) as usize])
+    }
+}
+
+/// Builds the segmenter registry instruction that creates the audit log of `registry` ahead of the
+/// first change that is logged, which otherwise creates it. `payer` must sign.
+pub fn create_audit_log_instruction(registry: &Pubkey, payer: &Pubkey) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [251, 248, 72, 101, 128, 23, 80, 101];
+
+    let (audit_log, _) = AuditLog::find_address(registry);
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(audit_log, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: DISCRIMINATOR.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_with(count: u64) -> AuditLog {
+        let mut log = AuditLog::zeroed();
+        for slot in 0..count {
+            log.entries[(slot % MAX_ITEMS as u64) as usize] = AuditEntry {
+                slot,
+                action: (slot % 2) as u8,
+                ..AuditEntry::zeroed()
+            };
+        }
+        log.head = count;
+        log
+    }
+
+    #[test]
+    fn test_iter() {
+        // Not yet wrapped
+        let log = log_with(3);
+        let slots = log.iter().map(|entry| entry.slot).collect::<Vec<_>>();
+        assert_eq!(slots, [0, 1, 2]);
+        assert_eq!(
+            log.iter().next().and_then(AuditEntry::action),
+            Some(AuditAction::AddSegmenter)
+        );
+
+        // Wrapped, so the oldest entries were overwritten
+        let log = log_with(MAX_ITEMS as u64 + 5);
+        let slots = log.iter().map(|entry| entry.slot).collect::<Vec<_>>();
+        assert_eq!(slots, (5..MAX_ITEMS as u64 + 5).collect::<Vec<_>>());
+        assert_eq!(log.iter().next_back().map(|entry| entry.slot), Some(68));
+
+        // Round trip through account data
+        let mut data = AuditLog::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&log));
+        let log = AuditLog::try_deserialize(&data).unwrap();
+        assert_eq!(log.iter().len(), MAX_ITEMS);
+    }
+
+    #[test]
+    fn test_authority_actions() {
+        let actions = [2, 3, 4].map(|action| {
+            AuditEntry {
+                action,
+                ..AuditEntry::zeroed()
+            }
+            .action()
+        });
+        assert_eq!(
+            actions,
+            [
+                Some(AuditAction::ChangeAdmin),
+                Some(AuditAction::SetDirectoryAuthority),
+                Some(AuditAction::SetMetadataAuthority),
+            ]
+        );
+        assert_eq!(
+            AuditLog::find_deployment_address(),
+            AuditLog::find_address(&config_address())
+        );
+    }
+
+    #[test]
+    fn test_unknown_action() {
+        let entry = AuditEntry {
+            action: u8::MAX,
+            ..AuditEntry::zeroed()
+        };
+        assert_eq!(entry.action(), None);
+    }
+}
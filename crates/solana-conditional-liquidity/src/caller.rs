@@ -0,0 +1,372 @@
+use solana_program::{instruction::TRANSACTION_LEVEL_STACK_HEIGHT, pubkey::Pubkey};
+
+use crate::{
+    introspection::{borrow_instructions_sysvar, InstructionsSysvar},
+    verify_origin, AccountView, Origin, VerifyOriginResult,
+};
+
+/// How the program was reached from the top-level instruction of the transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallerRoute {
+    /// The top-level instruction invokes the program directly
+    Direct,
+    /// The program was invoked through CPI by an allow-listed router, whose top-level instruction
+    /// the segmenter signed
+    AllowedRouter(Pubkey),
+    /// The program was invoked through CPI by a program that isn't an allow-listed router, or by a
+    /// top-level instruction the segmenter didn't sign
+    Untrusted,
+}
+
+/// The top-level caller of an invocation, found through the Instructions sysvar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallerContext {
+    /// The program of the currently executing top-level instruction
+    pub top_level_program: Pubkey,
+    /// The invocation stack height of the program, [`TRANSACTION_LEVEL_STACK_HEIGHT`] if it was
+    /// invoked by the top-level instruction
+    pub stack_height: usize,
+    pub route: CallerRoute,
+}
+
+impl CallerContext {
+    /// Whether the segmenter's signature was meant for this program, i.e. it was invoked directly
+    /// or through an allow-listed router
+    pub fn is_trusted(&self) -> bool {
+        self.route != CallerRoute::Untrusted
+    }
+}
+
+/// Reports the top-level program that invoked `program_id`, and whether the top-level instruction
+/// was signed by `segmenter` and targets `program_id` directly or one of `allowed_routers`. Pass
+/// the program's [`get_stack_height`](solana_program::instruction::get_stack_height) as
+/// `stack_height`. Returns `None` if `instructions_sysvar` isn't the Instructions sysvar account.
+pub fn caller_context(
+    program_id: &Pubkey,
+    segmenter: &Pubkey,
+    stack_height: usize,
+    instructions_sysvar: &impl AccountView,
+    allowed_routers: &[Pubkey],
+) -> Option<CallerContext> {
+    let instructions_data = borrow_instructions_sysvar(instructions_sysvar)?;
+    let instructions = InstructionsSysvar::new(&instructions_data)?;
+    let top_level = instructions.get(instructions.current_index()?)?;
+
+    let route = if stack_height <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        if top_level.program_id == *program_id {
+            CallerRoute::Direct
+        } else {
+            CallerRoute::Untrusted
+        }
+    } else if allowed_routers.contains(&top_level.program_id) && top_level.is_signer(segmenter) {
+        CallerRoute::AllowedRouter(top_level.program_id)
+    } else {
+        CallerRoute::Untrusted
+    };
+
+    Some(CallerContext {
+        top_level_program: top_level.program_id,
+        stack_height,
+        route,
+    })
+}
+
+/// Like [`verify_origin`], but also returns the [`caller_context`] of the invocation, so that a
+/// DEX reached through an aggregator's CPI can tell whether the segmenter's signature was meant
+/// for it. The context is `None` if the invocation wasn't signed by a segmenter or
+/// `instructions_sysvar` isn't the Instructions sysvar account.
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::{verify_origin_with_caller, Origin, VerifyOriginResult};
+/// use solana_program::{
+///     account_info::AccountInfo, instruction::get_stack_height, pubkey, pubkey::Pubkey,
+/// };
+///
+/// const ROUTERS: &[Pubkey] = &[pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4")];
+///
+/// fn handler(
+///     program_id: &Pubkey,
+///     registry: &AccountInfo<'_>,
+///     segmenter: &AccountInfo<'_>,
+///     instructions_sysvar: &AccountInfo<'_>,
+///     claimed_origin: Origin,
+/// ) {
+///     let (result, caller) = verify_origin_with_caller(
+///         claimed_origin,
+///         program_id,
+///         registry,
+///         segmenter,
+///         get_stack_height(),
+///         instructions_sysvar,
+///         ROUTERS,
+///     );
+///     let VerifyOriginResult::InvokedBySegmenter(origin) = result else {
+///         // The invocation wasn't signed by a segmenter
+///         return;
+///     };
+///     if !caller.is_some_and(|caller| caller.is_trusted()) {
+///         // The segmenter's signature may have been meant for another program
+///         return;
+///     }
+///
+///     // Branch on the origin here
+/// }
+/// ```
+pub fn verify_origin_with_caller(
+    claimed_origin: Origin,
+    program_id: &Pubkey,
+    registry: &impl AccountView,
+    segmenter: &impl AccountView,
+    stack_height: usize,
+    instructions_sysvar: &impl AccountView,
+    allowed_routers: &[Pubkey],
+) -> (VerifyOriginResult, Option<CallerContext>) {
+    let result = verify_origin(claimed_origin, registry, segmenter);
+    if result == VerifyOriginResult::NotInvokedBySegmenter {
+        return (result, None);
+    }
+
+    let caller = caller_context(
+        program_id,
+        segmenter.key(),
+        stack_height,
+        instructions_sysvar,
+        allowed_routers,
+    );
+    (result, caller)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::{
+        account_info::AccountInfo,
+        instruction::{AccountMeta, Instruction},
+        sysvar::{
+            self,
+            instructions::{
+                self, construct_instructions_data, store_current_index, BorrowedAccountMeta,
+                BorrowedInstruction,
+            },
+        },
+    };
+
+    use crate::{
+        origin_identity, test_utils::SEGMENTER, Registry, ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+    };
+
+    use super::*;
+
+    /// Serializes the Instructions sysvar for a transaction with the given instructions, executing
+    /// the instruction at `current_index`
+    fn instructions_data(instructions: &[Instruction], current_index: u16) -> Vec<u8> {
+        let borrowed = instructions
+            .iter()
+            .map(|instruction| BorrowedInstruction {
+                program_id: &instruction.program_id,
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| BorrowedAccountMeta {
+                        pubkey: &meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                data: &instruction.data,
+            })
+            .collect::<Vec<_>>();
+        let mut data = construct_instructions_data(&borrowed);
+        store_current_index(&mut data, current_index);
+        data
+    }
+
+    #[test]
+    fn test_caller_context() {
+        let dex = Pubkey::new_unique();
+        let router = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let allowed_routers = [router];
+
+        let signed_by_segmenter = |program_id| Instruction {
+            program_id,
+            accounts: vec![AccountMeta::new_readonly(SEGMENTER, true)],
+            data: vec![],
+        };
+        let context = |instructions: &[Instruction], current_index, stack_height| {
+            let mut sysvar_data = instructions_data(instructions, current_index);
+            let mut sysvar_lamports = 0;
+            let instructions_sysvar = AccountInfo {
+                key: &instructions::ID,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut sysvar_lamports)),
+                data: Rc::new(RefCell::new(&mut sysvar_data)),
+                owner: &sysvar::ID,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+            caller_context(
+                &dex,
+                &SEGMENTER,
+                stack_height,
+                &instructions_sysvar,
+                &allowed_routers,
+            )
+            .map(|context| (context.top_level_program, context.route))
+        };
+
+        // Invoked directly
+        let transaction = [signed_by_segmenter(other), signed_by_segmenter(dex)];
+        assert_eq!(
+            context(&transaction, 1, 1),
+            Some((dex, CallerRoute::Direct))
+        );
+
+        // Invoked through an allow-listed router, whose instruction the segmenter signed
+        let transaction = [signed_by_segmenter(router)];
+        assert_eq!(
+            context(&transaction, 0, 2),
+            Some((router, CallerRoute::AllowedRouter(router)))
+        );
+
+        // The segmenter didn't sign the router's instruction
+        let unsigned = Instruction {
+            program_id: router,
+            accounts: vec![AccountMeta::new_readonly(SEGMENTER, false)],
+            data: vec![],
+        };
+        assert_eq!(
+            context(&[unsigned], 0, 2),
+            Some((router, CallerRoute::Untrusted))
+        );
+
+        // Invoked through a program that isn't an allow-listed router
+        let transaction = [signed_by_segmenter(other)];
+        assert_eq!(
+            context(&transaction, 0, 3),
+            Some((other, CallerRoute::Untrusted))
+        );
+
+        // The current index is out of bounds
+        assert_eq!(context(&transaction, 1, 1), None);
+    }
+
+    #[test]
+    fn test_verify_origin_with_caller() {
+        let dex = Pubkey::new_unique();
+
+        let mut registry = Registry {
+            registered_segmenters: [Pubkey::default(); 64],
+        };
+        registry.registered_segmenters[63] = SEGMENTER;
+        let mut registry_data = Registry::DISCRIMINATOR.to_vec();
+        registry_data.extend_from_slice(bytemuck::bytes_of(&registry));
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut segmenter_lamports = 0;
+        let mut segmenter_data = [];
+        let segmenter = AccountInfo {
+            key: &SEGMENTER,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+            data: Rc::new(RefCell::new(&mut segmenter_data)),
+            owner: &solana_program::system_program::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut sysvar_data = instructions_data(
+            &[Instruction {
+                program_id: dex,
+                accounts: vec![AccountMeta::new_readonly(SEGMENTER, true)],
+                data: vec![],
+            }],
+            0,
+        );
+        let mut sysvar_lamports = 0;
+        let instructions_sysvar = AccountInfo {
+            key: &instructions::ID,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut sysvar_lamports)),
+            data: Rc::new(RefCell::new(&mut sysvar_data)),
+            owner: &sysvar::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        assert_eq!(
+            verify_origin_with_caller(
+                origin_identity::DFLOW_WEB,
+                &dex,
+                &registry,
+                &segmenter,
+                1,
+                &instructions_sysvar,
+                &[],
+            ),
+            (
+                VerifyOriginResult::InvokedBySegmenter(origin_identity::DFLOW_WEB),
+                Some(CallerContext {
+                    top_level_program: dex,
+                    stack_height: 1,
+                    route: CallerRoute::Direct,
+                })
+            )
+        );
+
+        // Not signed by the segmenter
+        let unsigned_segmenter = AccountInfo {
+            is_signer: false,
+            ..segmenter.clone()
+        };
+        assert_eq!(
+            verify_origin_with_caller(
+                origin_identity::DFLOW_WEB,
+                &dex,
+                &registry,
+                &unsigned_segmenter,
+                1,
+                &instructions_sysvar,
+                &[],
+            ),
+            (VerifyOriginResult::NotInvokedBySegmenter, None)
+        );
+
+        // Not the Instructions sysvar
+        let not_sysvar = AccountInfo {
+            key: &Pubkey::new_unique(),
+            ..instructions_sysvar.clone()
+        };
+        assert_eq!(
+            verify_origin_with_caller(
+                origin_identity::DFLOW_WEB,
+                &dex,
+                &registry,
+                &segmenter,
+                1,
+                &not_sysvar,
+                &[],
+            ),
+            (
+                VerifyOriginResult::InvokedBySegmenter(origin_identity::DFLOW_WEB),
+                None
+            )
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::{origin_identity, verify_origin, AccountView, FlowScore, Origin, VerifyOriginResult};
+
+const FLAG_EXPIRY_SLOT: u8 = 1 << 0;
+const FLAG_REFERRAL: u8 = 1 << 1;
+const FLAG_SCORE: u8 = 1 << 2;
+const KNOWN_FLAGS: u8 = FLAG_EXPIRY_SLOT | FLAG_REFERRAL | FLAG_SCORE;
+
+/// A claimed origin as carried in instruction data, shared by frontends, segmenters and DEXs.
+///
+/// The encoding is:
+///
+/// | Bytes | Field                                               |
+/// |-------|-----------------------------------------------------|
+/// | 1     | Version, currently [`OriginClaim::VERSION`]         |
+/// | 2     | Origin, little-endian `u16`                         |
+/// | 1     | Flags: bit 0 = expiry slot present, bit 1 = referral present, bit 2 = score present. Other bits must be zero. |
+/// | 8     | Expiry slot, little-endian `u64`, if present        |
+/// | 32    | Referral key, if present                            |
+/// | 1     | Flow score, if present                              |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OriginClaim {
+    pub origin: Origin,
+    /// The last slot in which the claim is valid
+    pub expiry_slot: Option<u64>,
+    /// The referral or frontend fee account the claim was made for
+    pub referral: Option<Pubkey>,
+    /// The flow score the segmenter attested for the invocation
+    pub score: Option<FlowScore>,
+}
+
+impl OriginClaim {
+    pub const VERSION: u8 = 1;
+    /// The length of a claim without any optional fields
+    pub const MIN_LEN: usize = 4;
+    /// The length of a claim with all optional fields
+    pub const MAX_LEN: usize = Self::MIN_LEN + 8 + 32 + 1;
+
+    pub fn new(origin: Origin) -> Self {
+        Self {
+            origin,
+            expiry_slot: None,
+            referral: None,
+            score: None,
+        }
+    }
+
+    /// The number of bytes [`OriginClaim::encode`] produces
+    pub fn encoded_len(&self) -> usize {
+        Self::MIN_LEN
+            + self.expiry_slot.map_or(0, |_| 8)
+            + self.referral.map_or(0, |_| 32)
+            + self.score.map_or(0, |_| 1)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut flags = 0;
+        if self.expiry_slot.is_some() {
+            flags |= FLAG_EXPIRY_SLOT;
+        }
+        if self.referral.is_some() {
+            flags |= FLAG_REFERRAL;
+        }
+        if self.score.is_some() {
+            flags |= FLAG_SCORE;
+        }
+
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.push(Self::VERSION);
+        bytes.extend_from_slice(&self.origin.0.to_le_bytes());
+        bytes.push(flags);
+        if let Some(expiry_slot) = self.expiry_slot {
+            bytes.extend_from_slice(&expiry_slot.to_le_bytes());
+        }
+        if let Some(referral) = self.referral {
+            bytes.extend_from_slice(referral.as_ref());
+        }
+        if let Some(score) = self.score {
+            bytes.push(score.0);
+        }
+        bytes
+    }
+
+    /// Decodes a claim from the start of `bytes`, returning the claim and the bytes after it. Never
+    /// panics, regardless of the contents of `bytes`.
+    pub fn decode(bytes: &[u8]) -> Result<(Self, &[u8]), OriginClaimError> {
+        let (&version, rest) = bytes.split_first().ok_or(OriginClaimError::Truncated)?;
+        if version != Self::VERSION {
+            return Err(OriginClaimError::UnsupportedVersion(version));
+        }
+
+        let (origin, rest) = take::<2>(rest)?;
+        let (&flags, rest) = rest.split_first().ok_or(OriginClaimError::Truncated)?;
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(OriginClaimError::UnknownFlags(flags));
+        }
+
+        let mut claim = Self::new(Origin(u16::from_le_bytes(origin)));
+        let mut rest = rest;
+        if flags & FLAG_EXPIRY_SLOT != 0 {
+            let (expiry_slot, tail) = take::<8>(rest)?;
+            claim.expiry_slot = Some(u64::from_le_bytes(expiry_slot));
+            rest = tail;
+        }
+        if flags & FLAG_REFERRAL != 0 {
+            let (referral, tail) = take::<32>(rest)?;
+            claim.referral = Some(Pubkey::new_from_array(referral));
+            rest = tail;
+        }
+        if flags & FLAG_SCORE != 0 {
+            let (&score, tail) = rest.split_first().ok_or(OriginClaimError::Truncated)?;
+            claim.score = Some(FlowScore(score));
+            rest = tail;
+        }
+        Ok((claim, rest))
+    }
+
+    /// Whether the claim has expired as of `current_slot`
+    pub fn is_expired(&self, current_slot: u64) -> bool {
+        self.expiry_slot
+            .is_some_and(|expiry_slot| current_slot > expiry_slot)
+    }
+}
+
+fn take<const N: usize>(bytes: &[u8]) -> Result<([u8; N], &[u8]), OriginClaimError> {
+    if bytes.len() < N {
+        return Err(OriginClaimError::Truncated);
+    }
+    let (head, rest) = bytes.split_at(N);
+    let mut array = [0; N];
+    array.copy_from_slice(head);
+    Ok((array, rest))
+}
+
+/// The reason an origin claim could not be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OriginClaimError {
+    /// The claim's version byte isn't supported by this version of the crate
+    UnsupportedVersion(u8),
+    /// The claim has flag bits set that this version of the crate doesn't know about
+    UnknownFlags(u8),
+    /// The bytes end before the claim does
+    Truncated,
+}
+
+impl std::fmt::Display for OriginClaimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported origin claim version {version}")
+            }
+            Self::UnknownFlags(flags) => write!(f, "origin claim has unknown flags {flags:#04x}"),
+            Self::Truncated => f.write_str("origin claim is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for OriginClaimError {}
+
+/// Checks whether the invocation was signed by a segmenter and verifies the claimed origin, like
+/// [`verify_origin`]. A claim that expired before `current_slot` can't be trusted, so its origin is
+/// [`origin_identity::UNKNOWN`].
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::{verify_origin_claim, OriginClaim, VerifyOriginResult};
+/// use solana_program::{account_info::AccountInfo, clock::Clock, sysvar::Sysvar};
+///
+/// fn handler(registry: &AccountInfo<'_>, segmenter: &AccountInfo<'_>, instruction_data: &[u8]) {
+///     let Ok((claim, _rest)) = OriginClaim::decode(instruction_data) else {
+///         // The instruction data doesn't start with an origin claim
+///         return;
+///     };
+///     let current_slot = Clock::get().unwrap().slot;
+///
+///     let VerifyOriginResult::InvokedBySegmenter(origin) =
+///         verify_origin_claim(&claim, current_slot, registry, segmenter)
+///     else {
+///         // The invocation wasn't signed by a segmenter
+///         return;
+///     };
+///
+///     // Branch on the origin here
+/// }
+/// ```
+pub fn verify_origin_claim(
+    claim: &OriginClaim,
+    current_slot: u64,
+    registry: &impl AccountView,
+    segmenter: &impl AccountView,
+) -> VerifyOriginResult {
+    match verify_origin(claim.origin, registry, segmenter) {
+        VerifyOriginResult::InvokedBySegmenter(_) if claim.is_expired(current_slot) => {
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN)
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::{account_info::AccountInfo, system_program};
+
+    use crate::{
+        test_utils::{registry_data, SEGMENTER},
+        Registry, ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_origin_claim_round_trip() {
+        let referral = Pubkey::new_unique();
+        let claims = [
+            OriginClaim::new(origin_identity::UNKNOWN),
+            OriginClaim::new(Origin(2)),
+            OriginClaim {
+                expiry_slot: Some(u64::MAX),
+                ..OriginClaim::new(Origin(u16::MAX))
+            },
+            OriginClaim {
+                referral: Some(referral),
+                ..OriginClaim::new(Origin(4))
+            },
+            OriginClaim {
+                score: Some(FlowScore(0)),
+                ..OriginClaim::new(Origin(5))
+            },
+            OriginClaim {
+                origin: Origin(0x1234),
+                expiry_slot: Some(300_000_000),
+                referral: Some(referral),
+                score: Some(FlowScore::MAX),
+            },
+        ];
+
+        for claim in claims {
+            let mut bytes = claim.encode();
+            assert_eq!(bytes.len(), claim.encoded_len());
+            assert_eq!(OriginClaim::decode(&bytes), Ok((claim, &[][..])));
+
+            // Trailing instruction data is returned
+            bytes.extend_from_slice(&[7, 8, 9]);
+            assert_eq!(OriginClaim::decode(&bytes), Ok((claim, &[7, 8, 9][..])));
+        }
+    }
+
+    #[test]
+    fn test_origin_claim_encoding() {
+        let claim = OriginClaim {
+            origin: Origin(0x1234),
+            expiry_slot: Some(0x0102030405060708),
+            referral: Some(Pubkey::new_from_array([0xaa; 32])),
+            score: Some(FlowScore(200)),
+        };
+        let mut expected = vec![1, 0x34, 0x12, 0b111, 8, 7, 6, 5, 4, 3, 2, 1];
+        expected.extend_from_slice(&[0xaa; 32]);
+        expected.push(200);
+        assert_eq!(claim.encode(), expected);
+        assert_eq!(claim.encoded_len(), OriginClaim::MAX_LEN);
+
+        assert_eq!(OriginClaim::new(Origin(2)).encode(), [1, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_origin_claim_decode_invalid() {
+        assert_eq!(OriginClaim::decode(&[]), Err(OriginClaimError::Truncated));
+        assert_eq!(
+            OriginClaim::decode(&[2, 1, 0, 0]),
+            Err(OriginClaimError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            OriginClaim::decode(&[1, 1, 0, 0b1000]),
+            Err(OriginClaimError::UnknownFlags(0b1000))
+        );
+
+        // Every strict prefix of a full claim is truncated
+        let bytes = OriginClaim {
+            origin: Origin(1),
+            expiry_slot: Some(1),
+            referral: Some(Pubkey::new_unique()),
+            score: Some(FlowScore(1)),
+        }
+        .encode();
+        for len in 0..bytes.len() {
+            assert_eq!(
+                OriginClaim::decode(&bytes[..len]),
+                Err(OriginClaimError::Truncated)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_origin_claim() {
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut segmenter_lamports = 0;
+        let mut segmenter_data = [];
+        let segmenter = AccountInfo {
+            key: &SEGMENTER,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+            data: Rc::new(RefCell::new(&mut segmenter_data)),
+            owner: &system_program::id(),
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let claim = OriginClaim {
+            expiry_slot: Some(100),
+            ..OriginClaim::new(origin_identity::DFLOW_WEB)
+        };
+
+        // Not expired, including the expiry slot itself
+        for current_slot in [0, 100] {
+            assert_eq!(
+                verify_origin_claim(&claim, current_slot, &registry, &segmenter),
+                VerifyOriginResult::InvokedBySegmenter(origin_identity::DFLOW_WEB)
+            );
+        }
+
+        // Expired
+        assert_eq!(
+            verify_origin_claim(&claim, 101, &registry, &segmenter),
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN)
+        );
+
+        // No expiry
+        let claim = OriginClaim::new(origin_identity::DFLOW_WEB);
+        assert_eq!(
+            verify_origin_claim(&claim, u64::MAX, &registry, &segmenter),
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::DFLOW_WEB)
+        );
+
+        // Not signed by the segmenter
+        let unsigned_segmenter = AccountInfo {
+            is_signer: false,
+            ..segmenter.clone()
+        };
+        assert_eq!(
+            verify_origin_claim(&claim, 0, &registry, &unsigned_segmenter),
+            VerifyOriginResult::NotInvokedBySegmenter
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{AuditLog, Registry};
+
+const MAX_ITEMS: usize = 32;
+
+/// A registry listed in the directory
+#[derive(Pod, Zeroable, Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct DirectoryEntry {
+    pub registry: Pubkey,
+    /// The account that created the registry
+    pub authority: Pubkey,
+    /// The name of the registry, padded with zero bytes. Use [`DirectoryEntry::name`] for the
+    /// name as a string.
+    pub name: [u8; 32],
+    /// The slot the registry was created at
+    pub created_at_slot: u64,
+}
+
+impl DirectoryEntry {
+    /// The name of the registry, empty if it isn't valid UTF-8
+    pub fn name(&self) -> &str {
+        padded_str(&self.name)
+    }
+}
+
+/// The string in `padded` without its zero padding, empty if it isn't valid UTF-8
+pub(crate) fn padded_str(padded: &[u8]) -> &str {
+    let len = padded
+        .iter()
+        .rposition(|&byte| byte != 0)
+        .map_or(0, |i| i + 1);
+    std::str::from_utf8(&padded[..len]).unwrap_or_default()
+}
+
+/// The address of the deployment's config account
+pub(crate) fn config_address() -> Pubkey {
+    const CONFIG_SEED: &[u8] = b"config";
+    Pubkey::find_program_address(&[CONFIG_SEED], &Registry::PROGRAM_ID).0
+}
+
+/// A page of the directory of registries of the segmenter registry program, which `create_registry`
+/// appends to. Pages are numbered from 0 and a page is only created once the previous one is full,
+/// so the directory is listed by reading pages in order until one doesn't exist.
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct DirectoryPage {
+    pub page_index: u32,
+    /// The number of entries in use
+    pub len: u32,
+    pub entries: [DirectoryEntry; MAX_ITEMS],
+}
+
+impl DirectoryPage {
+    pub const DISCRIMINATOR: [u8; 8] = [121, 39, 152, 187, 125, 93, 124, 188];
+    pub const SEED: &'static [u8] = b"directory";
+    pub const MAX_NAME_LEN: usize = 32;
+
+    /// The address of the directory page with index `page_index`
+    pub fn find_address(page_index: u32) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, &page_index.to_le_bytes()],
+            &Registry::PROGRAM_ID,
+        )
+    }
+
+    /// Deserializes directory page account data, including the discriminator. Returns `None` if
+    /// the data isn't a directory page. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+
+    /// The entries in use
+    pub fn entries(&self) -> &[DirectoryEntry] {
+        let len = (self.len as usize).min(MAX_ITEMS);
+        &self.entries[..len]
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len as usize >= MAX_ITEMS
+    }
+}
+
+/// Lists the registries in the directory, given the account data of the directory pages in page
+/// order, e.g. as fetched with `getMultipleAccounts` for the addresses of
+/// [`DirectoryPage::find_address`]. Stops at the first account that isn't a directory page.
+pub fn list_registries<'a>(pages: impl IntoIterator<Item = &'a [u8]>) -> Vec<DirectoryEntry> {
+    pages
+        .into_iter()
+        .map_while(DirectoryPage::try_deserialize)
+        .flat_map(|page| page.entries().to_vec())
+        .collect()
+}
+
+/// Builds the segmenter registry instruction that creates `registry` and lists it in the directory
+/// under `name`, which must be at most [`DirectoryPage::MAX_NAME_LEN`] bytes. `page_index` must be
+/// the index of the last directory page, or the index of the next page if the last one is full or
+/// there are no pages yet, in which case the page is created. `registry` and `payer` must sign. If
+/// the deployment's creation policy requires a fee, pass its `treasury`, which the fee is paid to
+/// from `payer`.
+pub fn create_registry_instruction(
+    registry: &Pubkey,
+    payer: &Pubkey,
+    page_index: u32,
+    name: &str,
+    treasury: Option<&Pubkey>,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [210, 219, 233, 49, 251, 19, 135, 13];
+
+    let (directory_page, _) = DirectoryPage::find_address(page_index);
+    // Anchor treats the program id as a missing optional account
+    let previous_page = match page_index.checked_sub(1) {
+        Some(previous_index) => DirectoryPage::find_address(previous_index).0,
+        None => Registry::PROGRAM_ID,
+    };
+    let treasury = match treasury {
+        Some(treasury) => AccountMeta::new(*treasury, false),
+        None => AccountMeta::new_readonly(Registry::PROGRAM_ID, false),
+    };
+    let mut data = DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    data.extend_from_slice(name.as_bytes());
+    data.extend_from_slice(&page_index.to_le_bytes());
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*registry, true),
+            AccountMeta::new(directory_page, false),
+            AccountMeta::new_readonly(previous_page, false),
+            AccountMeta::new(AuditLog::find_address(registry).0, false),
+            AccountMeta::new_readonly(config_address(), false),
+            AccountMeta::new(*payer, true),
+            treasury,
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+/// Builds the segmenter registry instruction that creates the directory page with index
+/// `page_index`, which is only allowed once the previous page is full. `payer` must sign.
+pub fn create_directory_page_instruction(page_index: u32, payer: &Pubkey) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [157, 99, 132, 209, 98, 104, 157, 104];
+
+    let (directory_page, _) = DirectoryPage::find_address(page_index);
+    // Anchor treats the program id as a missing optional account
+    let previous_page = match page_index.checked_sub(1) {
+        Some(previous_index) => DirectoryPage::find_address(previous_index).0,
+        None => Registry::PROGRAM_ID,
+    };
+    let mut data = DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&page_index.to_le_bytes());
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(directory_page, false),
+            AccountMeta::new_readonly(previous_page, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page_data(page_index: u32, names: &[&str]) -> Vec<u8> {
+        let mut page = DirectoryPage::zeroed();
+        page.page_index = page_index;
+        for (entry, name) in page.entries.iter_mut().zip(names) {
+            entry.registry = Pubkey::new_unique();
+            entry.name[..name.len()].copy_from_slice(name.as_bytes());
+        }
+        page.len = names.len() as u32;
+
+        let mut data = DirectoryPage::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&page));
+        data
+    }
+
+    #[test]
+    fn test_list_registries() {
+        let first = page_data(0, &["dflow"; MAX_ITEMS]);
+        let second = page_data(1, &["phantom", "a-name-that-uses-all-32-bytes-ok"]);
+
+        let registries = list_registries([&first[..], &second[..], &[]]);
+        assert_eq!(registries.len(), MAX_ITEMS + 2);
+        assert_eq!(registries[0].name(), "dflow");
+        assert_eq!(registries[MAX_ITEMS].name(), "phantom");
+        assert_eq!(
+            registries[MAX_ITEMS + 1].name(),
+            "a-name-that-uses-all-32-bytes-ok"
+        );
+
+        // Listing stops at the first missing page
+        assert_eq!(list_registries([&[][..], &second[..]]), vec![]);
+    }
+
+    #[test]
+    fn test_create_registry_instruction() {
+        let registry = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+
+        let instruction = create_registry_instruction(&registry, &payer, 0, "dflow", None);
+        assert_eq!(&instruction.data[8..], b"\x05\0\0\0dflow\0\0\0\0");
+        assert_eq!(instruction.accounts[2].pubkey, Registry::PROGRAM_ID);
+        assert_eq!(instruction.accounts[6].pubkey, Registry::PROGRAM_ID);
+
+        let instruction =
+            create_registry_instruction(&registry, &payer, 1, "dflow", Some(&treasury));
+        assert_eq!(&instruction.data[17..], 1u32.to_le_bytes());
+        assert_eq!(
+            instruction.accounts[1].pubkey,
+            DirectoryPage::find_address(1).0
+        );
+        assert_eq!(
+            instruction.accounts[2].pubkey,
+            DirectoryPage::find_address(0).0
+        );
+        assert_eq!(instruction.accounts[6].pubkey, treasury);
+        assert!(instruction.accounts[6].is_writable);
+    }
+
+    #[test]
+    fn test_entries_never_panic() {
+        let mut page = DirectoryPage::zeroed();
+        page.len = u32::MAX;
+        assert_eq!(page.entries().len(), MAX_ITEMS);
+        assert!(page.is_full());
+    }
+}
@@ -0,0 +1,425 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{ed25519_program, instruction::Instruction, pubkey::Pubkey};
+
+use crate::{
+    introspection::{ed25519_instruction_data, find_ed25519_signature},
+    origin_identity, verify_origin_claim, AccountView, Origin, OriginClaim, Registry,
+    VerifyOriginResult,
+};
+
+/// An entry in the origin directory of the segmenter registry program, which registers the key the
+/// frontend an origin identifies signs origin claims with
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct OriginEntry {
+    /// The key the frontend signs origin claims with
+    pub frontend_key: Pubkey,
+    pub origin: Origin,
+}
+
+impl OriginEntry {
+    pub const DISCRIMINATOR: [u8; 8] = [84, 15, 219, 146, 83, 185, 143, 231];
+    pub const SEED: &'static [u8] = b"origin";
+
+    /// The address of the origin directory entry for `origin`
+    pub fn find_address(origin: Origin) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, &origin.0.to_le_bytes()],
+            &Registry::PROGRAM_ID,
+        )
+    }
+
+    /// Deserializes origin entry account data, including the discriminator. Returns `None` if the
+    /// data isn't an origin entry. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+}
+
+impl OriginClaim {
+    /// Prefixes every frontend co-signature message, so that a frontend's co-signatures can't be
+    /// mistaken for signatures over anything else
+    pub const COSIGN_DOMAIN: [u8; 16] = *b"origin-cosign-v1";
+
+    /// The message the frontend signs to co-sign the claim for `user` of the DEX `program_id`:
+    /// [`OriginClaim::COSIGN_DOMAIN`], the program id, the user, then the
+    /// [encoded](OriginClaim::encode) claim. The program id keeps the co-signature from being
+    /// replayed against another DEX.
+    pub fn cosign_message(&self, program_id: &Pubkey, user: &Pubkey) -> Vec<u8> {
+        let mut message = Vec::with_capacity(16 + 32 + 32 + self.encoded_len());
+        message.extend_from_slice(&Self::COSIGN_DOMAIN);
+        message.extend_from_slice(program_id.as_ref());
+        message.extend_from_slice(user.as_ref());
+        message.extend_from_slice(&self.encode());
+        message
+    }
+
+    /// Builds the Ed25519 precompile instruction that verifies the frontend's `signature` over
+    /// [`OriginClaim::cosign_message`]. Add it to the same transaction as the DEX instruction.
+    pub fn cosign_ed25519_instruction(
+        &self,
+        program_id: &Pubkey,
+        user: &Pubkey,
+        frontend_key: &Pubkey,
+        signature: &[u8; 64],
+    ) -> Instruction {
+        Instruction {
+            program_id: ed25519_program::ID,
+            accounts: vec![],
+            data: ed25519_instruction_data(
+                frontend_key,
+                signature,
+                &self.cosign_message(program_id, user),
+            ),
+        }
+    }
+}
+
+/// Like [`verify_origin_claim`], but the claimed origin is only honored if the frontend registered
+/// for it in the origin directory also co-signed the claim for `user` of the DEX `program_id`,
+/// usually the invoked program's id. The co-signature is verified
+/// by an Ed25519 precompile instruction in the transaction, found through the Instructions sysvar
+/// account. If the origin entry or the co-signature is missing, the origin is
+/// [`origin_identity::UNKNOWN`].
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::{
+///     verify_cosigned_origin_claim, OriginClaim, VerifyOriginResult,
+/// };
+/// use solana_program::{account_info::AccountInfo, clock::Clock, pubkey::Pubkey, sysvar::Sysvar};
+///
+/// fn handler(
+///     program_id: &Pubkey,
+///     user: &AccountInfo<'_>,
+///     registry: &AccountInfo<'_>,
+///     segmenter: &AccountInfo<'_>,
+///     origin_entry: &AccountInfo<'_>,
+///     instructions_sysvar: &AccountInfo<'_>,
+///     claim: &OriginClaim,
+/// ) {
+///     let current_slot = Clock::get().unwrap().slot;
+///     let VerifyOriginResult::InvokedBySegmenter(origin) = verify_cosigned_origin_claim(
+///         claim,
+///         program_id,
+///         user.key,
+///         current_slot,
+///         registry,
+///         segmenter,
+///         origin_entry,
+///         instructions_sysvar,
+///     ) else {
+///         // The invocation wasn't signed by a segmenter
+///         return;
+///     };
+///
+///     // Branch on the origin here
+/// }
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn verify_cosigned_origin_claim(
+    claim: &OriginClaim,
+    program_id: &Pubkey,
+    user: &Pubkey,
+    current_slot: u64,
+    registry: &impl AccountView,
+    segmenter: &impl AccountView,
+    origin_entry: &impl AccountView,
+    instructions_sysvar: &impl AccountView,
+) -> VerifyOriginResult {
+    let origin = match verify_origin_claim(claim, current_slot, registry, segmenter) {
+        VerifyOriginResult::InvokedBySegmenter(origin) if origin != origin_identity::UNKNOWN => {
+            origin
+        }
+        result => return result,
+    };
+
+    if *origin_entry.owner() != Registry::PROGRAM_ID {
+        return VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN);
+    }
+    let Some(entry) = origin_entry
+        .try_borrow_data()
+        .and_then(|data| OriginEntry::try_deserialize(&data))
+    else {
+        return VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN);
+    };
+    if entry.origin != origin {
+        return VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN);
+    }
+
+    let message = claim.cosign_message(program_id, user);
+    let is_cosigned = find_ed25519_signature(instructions_sysvar, |public_key, signed| {
+        (public_key == entry.frontend_key && signed == message).then_some(())
+    })
+    .is_some();
+    if !is_cosigned {
+        return VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN);
+    }
+
+    VerifyOriginResult::InvokedBySegmenter(origin)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::{
+        account_info::AccountInfo,
+        system_program,
+        sysvar::{
+            self,
+            instructions::{self, construct_instructions_data, BorrowedInstruction},
+        },
+    };
+
+    use crate::{
+        test_utils::{registry_data, SEGMENTER},
+        ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+    };
+
+    use super::*;
+
+    fn origin_entry_data(entry: &OriginEntry) -> Vec<u8> {
+        let mut data = OriginEntry::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(entry));
+        data
+    }
+
+    /// Serializes the Instructions sysvar for a transaction with the given instructions
+    fn instructions_data(instructions: &[Instruction]) -> Vec<u8> {
+        let borrowed = instructions
+            .iter()
+            .map(|instruction| BorrowedInstruction {
+                program_id: &instruction.program_id,
+                accounts: vec![],
+                data: &instruction.data,
+            })
+            .collect::<Vec<_>>();
+        construct_instructions_data(&borrowed)
+    }
+
+    #[test]
+    fn test_origin_entry_deserialize() {
+        let entry = OriginEntry {
+            frontend_key: Pubkey::new_unique(),
+            origin: Origin(3),
+        };
+        let data = origin_entry_data(&entry);
+        assert_eq!(data.len(), 8 + 32 + 2);
+
+        let deserialized = OriginEntry::try_deserialize(&data).unwrap();
+        assert_eq!(deserialized.frontend_key, entry.frontend_key);
+        assert_eq!(deserialized.origin, entry.origin);
+
+        assert!(OriginEntry::try_deserialize(&data[..data.len() - 1]).is_none());
+        assert!(OriginEntry::try_deserialize(&registry_data()).is_none());
+    }
+
+    #[test]
+    fn test_verify_cosigned_origin_claim() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let frontend_key = Pubkey::new_unique();
+        let claim = OriginClaim {
+            expiry_slot: Some(100),
+            ..OriginClaim::new(Origin(3))
+        };
+
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut segmenter_lamports = 0;
+        let mut segmenter_data = [];
+        let segmenter = AccountInfo {
+            key: &SEGMENTER,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+            data: Rc::new(RefCell::new(&mut segmenter_data)),
+            owner: &system_program::id(),
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let (origin_entry_address, _) = OriginEntry::find_address(claim.origin);
+        let verify = |claim: &OriginClaim,
+                      current_slot: u64,
+                      entry: &OriginEntry,
+                      owner: &Pubkey,
+                      instructions: &[Instruction]| {
+            let mut entry_data = origin_entry_data(entry);
+            let mut entry_lamports = 0;
+            let origin_entry = AccountInfo {
+                key: &origin_entry_address,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut entry_lamports)),
+                data: Rc::new(RefCell::new(&mut entry_data)),
+                owner,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+
+            let mut sysvar_data = instructions_data(instructions);
+            let mut sysvar_lamports = 0;
+            let instructions_sysvar = AccountInfo {
+                key: &instructions::ID,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut sysvar_lamports)),
+                data: Rc::new(RefCell::new(&mut sysvar_data)),
+                owner: &sysvar::ID,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+
+            verify_cosigned_origin_claim(
+                claim,
+                &program_id,
+                &user,
+                current_slot,
+                &registry,
+                &segmenter,
+                &origin_entry,
+                &instructions_sysvar,
+            )
+        };
+
+        let entry = OriginEntry {
+            frontend_key,
+            origin: claim.origin,
+        };
+        let cosigned =
+            [claim.cosign_ed25519_instruction(&program_id, &user, &frontend_key, &[7; 64])];
+        let unknown = VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN);
+
+        // Co-signed by the registered frontend
+        assert_eq!(
+            verify(&claim, 100, &entry, &Registry::PROGRAM_ID, &cosigned),
+            VerifyOriginResult::InvokedBySegmenter(Origin(3))
+        );
+
+        // Expired
+        assert_eq!(
+            verify(&claim, 101, &entry, &Registry::PROGRAM_ID, &cosigned),
+            unknown
+        );
+
+        // Not co-signed
+        assert_eq!(
+            verify(&claim, 0, &entry, &Registry::PROGRAM_ID, &[]),
+            unknown
+        );
+
+        // Co-signed by another key
+        let other_key =
+            [
+                claim.cosign_ed25519_instruction(
+                    &program_id,
+                    &user,
+                    &Pubkey::new_unique(),
+                    &[7; 64],
+                ),
+            ];
+        assert_eq!(
+            verify(&claim, 0, &entry, &Registry::PROGRAM_ID, &other_key),
+            unknown
+        );
+
+        // Co-signed for another user
+        let other_user = [claim.cosign_ed25519_instruction(
+            &program_id,
+            &Pubkey::new_unique(),
+            &frontend_key,
+            &[7; 64],
+        )];
+        assert_eq!(
+            verify(&claim, 0, &entry, &Registry::PROGRAM_ID, &other_user),
+            unknown
+        );
+
+        // Co-signed for another DEX
+        let other_program = [claim.cosign_ed25519_instruction(
+            &Pubkey::new_unique(),
+            &user,
+            &frontend_key,
+            &[7; 64],
+        )];
+        assert_eq!(
+            verify(&claim, 0, &entry, &Registry::PROGRAM_ID, &other_program),
+            unknown
+        );
+
+        // The co-signed claim differs from the one presented
+        let other_claim = OriginClaim {
+            expiry_slot: Some(200),
+            ..claim
+        };
+        assert_eq!(
+            verify(&other_claim, 0, &entry, &Registry::PROGRAM_ID, &cosigned),
+            unknown
+        );
+
+        // The entry is for another origin
+        let other_origin = OriginEntry {
+            origin: origin_identity::DFLOW_WEB,
+            ..entry
+        };
+        assert_eq!(
+            verify(&claim, 0, &other_origin, &Registry::PROGRAM_ID, &cosigned),
+            unknown
+        );
+
+        // The entry isn't owned by the registry program
+        assert_eq!(
+            verify(&claim, 0, &entry, &system_program::id(), &cosigned),
+            unknown
+        );
+
+        // Not signed by the segmenter
+        let unsigned_segmenter = AccountInfo {
+            is_signer: false,
+            ..segmenter.clone()
+        };
+        let mut entry_data = origin_entry_data(&entry);
+        let mut entry_lamports = 0;
+        let origin_entry = AccountInfo {
+            key: &origin_entry_address,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut entry_lamports)),
+            data: Rc::new(RefCell::new(&mut entry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+        assert_eq!(
+            verify_cosigned_origin_claim(
+                &claim,
+                &program_id,
+                &user,
+                0,
+                &registry,
+                &unsigned_segmenter,
+                &origin_entry,
+                &origin_entry,
+            ),
+            VerifyOriginResult::NotInvokedBySegmenter
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{directory::config_address, AccountView, Registry, SegmenterRejection};
+
+const MAX_ITEMS: usize = 64;
+
+/// The last slot a segmenter sent a heartbeat in
+#[derive(Pod, Zeroable, Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct HeartbeatEntry {
+    pub segmenter: Pubkey,
+    pub last_seen_slot: u64,
+}
+
+/// The heartbeat tracker of a registry, owned by the segmenter registry program, which records the
+/// last slot each segmenter sent a heartbeat in
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct HeartbeatTracker {
+    pub registry: Pubkey,
+    /// Segmenters whose last heartbeat is more than this many slots old are inactive. 0 disables
+    /// staleness detection.
+    pub max_heartbeat_age_slots: u64,
+    pub entries: [HeartbeatEntry; MAX_ITEMS],
+}
+
+impl HeartbeatTracker {
+    pub const DISCRIMINATOR: [u8; 8] = [160, 156, 106, 121, 251, 50, 94, 209];
+    pub const SEED: &'static [u8] = b"heartbeat";
+
+    /// The address of the heartbeat tracker of `registry`
+    pub fn find_address(registry: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, registry.as_ref()], &Registry::PROGRAM_ID)
+    }
+
+    /// Deserializes heartbeat tracker account data, including the discriminator, without copying
+    /// it. Returns `None` if the data isn't a heartbeat tracker or isn't 8-byte aligned, which
+    /// account data always is. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<&Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_from_bytes(data).ok()
+    }
+
+    /// The last slot `segmenter` sent a heartbeat in, or `None` if it never has
+    pub fn last_seen_slot(&self, segmenter: &Pubkey) -> Option<u64> {
+        self.entries
+            .iter()
+            .find(|entry| entry.segmenter == *segmenter && *segmenter != Pubkey::default())
+            .map(|entry| entry.last_seen_slot)
+    }
+
+    /// Whether `segmenter` sent a heartbeat recently enough as of `current_slot`. Always `true` if
+    /// staleness detection is disabled, and `false` if the segmenter never sent a heartbeat
+    /// otherwise.
+    pub fn is_active(&self, segmenter: &Pubkey, current_slot: u64) -> bool {
+        if self.max_heartbeat_age_slots == 0 {
+            return true;
+        }
+        self.last_seen_slot(segmenter)
+            .is_some_and(|last_seen_slot| {
+                current_slot.saturating_sub(last_seen_slot) <= self.max_heartbeat_age_slots
+            })
+    }
+}
+
+/// Builds the segmenter registry instruction that creates the heartbeat tracker of `registry`.
+/// `payer` must sign.
+pub fn create_heartbeat_tracker_instruction(registry: &Pubkey, payer: &Pubkey) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [219, 143, 114, 237, 158, 10, 159, 189];
+
+    let (heartbeat_tracker, _) = HeartbeatTracker::find_address(registry);
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(heartbeat_tracker, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: DISCRIMINATOR.to_vec(),
+    }
+}
+
+/// Builds the segmenter registry instruction that records a heartbeat of `segmenter` in the
+/// current slot. `segmenter` must be registered in `registry` and sign.
+pub fn heartbeat_instruction(registry: &Pubkey, segmenter: &Pubkey) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [202, 104, 56, 6, 240, 170, 63, 134];
+
+    let (heartbeat_tracker, _) = HeartbeatTracker::find_address(registry);
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(heartbeat_tracker, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new_readonly(*segmenter, true),
+        ],
+        data: DISCRIMINATOR.to_vec(),
+    }
+}
+
+/// Builds the segmenter registry instruction that sets the maximum heartbeat age of `registry`'s
+/// segmenters, or disables staleness detection if `max_heartbeat_age_slots` is 0. `admin` must be
+/// the deployment's admin and sign.
+pub fn set_max_heartbeat_age_instruction(
+    registry: &Pubkey,
+    admin: &Pubkey,
+    max_heartbeat_age_slots: u64,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [244, 173, 101, 252, 147, 245, 134, 130];
+
+    let (heartbeat_tracker, _) = HeartbeatTracker::find_address(registry);
+    let mut data = DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&max_heartbeat_age_slots.to_le_bytes());
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(heartbeat_tracker, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new_readonly(config_address(), false),
+            AccountMeta::new_readonly(*admin, true),
+        ],
+        data,
+    }
+}
+
+/// Checks that `segmenter` is active as of `current_slot` according to `heartbeat_tracker`, which
+/// must be the heartbeat tracker of `registry`. A heartbeat tracker that wasn't created doesn't
+/// reject any segmenter, but only if it is at the registry's heartbeat tracker address, so that
+/// callers can't skip the check by passing another account.
+pub(crate) fn check_heartbeat(
+    current_slot: u64,
+    registry: &Pubkey,
+    heartbeat_tracker: &impl AccountView,
+    segmenter: &Pubkey,
+) -> Result<(), SegmenterRejection> {
+    if *heartbeat_tracker.owner() != Registry::PROGRAM_ID {
+        if *heartbeat_tracker.key() != HeartbeatTracker::find_address(registry).0 {
+            return Err(SegmenterRejection::InvalidHeartbeatTracker);
+        }
+        return Ok(());
+    }
+    let heartbeat_tracker_data = heartbeat_tracker
+        .try_borrow_data()
+        .ok_or(SegmenterRejection::InvalidHeartbeatTracker)?;
+    let heartbeat_tracker = HeartbeatTracker::try_deserialize(&heartbeat_tracker_data)
+        .ok_or(SegmenterRejection::InvalidHeartbeatTracker)?;
+    if heartbeat_tracker.registry != *registry {
+        return Err(SegmenterRejection::HeartbeatTrackerMismatch);
+    }
+    if !heartbeat_tracker.is_active(segmenter, current_slot) {
+        return Err(SegmenterRejection::SegmenterStale);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::account_info::AccountInfo;
+
+    use crate::{check_segmenter, test_utils::SEGMENTER};
+
+    use super::*;
+
+    #[test]
+    fn test_is_active() {
+        let mut tracker = HeartbeatTracker::zeroed();
+        tracker.entries[5] = HeartbeatEntry {
+            segmenter: SEGMENTER,
+            last_seen_slot: 100,
+        };
+
+        // Staleness detection is disabled
+        assert!(tracker.is_active(&Pubkey::new_unique(), 1_000_000));
+
+        tracker.max_heartbeat_age_slots = 50;
+        assert_eq!(tracker.last_seen_slot(&SEGMENTER), Some(100));
+        assert!(tracker.is_active(&SEGMENTER, 150));
+        assert!(!tracker.is_active(&SEGMENTER, 151));
+        assert!(!tracker.is_active(&Pubkey::new_unique(), 100));
+        assert!(!tracker.is_active(&Pubkey::default(), 0));
+    }
+
+    #[test]
+    fn test_try_deserialize() {
+        let mut tracker = HeartbeatTracker::zeroed();
+        tracker.max_heartbeat_age_slots = 50;
+        let len = 8 + std::mem::size_of::<HeartbeatTracker>();
+
+        // Back the data with words so it is 8-byte aligned, like account data
+        for offset in [0, 1] {
+            let mut words = vec![0u64; len / 8 + 1];
+            let data = &mut bytemuck::cast_slice_mut::<u64, u8>(&mut words)[offset..offset + len];
+            data[..8].copy_from_slice(&HeartbeatTracker::DISCRIMINATOR);
+            data[8..].copy_from_slice(bytemuck::bytes_of(&tracker));
+            let max_heartbeat_age_slots = HeartbeatTracker::try_deserialize(data)
+                .map(|tracker| tracker.max_heartbeat_age_slots);
+            assert_eq!(max_heartbeat_age_slots, (offset == 0).then_some(50));
+        }
+    }
+
+    #[test]
+    fn test_check_segmenter_heartbeat() {
+        let registry_address = Pubkey::new_unique();
+        let mut registry_state = Registry {
+            registered_segmenters: [Pubkey::default(); 64],
+        };
+        registry_state.registered_segmenters[63] = SEGMENTER;
+        let mut registry_data = Registry::DISCRIMINATOR.to_vec();
+        registry_data.extend_from_slice(bytemuck::bytes_of(&registry_state));
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &registry_address,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut tracker = HeartbeatTracker::zeroed();
+        tracker.registry = registry_address;
+        tracker.max_heartbeat_age_slots = 50;
+        tracker.entries[0] = HeartbeatEntry {
+            segmenter: SEGMENTER,
+            last_seen_slot: 100,
+        };
+
+        let (tracker_address, _) = HeartbeatTracker::find_address(&registry_address);
+        let check =
+            |tracker: Option<&HeartbeatTracker>, key: &Pubkey, owner: &Pubkey, current_slot| {
+                // A tracker that wasn't created has no data
+                let mut tracker_data = vec![];
+                if let Some(tracker) = tracker {
+                    tracker_data.extend_from_slice(&HeartbeatTracker::DISCRIMINATOR);
+                    tracker_data.extend_from_slice(bytemuck::bytes_of(tracker));
+                }
+                let mut tracker_lamports = 0;
+                let heartbeat_tracker = AccountInfo {
+                    key,
+                    is_signer: false,
+                    is_writable: false,
+                    lamports: Rc::new(RefCell::new(&mut tracker_lamports)),
+                    data: Rc::new(RefCell::new(&mut tracker_data)),
+                    owner,
+                    executable: false,
+                    rent_epoch: u64::MAX,
+                };
+
+                let mut segmenter_lamports = 0;
+                let mut segmenter_data = [];
+                let segmenter = AccountInfo {
+                    key: &SEGMENTER,
+                    is_signer: true,
+                    is_writable: false,
+                    lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+                    data: Rc::new(RefCell::new(&mut segmenter_data)),
+                    owner: &system_program::ID,
+                    executable: false,
+                    rent_epoch: u64::MAX,
+                };
+                check_segmenter(current_slot, &registry, &heartbeat_tracker, &segmenter)
+            };
+        let program_id = Registry::PROGRAM_ID;
+
+        assert!(check(Some(&tracker), &tracker_address, &program_id, 150).is_ok());
+        assert_eq!(
+            check(Some(&tracker), &tracker_address, &program_id, 151),
+            Err(SegmenterRejection::SegmenterStale)
+        );
+        assert_eq!(
+            check(
+                Some(&tracker),
+                &Pubkey::new_unique(),
+                &system_program::ID,
+                150
+            ),
+            Err(SegmenterRejection::InvalidHeartbeatTracker)
+        );
+
+        let mut other_registry = tracker;
+        other_registry.registry = Pubkey::new_unique();
+        assert_eq!(
+            check(Some(&other_registry), &tracker_address, &program_id, 150),
+            Err(SegmenterRejection::HeartbeatTrackerMismatch)
+        );
+
+        // Not a heartbeat tracker
+        assert_eq!(
+            check(None, &tracker_address, &program_id, 150),
+            Err(SegmenterRejection::InvalidHeartbeatTracker)
+        );
+
+        // Staleness detection is disabled until the tracker is created, but only if the tracker's
+        // address is passed
+        assert!(check(None, &tracker_address, &system_program::ID, 1_000_000).is_ok());
+        assert_eq!(
+            check(None, &Pubkey::new_unique(), &system_program::ID, 150),
+            Err(SegmenterRejection::InvalidHeartbeatTracker)
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Panic-free parsing of the Instructions sysvar and Ed25519 precompile instructions, which works on
+//! the raw account data so that every [`AccountView`](crate::AccountView) backend can use it.
+
+use solana_program::{
+    ed25519_program,
+    pubkey::Pubkey,
+    sysvar::{self, instructions},
+};
+
+use crate::AccountView;
+
+/// Borrows the data of the Instructions sysvar account. Returns `None` if `instructions_sysvar`
+/// isn't the Instructions sysvar account.
+pub(crate) fn borrow_instructions_sysvar<A: AccountView>(
+    instructions_sysvar: &A,
+) -> Option<A::Data<'_>> {
+    let is_instructions_sysvar = *instructions_sysvar.key() == instructions::ID
+        && *instructions_sysvar.owner() == sysvar::ID;
+    if !is_instructions_sysvar {
+        return None;
+    }
+    instructions_sysvar.try_borrow_data()
+}
+
+/// Calls `f` with the public key and message of each signature that the Ed25519 precompile
+/// instructions of the transaction verified, returning the first `Some`. Returns `None` if
+/// `instructions_sysvar` isn't the Instructions sysvar account.
+pub(crate) fn find_ed25519_signature<T>(
+    instructions_sysvar: &impl AccountView,
+    mut f: impl FnMut(Pubkey, &[u8]) -> Option<T>,
+) -> Option<T> {
+    let instructions_data = borrow_instructions_sysvar(instructions_sysvar)?;
+    let instructions = InstructionsSysvar::new(&instructions_data)?;
+
+    let found = instructions
+        .iter()
+        .flat_map(|instruction| instruction.ed25519_signatures())
+        .find_map(|(public_key, message)| f(public_key, message));
+    found
+}
+
+/// The serialized Instructions sysvar: the number of instructions, the offset of each instruction,
+/// the instructions, and the index of the currently executing top-level instruction
+#[derive(Clone, Copy)]
+pub(crate) struct InstructionsSysvar<'a> {
+    data: &'a [u8],
+    len: u16,
+}
+
+impl<'a> InstructionsSysvar<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Option<Self> {
+        let len = read_u16(data, 0)?;
+        // The offsets table and the current index must fit
+        if data.len() < 2 + 2 * usize::from(len) + 2 {
+            return None;
+        }
+        Some(Self { data, len })
+    }
+
+    /// The index of the currently executing top-level instruction
+    pub(crate) fn current_index(&self) -> Option<u16> {
+        read_u16(self.data, self.data.len().checked_sub(2)?)
+    }
+
+    pub(crate) fn get(&self, index: u16) -> Option<IntrospectedInstruction<'a>> {
+        if index >= self.len {
+            return None;
+        }
+        let start = usize::from(read_u16(self.data, 2 + 2 * usize::from(index))?);
+
+        let num_accounts = usize::from(read_u16(self.data, start)?);
+        let accounts_start = start + 2;
+        let program_id_start = accounts_start + num_accounts * (1 + 32);
+        let program_id = read_pubkey(self.data, program_id_start)?;
+        let data_len = usize::from(read_u16(self.data, program_id_start + 32)?);
+        let data_start = program_id_start + 32 + 2;
+
+        Some(IntrospectedInstruction {
+            index,
+            program_id,
+            accounts: self.data.get(accounts_start..program_id_start)?,
+            data: self.data.get(data_start..data_start + data_len)?,
+        })
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = IntrospectedInstruction<'a>> + '_ {
+        (0..self.len).filter_map(|index| self.get(index))
+    }
+}
+
+/// A top-level instruction of the transaction, as serialized in the Instructions sysvar
+#[derive(Clone, Copy)]
+pub(crate) struct IntrospectedInstruction<'a> {
+    pub(crate) index: u16,
+    pub(crate) program_id: Pubkey,
+    /// The account metas, each a flags byte followed by the public key
+    pub(crate) accounts: &'a [u8],
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> IntrospectedInstruction<'a> {
+    /// Whether the instruction lists `key` as a signer
+    pub(crate) fn is_signer(&self, key: &Pubkey) -> bool {
+        const IS_SIGNER: u8 = 0b01;
+
+        self.accounts
+            .chunks_exact(1 + 32)
+            .any(|meta| meta[0] & IS_SIGNER != 0 && meta[1..] == key.as_ref()[..])
+    }
+
+    /// The public keys and messages of the signatures the Ed25519 precompile verified in this
+    /// instruction. Signatures whose public key or message is stored in another instruction are
+    /// skipped.
+    pub(crate) fn ed25519_signatures(&self) -> impl Iterator<Item = (Pubkey, &'a [u8])> + 'a {
+        const OFFSETS_START: usize = 2;
+        const OFFSETS_LEN: usize = 14;
+
+        let index = self.index;
+        let data = self.data;
+        let num_signatures = if self.program_id == ed25519_program::ID {
+            data.first().copied().unwrap_or_default()
+        } else {
+            0
+        };
+        let is_this_instruction = move |instruction_index: u16| {
+            instruction_index == u16::MAX || instruction_index == index
+        };
+
+        (0..usize::from(num_signatures)).filter_map(move |i| {
+            let offsets = OFFSETS_START + i * OFFSETS_LEN;
+            let public_key_offset = read_u16(data, offsets + 4)?;
+            let public_key_instruction_index = read_u16(data, offsets + 6)?;
+            let message_data_offset = usize::from(read_u16(data, offsets + 8)?);
+            let message_data_size = usize::from(read_u16(data, offsets + 10)?);
+            let message_instruction_index = read_u16(data, offsets + 12)?;
+
+            if !is_this_instruction(public_key_instruction_index)
+                || !is_this_instruction(message_instruction_index)
+            {
+                return None;
+            }
+
+            let public_key = read_pubkey(data, usize::from(public_key_offset))?;
+            let message = data.get(message_data_offset..message_data_offset + message_data_size)?;
+            Some((public_key, message))
+        })
+    }
+}
+
+/// Builds the data of an Ed25519 precompile instruction that verifies one signature, with the public
+/// key, signature and message stored in the instruction itself
+pub(crate) fn ed25519_instruction_data(
+    public_key: &Pubkey,
+    signature: &[u8; 64],
+    message: &[u8],
+) -> Vec<u8> {
+    const DATA_START: u16 = 2 + 14;
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_data_offset = signature_offset + 64;
+
+    let mut data = Vec::with_capacity(usize::from(message_data_offset) + message.len());
+    data.extend_from_slice(&[1, 0]);
+    for field in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_data_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(public_key.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+    data
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    let bytes = data.get(offset..offset + 32)?;
+    Pubkey::try_from(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::sysvar::instructions::{
+        construct_instructions_data, BorrowedAccountMeta, BorrowedInstruction,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_instructions_sysvar() {
+        let program_id = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let writable = Pubkey::new_unique();
+        let segmenter = Pubkey::new_unique();
+        let ed25519_data = ed25519_instruction_data(&segmenter, &[7; 64], b"message");
+
+        let data = construct_instructions_data(&[
+            BorrowedInstruction {
+                program_id: &ed25519_program::ID,
+                accounts: vec![],
+                data: &ed25519_data,
+            },
+            BorrowedInstruction {
+                program_id: &program_id,
+                accounts: vec![
+                    BorrowedAccountMeta {
+                        pubkey: &signer,
+                        is_signer: true,
+                        is_writable: false,
+                    },
+                    BorrowedAccountMeta {
+                        pubkey: &writable,
+                        is_signer: false,
+                        is_writable: true,
+                    },
+                ],
+                data: &[1, 2, 3],
+            },
+        ]);
+
+        let sysvar = InstructionsSysvar::new(&data).unwrap();
+        assert!(sysvar.get(2).is_none());
+
+        let instruction = sysvar.get(1).unwrap();
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.data, [1, 2, 3]);
+        assert!(instruction.is_signer(&signer));
+        assert!(!instruction.is_signer(&writable));
+        assert!(!instruction.is_signer(&segmenter));
+        assert_eq!(instruction.ed25519_signatures().count(), 0);
+
+        let instruction = sysvar.get(0).unwrap();
+        assert_eq!(
+            instruction.ed25519_signatures().collect::<Vec<_>>(),
+            [(segmenter, &b"message"[..])]
+        );
+        assert_eq!(sysvar.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_ed25519_signatures_in_other_instructions() {
+        let segmenter = Pubkey::new_unique();
+        let mut ed25519_data = ed25519_instruction_data(&segmenter, &[7; 64], b"message");
+
+        // The instruction's own index is accepted like u16::MAX
+        ed25519_data[8..10].copy_from_slice(&0u16.to_le_bytes());
+        ed25519_data[14..16].copy_from_slice(&0u16.to_le_bytes());
+        let instruction = IntrospectedInstruction {
+            index: 0,
+            program_id: ed25519_program::ID,
+            accounts: &[],
+            data: &ed25519_data,
+        };
+        assert_eq!(instruction.ed25519_signatures().count(), 1);
+
+        // The message is stored in another instruction
+        ed25519_data[14..16].copy_from_slice(&1u16.to_le_bytes());
+        let instruction = IntrospectedInstruction {
+            index: 0,
+            program_id: ed25519_program::ID,
+            accounts: &[],
+            data: &ed25519_data,
+        };
+        assert_eq!(instruction.ed25519_signatures().count(), 0);
+    }
+
+    #[test]
+    fn test_instructions_sysvar_never_panics() {
+        let ed25519_data = ed25519_instruction_data(&Pubkey::new_unique(), &[7; 64], b"message");
+        let data = construct_instructions_data(&[BorrowedInstruction {
+            program_id: &ed25519_program::ID,
+            accounts: vec![],
+            data: &ed25519_data,
+        }]);
+
+        // Every truncation and single-byte corruption of valid data
+        for len in 0..data.len() {
+            if let Some(sysvar) = InstructionsSysvar::new(&data[..len]) {
+                for instruction in sysvar.iter() {
+                    instruction.ed25519_signatures().for_each(drop);
+                }
+            }
+        }
+        for i in 0..data.len() {
+            for byte in [0, 1, 0x7f, 0xff] {
+                let mut data = data.clone();
+                data[i] = byte;
+                if let Some(sysvar) = InstructionsSysvar::new(&data) {
+                    for instruction in sysvar.iter() {
+                        instruction.ed25519_signatures().for_each(drop);
+                    }
+                }
+            }
+        }
+    }
+}
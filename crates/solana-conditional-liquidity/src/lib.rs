@@ -1,5 +1,48 @@
+mod account;
+#[cfg(feature = "anchor")]
+pub mod anchor;
+mod attestation;
+mod attribution;
+mod audit_log;
+mod caller;
+mod claim;
+mod directory;
+mod frontend;
+mod heartbeat;
+mod introspection;
+mod nonce;
 mod origin;
+mod overlay;
+mod program_segmenter;
+mod quorum;
 mod registry;
+mod registry_metadata;
+mod revocation;
+mod score;
+mod session;
+mod snapshot;
+#[cfg(test)]
+mod test_utils;
+mod tier;
 
+pub use account::*;
+pub use attestation::*;
+pub use attribution::*;
+pub use audit_log::*;
+pub use caller::*;
+pub use claim::*;
+pub use directory::*;
+pub use frontend::*;
+pub use heartbeat::*;
+pub use nonce::*;
 pub use origin::*;
+pub use overlay::*;
+pub use program_segmenter::*;
+pub use quorum::*;
 pub use registry::*;
+pub use registry_metadata::*;
+pub use revocation::*;
+pub use score::*;
+pub use session::*;
+pub use snapshot::*;
+pub use tier::*;
@@ -0,0 +1,385 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+use crate::{verify_attestation, AccountView, Registry, VerifyAttestationResult};
+
+const WORDS: usize = 64;
+
+/// A nonce tracker account of the segmenter registry program, which records the attestation nonces
+/// of a segmenter that an authority has consumed. Only the most recent [`NonceTracker::WINDOW`]
+/// nonces are tracked; older nonces count as consumed.
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct NonceTracker {
+    /// The account that may consume nonces
+    pub authority: Pubkey,
+    /// The segmenter whose attestation nonces are tracked
+    pub segmenter: Pubkey,
+    /// The lowest nonce in the window
+    pub base: u64,
+    pub bitmap: [u64; WORDS],
+}
+
+impl NonceTracker {
+    pub const DISCRIMINATOR: [u8; 8] = [244, 104, 52, 45, 43, 120, 94, 13];
+    pub const SEED: &'static [u8] = b"nonce_tracker";
+    pub const WINDOW: u64 = (WORDS * 64) as u64;
+
+    /// The address of the nonce tracker for `authority` and `segmenter`
+    pub fn find_address(authority: &Pubkey, segmenter: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, authority.as_ref(), segmenter.as_ref()],
+            &Registry::PROGRAM_ID,
+        )
+    }
+
+    /// Deserializes nonce tracker account data, including the discriminator. Returns `None` if the
+    /// data isn't a nonce tracker. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+
+    /// Whether `nonce` has been consumed or has fallen behind the window
+    pub fn is_consumed(&self, nonce: u64) -> bool {
+        if nonce < self.base {
+            return true;
+        }
+        if nonce - self.base >= Self::WINDOW {
+            return false;
+        }
+        let bit = nonce % Self::WINDOW;
+        self.bitmap[(bit / 64) as usize] & (1 << (bit % 64)) != 0
+    }
+}
+
+/// Builds the segmenter registry instruction that marks `nonce` consumed in the nonce tracker.
+/// `authority` must sign, so programs whose authority is a PDA invoke it with `invoke_signed`.
+pub fn consume_nonce_instruction(
+    nonce_tracker: &Pubkey,
+    authority: &Pubkey,
+    nonce: u64,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [165, 117, 159, 47, 195, 97, 53, 98];
+
+    let mut data = DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&nonce.to_le_bytes());
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*nonce_tracker, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+/// Like [`verify_attestation`], but also rejects attestations whose nonce has already been consumed
+/// in `authority`'s nonce tracker for the segmenter. To prevent replays, consume the nonce with
+/// [`consume_nonce_instruction`] in the same instruction that accepts the attestation.
+///
+/// The tracker only keeps the most recent [`NonceTracker::WINDOW`] nonces, and consuming a nonce
+/// beyond the window slides it forward. Nonces that fall behind the window count as consumed, so
+/// this only accepts every valid attestation if each segmenter issues increasing nonces, with the
+/// nonces of its unexpired attestations within `WINDOW` of each other.
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::{
+///     consume_nonce_instruction, verify_unconsumed_attestation, VerifyAttestationResult,
+/// };
+/// use solana_program::{
+///     account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+///     program::invoke_signed, pubkey::Pubkey, sysvar::Sysvar,
+/// };
+///
+/// fn handler<'info>(
+///     program_id: &Pubkey,
+///     user: &AccountInfo<'_>,
+///     registry: &AccountInfo<'_>,
+///     instructions_sysvar: &AccountInfo<'_>,
+///     nonce_tracker: &AccountInfo<'info>,
+///     authority: &AccountInfo<'info>,
+///     authority_seeds: &[&[u8]],
+/// ) -> ProgramResult {
+///     if !user.is_signer {
+///         // The attestation only counts for a user who signed the transaction
+///         return Ok(());
+///     }
+///
+///     let current_slot = Clock::get()?.slot;
+///     let VerifyAttestationResult::AttestedBySegmenter(attestation) =
+///         verify_unconsumed_attestation(
+///             program_id,
+///             user.key,
+///             current_slot,
+///             registry,
+///             instructions_sysvar,
+///             nonce_tracker,
+///             authority.key,
+///         )
+///     else {
+///         // No segmenter attested the invocation, or the attestation was already used
+///         return Ok(());
+///     };
+///
+///     invoke_signed(
+///         &consume_nonce_instruction(nonce_tracker.key, authority.key, attestation.nonce),
+///         &[nonce_tracker.clone(), authority.clone()],
+///         &[authority_seeds],
+///     )?;
+///
+///     // Branch on attestation.origin here
+///     Ok(())
+/// }
+/// ```
+pub fn verify_unconsumed_attestation(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    current_slot: u64,
+    registry: &impl AccountView,
+    instructions_sysvar: &impl AccountView,
+    nonce_tracker: &impl AccountView,
+    authority: &Pubkey,
+) -> VerifyAttestationResult {
+    let result = verify_attestation(
+        program_id,
+        user,
+        current_slot,
+        registry,
+        instructions_sysvar,
+    );
+    let VerifyAttestationResult::AttestedBySegmenter(attestation) = result else {
+        return result;
+    };
+
+    if *nonce_tracker.owner() != Registry::PROGRAM_ID {
+        return VerifyAttestationResult::NotAttestedBySegmenter;
+    }
+    let Some(nonce_tracker_data) = nonce_tracker.try_borrow_data() else {
+        return VerifyAttestationResult::NotAttestedBySegmenter;
+    };
+    let Some(tracker) = NonceTracker::try_deserialize(&nonce_tracker_data) else {
+        return VerifyAttestationResult::NotAttestedBySegmenter;
+    };
+
+    if tracker.authority != *authority
+        || tracker.segmenter != attestation.segmenter
+        || tracker.is_consumed(attestation.nonce)
+    {
+        return VerifyAttestationResult::NotAttestedBySegmenter;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::{
+        account_info::AccountInfo,
+        sysvar::{
+            self,
+            instructions::{self, construct_instructions_data, BorrowedInstruction},
+        },
+    };
+
+    use crate::{
+        origin_identity,
+        test_utils::{registry_data, SEGMENTER},
+        Attestation, VerifiedAttestation,
+    };
+
+    use super::*;
+
+    fn tracker(authority: Pubkey, base: u64, consumed: &[u64]) -> NonceTracker {
+        let mut tracker = NonceTracker {
+            authority,
+            segmenter: SEGMENTER,
+            base,
+            bitmap: [0; WORDS],
+        };
+        // Mirrors how the segmenter registry program sets bits
+        for nonce in consumed {
+            let bit = nonce % NonceTracker::WINDOW;
+            tracker.bitmap[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+        tracker
+    }
+
+    fn tracker_data(tracker: &NonceTracker) -> Vec<u8> {
+        let mut data = NonceTracker::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(tracker));
+        data
+    }
+
+    #[test]
+    fn test_nonce_tracker_is_consumed() {
+        let window = NonceTracker::WINDOW;
+        let tracker = tracker(Pubkey::new_unique(), 1000, &[1000, 1001, 1000 + window - 1]);
+
+        assert!(tracker.is_consumed(0));
+        assert!(tracker.is_consumed(999));
+        assert!(tracker.is_consumed(1000));
+        assert!(tracker.is_consumed(1001));
+        assert!(!tracker.is_consumed(1002));
+        assert!(tracker.is_consumed(1000 + window - 1));
+
+        // Beyond the window. These share bits with nonces in the window, but aren't consumed.
+        assert!(!tracker.is_consumed(1000 + window));
+        assert!(!tracker.is_consumed(1001 + window));
+        assert!(!tracker.is_consumed(u64::MAX));
+
+        let data = tracker_data(&tracker);
+        let deserialized = NonceTracker::try_deserialize(&data).unwrap();
+        assert_eq!(
+            bytemuck::bytes_of(&deserialized),
+            bytemuck::bytes_of(&tracker)
+        );
+        assert!(NonceTracker::try_deserialize(&data[..data.len() - 1]).is_none());
+        assert!(NonceTracker::try_deserialize(&data[8..]).is_none());
+    }
+
+    #[test]
+    fn test_consume_nonce_instruction() {
+        let nonce_tracker = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let instruction = consume_nonce_instruction(&nonce_tracker, &authority, 0x0102);
+
+        assert_eq!(instruction.program_id, Registry::PROGRAM_ID);
+        assert_eq!(
+            instruction.accounts,
+            [
+                AccountMeta::new(nonce_tracker, false),
+                AccountMeta::new_readonly(authority, true),
+            ]
+        );
+        assert_eq!(
+            instruction.data,
+            [165, 117, 159, 47, 195, 97, 53, 98, 2, 1, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_verify_unconsumed_attestation() {
+        let authority = Pubkey::new_unique();
+        let attestation = Attestation {
+            program_id: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            origin: origin_identity::DFLOW_WEB,
+            expiry_slot: 100,
+            nonce: 42,
+        };
+        let ed25519_instruction = attestation.ed25519_instruction(&SEGMENTER, &[7; 64]);
+
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &crate::ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+        let mut sysvar_data = construct_instructions_data(&[BorrowedInstruction {
+            program_id: &ed25519_instruction.program_id,
+            accounts: vec![],
+            data: &ed25519_instruction.data,
+        }]);
+        let mut sysvar_lamports = 0;
+        let instructions_sysvar = AccountInfo {
+            key: &instructions::ID,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut sysvar_lamports)),
+            data: Rc::new(RefCell::new(&mut sysvar_data)),
+            owner: &sysvar::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let verify = |tracker: &NonceTracker, owner: &Pubkey| {
+            let nonce_tracker_key = NonceTracker::find_address(&tracker.authority, &SEGMENTER).0;
+            let mut nonce_tracker_data = tracker_data(tracker);
+            let mut nonce_tracker_lamports = 0;
+            let nonce_tracker = AccountInfo {
+                key: &nonce_tracker_key,
+                is_signer: false,
+                is_writable: true,
+                lamports: Rc::new(RefCell::new(&mut nonce_tracker_lamports)),
+                data: Rc::new(RefCell::new(&mut nonce_tracker_data)),
+                owner,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+            verify_unconsumed_attestation(
+                &attestation.program_id,
+                &attestation.user,
+                0,
+                &registry,
+                &instructions_sysvar,
+                &nonce_tracker,
+                &authority,
+            )
+        };
+
+        // The first use of the attestation is accepted
+        let fresh = tracker(authority, 0, &[]);
+        assert_eq!(
+            verify(&fresh, &Registry::PROGRAM_ID),
+            VerifyAttestationResult::AttestedBySegmenter(VerifiedAttestation {
+                segmenter: SEGMENTER,
+                origin: origin_identity::DFLOW_WEB,
+                expiry_slot: 100,
+                nonce: 42,
+            })
+        );
+
+        // Once the nonce is consumed, replaying the attestation is rejected
+        let consumed = tracker(authority, 0, &[42]);
+        assert_eq!(
+            verify(&consumed, &Registry::PROGRAM_ID),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+
+        // So is an attestation whose nonce fell behind the window
+        let slid = tracker(authority, 43, &[]);
+        assert_eq!(
+            verify(&slid, &Registry::PROGRAM_ID),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+
+        // Another authority's tracker
+        let other_authority = tracker(Pubkey::new_unique(), 0, &[]);
+        assert_eq!(
+            verify(&other_authority, &Registry::PROGRAM_ID),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+
+        // Another segmenter's tracker
+        let other_segmenter = NonceTracker {
+            segmenter: Pubkey::new_unique(),
+            ..fresh
+        };
+        assert_eq!(
+            verify(&other_segmenter, &Registry::PROGRAM_ID),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+
+        // A tracker that isn't owned by the segmenter registry program
+        assert_eq!(
+            verify(&fresh, &Pubkey::new_unique()),
+            VerifyAttestationResult::NotAttestedBySegmenter
+        );
+    }
+}
@@ -1,16 +1,132 @@
-use solana_program::{account_info::AccountInfo, pubkey, pubkey::Pubkey};
+use std::{fmt, str::FromStr};
 
-use crate::is_invoked_by_segmenter;
+use bytemuck::{Pod, Zeroable};
+use solana_program::{pubkey, pubkey::Pubkey};
+
+use crate::{registry::check_registered_signer, AccountView};
 
 /// The identifier for the origin of an invocation. This identifies the application or frontend from
-/// which the invocation originated.
-pub type Origin = u16;
+/// which the invocation originated. Wire-compatible with a little-endian `u16`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct Origin(pub u16);
+
+impl Origin {
+    /// The catalog entry for this origin, if it is a well-known origin
+    pub fn info(self) -> Option<&'static OriginInfo> {
+        origin_identity::CATALOG
+            .iter()
+            .find(|info| info.origin == self)
+    }
+
+    /// The constant name of this origin in [`origin_identity`], if it is a well-known origin
+    pub fn name(self) -> Option<&'static str> {
+        if self == origin_identity::UNKNOWN {
+            return Some("UNKNOWN");
+        }
+        self.info().map(|info| info.name)
+    }
+
+    /// The vendor of the application or frontend, if it is a well-known origin
+    pub fn vendor(self) -> Option<&'static str> {
+        self.info().map(|info| info.vendor)
+    }
+
+    /// The platform of the application or frontend, if it is a well-known origin
+    pub fn platform(self) -> Option<Platform> {
+        self.info().map(|info| info.platform)
+    }
+}
+
+impl From<u16> for Origin {
+    fn from(origin: u16) -> Self {
+        Self(origin)
+    }
+}
+
+impl From<Origin> for u16 {
+    fn from(origin: Origin) -> Self {
+        origin.0
+    }
+}
+
+/// Formats well-known origins by their constant name, e.g. `DFLOW_WEB`, and other origins by
+/// their identifier. The output can be parsed back with [`FromStr`].
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// Parses either the constant name of a well-known origin, e.g. `DFLOW_WEB`, or a numeric
+/// identifier
+impl FromStr for Origin {
+    type Err = ParseOriginError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "UNKNOWN" {
+            return Ok(origin_identity::UNKNOWN);
+        }
+        if let Some(info) = origin_identity::CATALOG.iter().find(|info| info.name == s) {
+            return Ok(info.origin);
+        }
+        s.parse().map(Self).map_err(|_| ParseOriginError)
+    }
+}
+
+/// The string is neither a well-known origin name nor a `u16` identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOriginError;
+
+impl fmt::Display for ParseOriginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid origin")
+    }
+}
+
+impl std::error::Error for ParseOriginError {}
+
+/// The platform of an application or frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Platform {
+    Web,
+    Mobile,
+    Extension,
+    Desktop,
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Platform::Web => "web",
+            Platform::Mobile => "mobile",
+            Platform::Extension => "extension",
+            Platform::Desktop => "desktop",
+        })
+    }
+}
+
+/// A well-known origin in the [`origin_identity::CATALOG`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OriginInfo {
+    pub origin: Origin,
+    /// The constant name in [`origin_identity`]
+    pub name: &'static str,
+    pub vendor: &'static str,
+    pub platform: Platform,
+}
 
+/// Well-known origins, generated from `origins.csv`
 pub mod origin_identity {
-    use super::Origin;
+    use super::{Origin, OriginInfo, Platform};
 
     /// Origin when the application or frontend is unknown or could not be verified
-    pub const UNKNOWN: Origin = 0;
+    pub const UNKNOWN: Origin = Origin(0);
+
+    include!(concat!(env!("OUT_DIR"), "/origin_identity.rs"));
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,7 +138,8 @@ pub enum VerifyOriginResult {
 }
 
 /// Checks whether the invocation was signed by a segmenter and verifies the origin of the
-/// invocation. Use this if you want to branch on the origin of the invocation.
+/// invocation. Use this if you want to branch on the origin of the invocation. Heartbeats aren't
+/// checked, so use [`check_segmenter`](crate::check_segmenter) as well to reject stale segmenters.
 ///
 /// Examples
 ///
@@ -48,13 +165,10 @@ pub enum VerifyOriginResult {
 ///         origin_identity::UNKNOWN => {
 ///             // Origin is unknown or could not be verified
 ///         }
+///         origin_identity::DFLOW_WEB => {}
 ///         // These identifiers are just examples, but you get the idea...
-///         // origin_identity::DFLOW_MOBILE => {}
-///         // origin_identity::DFLOW_WEB => {}
-///         // origin_identity::PHANTOM_MOBILE => {}
-///         // origin_identity::PHANTOM_EXTENSION => {}
-///         // origin_identity::SOLFLARE_MOBILE => {}
-///         // origin_identity::SOLFLARE_EXTENSION => {}
+///         // Origin(2) => {}
+///         // Origin(3) => {}
 ///         _ => {
 ///             // Fallback for any other origin. This is different than the origin being unknown.
 ///         }
@@ -62,20 +176,20 @@ pub enum VerifyOriginResult {
 /// }
 /// ```
 pub fn verify_origin(
-    claimed_origin: Origin,
-    registry: &AccountInfo<'_>,
-    segmenter: &AccountInfo<'_>,
+    claimed_origin: impl Into<Origin>,
+    registry: &impl AccountView,
+    segmenter: &impl AccountView,
 ) -> VerifyOriginResult {
-    if !is_invoked_by_segmenter(registry, segmenter) {
+    if check_registered_signer(registry, segmenter).is_err() {
         return VerifyOriginResult::NotInvokedBySegmenter;
     }
 
-    if registry.key != &ORIGIN_VERIFYING_SEGMENTER_REGISTRY {
+    if registry.key() != &ORIGIN_VERIFYING_SEGMENTER_REGISTRY {
         return VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN);
     }
 
     // If one of the DFlow segmenters signed the invocation, we can trust the origin
-    VerifyOriginResult::InvokedBySegmenter(claimed_origin)
+    VerifyOriginResult::InvokedBySegmenter(claimed_origin.into())
 }
 
 /// The origin-verifying registry is a special registry that contains segmenters that verify the
@@ -88,12 +202,57 @@ mod tests {
     use std::{cell::RefCell, rc::Rc};
 
     use base64::Engine;
-    use solana_program::system_program;
+    use solana_program::{account_info::AccountInfo, system_program};
 
-    use crate::Registry;
+    use crate::{
+        test_utils::{registry_data, SEGMENTER},
+        Registry,
+    };
 
     use super::*;
 
+    #[test]
+    fn test_origin_catalog() {
+        assert_eq!(origin_identity::DFLOW_WEB, Origin(1));
+        assert_eq!(origin_identity::DFLOW_WEB.vendor(), Some("DFlow"));
+        assert_eq!(origin_identity::DFLOW_WEB.platform(), Some(Platform::Web));
+        assert_eq!(origin_identity::UNKNOWN.info(), None);
+        assert_eq!(Origin(4242).info(), None);
+
+        for info in origin_identity::CATALOG {
+            assert_ne!(info.origin, origin_identity::UNKNOWN);
+            assert_eq!(info.origin.info(), Some(info));
+        }
+    }
+
+    #[test]
+    fn test_origin_display_from_str() {
+        for origin in [
+            origin_identity::UNKNOWN,
+            origin_identity::DFLOW_WEB,
+            Origin(4242),
+            Origin(u16::MAX),
+        ] {
+            assert_eq!(origin.to_string().parse(), Ok(origin));
+        }
+
+        assert_eq!(origin_identity::UNKNOWN.to_string(), "UNKNOWN");
+        assert_eq!(origin_identity::DFLOW_WEB.to_string(), "DFLOW_WEB");
+        assert_eq!(Origin(4242).to_string(), "4242");
+        assert_eq!("1".parse(), Ok(origin_identity::DFLOW_WEB));
+
+        assert_eq!("dflow_web".parse::<Origin>(), Err(ParseOriginError));
+        assert_eq!("65536".parse::<Origin>(), Err(ParseOriginError));
+        assert_eq!("".parse::<Origin>(), Err(ParseOriginError));
+    }
+
+    #[test]
+    fn test_origin_wire_format() {
+        assert_eq!(std::mem::size_of::<Origin>(), 2);
+        assert_eq!(bytemuck::bytes_of(&Origin(0x1234)), 0x1234u16.to_le_bytes());
+        assert_eq!(u16::from(Origin::from(7)), 7);
+    }
+
     #[test]
     fn test_verify_origin() {
         let mut registry_data = base64::engine::general_purpose::STANDARD
@@ -128,7 +287,12 @@ mod tests {
         };
         assert_eq!(
             verify_origin(1, &registry, &segmenter),
-            VerifyOriginResult::InvokedBySegmenter(1)
+            VerifyOriginResult::InvokedBySegmenter(Origin(1))
+        );
+        // Typed origins are accepted too
+        assert_eq!(
+            verify_origin(Origin(1), &registry, &segmenter),
+            VerifyOriginResult::InvokedBySegmenter(Origin(1))
         );
 
         // Didn't sign but exists in origin-verifying registry
@@ -204,6 +368,10 @@ mod tests {
             verify_origin(1, &registry, &segmenter),
             VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN)
         );
+        assert_eq!(
+            verify_origin(Origin(1), &registry, &segmenter),
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN)
+        );
 
         // Segmenter didn't sign but exists in non-origin-verifying registry
         let mut segmenter_lamports = 0;
@@ -241,4 +409,57 @@ mod tests {
             VerifyOriginResult::NotInvokedBySegmenter
         );
     }
+
+    #[test]
+    fn test_verify_origin_aliased_accounts() {
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 15200640;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: true,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: 18446744073709551615,
+        };
+
+        let mut segmenter_lamports = 0;
+        let mut segmenter_data = [];
+        let segmenter = AccountInfo {
+            key: &SEGMENTER,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+            data: Rc::new(RefCell::new(&mut segmenter_data)),
+            owner: &system_program::id(),
+            executable: false,
+            rent_epoch: 18446744073709551615,
+        };
+
+        // The same registry account passed twice, with the caller holding a mutable borrow of it
+        let registry_alias = registry.clone();
+        {
+            let _held = registry_alias.data.borrow_mut();
+            assert_eq!(
+                verify_origin(Origin(1), &registry, &segmenter),
+                VerifyOriginResult::NotInvokedBySegmenter
+            );
+        }
+        assert_eq!(
+            verify_origin(Origin(1), &registry, &segmenter),
+            VerifyOriginResult::InvokedBySegmenter(Origin(1))
+        );
+
+        // The registry account passed as its own segmenter
+        let signing_registry = AccountInfo {
+            is_signer: true,
+            ..registry.clone()
+        };
+        assert_eq!(
+            verify_origin(Origin(1), &signing_registry, &signing_registry),
+            VerifyOriginResult::NotInvokedBySegmenter
+        );
+    }
 }
@@ -0,0 +1,382 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{
+    registry::check_registered, AccountView, Registry, SegmenterProof, SegmenterRejection,
+};
+
+const MAX_ITEMS: usize = 16;
+
+/// A DEX's local overlay on a registry, owned by the segmenter registry program, which denies
+/// registered segmenters the DEX distrusts and allows additional segmenters. A key in both lists is
+/// denied.
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct Overlay {
+    /// The DEX that manages the overlay
+    pub authority: Pubkey,
+    /// The registry the overlay applies to
+    pub registry: Pubkey,
+    pub denied_segmenters: [Pubkey; MAX_ITEMS],
+    pub allowed_segmenters: [Pubkey; MAX_ITEMS],
+}
+
+impl Overlay {
+    pub const DISCRIMINATOR: [u8; 8] = [219, 12, 197, 121, 200, 249, 95, 4];
+    pub const SEED: &'static [u8] = b"overlay";
+
+    /// The address of `authority`'s overlay on `registry`
+    pub fn find_address(authority: &Pubkey, registry: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, authority.as_ref(), registry.as_ref()],
+            &Registry::PROGRAM_ID,
+        )
+    }
+
+    /// Deserializes overlay account data, including the discriminator. Returns `None` if the data
+    /// isn't an overlay. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+
+    pub fn is_denied(&self, key: &Pubkey) -> bool {
+        self.denied_segmenters.binary_search(key).is_ok()
+    }
+
+    pub fn is_allowed(&self, key: &Pubkey) -> bool {
+        self.allowed_segmenters.binary_search(key).is_ok()
+    }
+}
+
+/// One of the lists of an [`Overlay`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayList {
+    Denied,
+    Allowed,
+}
+
+/// Builds the segmenter registry instruction that creates `authority`'s overlay on `registry`.
+/// `authority` and `payer` must sign, so programs whose authority is a PDA invoke it with
+/// `invoke_signed`.
+pub fn create_overlay_instruction(
+    authority: &Pubkey,
+    registry: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [40, 252, 139, 178, 174, 181, 212, 149];
+
+    let (overlay, _) = Overlay::find_address(authority, registry);
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(overlay, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: DISCRIMINATOR.to_vec(),
+    }
+}
+
+/// Builds the segmenter registry instruction that adds `key` to a list of the overlay. `authority`
+/// must sign.
+pub fn add_overlay_entry_instruction(
+    overlay: &Pubkey,
+    authority: &Pubkey,
+    list: OverlayList,
+    key: &Pubkey,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [52, 211, 237, 124, 252, 84, 63, 31];
+    overlay_entry_instruction(DISCRIMINATOR, overlay, authority, list, key)
+}
+
+/// Builds the segmenter registry instruction that removes `key` from a list of the overlay.
+/// `authority` must sign.
+pub fn remove_overlay_entry_instruction(
+    overlay: &Pubkey,
+    authority: &Pubkey,
+    list: OverlayList,
+    key: &Pubkey,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [128, 148, 75, 14, 57, 177, 177, 153];
+    overlay_entry_instruction(DISCRIMINATOR, overlay, authority, list, key)
+}
+
+fn overlay_entry_instruction(
+    discriminator: [u8; 8],
+    overlay: &Pubkey,
+    authority: &Pubkey,
+    list: OverlayList,
+    key: &Pubkey,
+) -> Instruction {
+    let mut data = discriminator.to_vec();
+    data.push(list as u8);
+    data.extend_from_slice(key.as_ref());
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*overlay, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+/// The reason an invocation was not treated as signed by a segmenter under an overlay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayRejection {
+    /// The registry is invalid, or the segmenter didn't sign or isn't registered or allowed
+    Segmenter(SegmenterRejection),
+    /// The overlay account isn't an overlay of the segmenter registry program
+    InvalidOverlay,
+    /// The overlay is managed by another authority or applies to another registry
+    OverlayMismatch,
+    /// The overlay denies the segmenter
+    SegmenterDenied,
+}
+
+impl std::fmt::Display for OverlayRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::Segmenter(rejection) => return rejection.fmt(f),
+            Self::InvalidOverlay => "overlay is not an overlay account",
+            Self::OverlayMismatch => "overlay is for another authority or registry",
+            Self::SegmenterDenied => "segmenter is denied by the overlay",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for OverlayRejection {}
+
+impl From<SegmenterRejection> for OverlayRejection {
+    fn from(rejection: SegmenterRejection) -> Self {
+        Self::Segmenter(rejection)
+    }
+}
+
+/// Checks whether the invocation was signed by a segmenter, like
+/// [`check_segmenter`](crate::check_segmenter), with `overlay_authority`'s overlay applied on top
+/// of the registry. Segmenters the overlay denies are rejected, and segmenters it allows are
+/// accepted even if they aren't registered. The overlay must be managed by `overlay_authority`,
+/// since anyone can create an overlay that allows their own key.
+pub fn check_segmenter_with_overlay(
+    registry: &impl AccountView,
+    overlay: &impl AccountView,
+    overlay_authority: &Pubkey,
+    segmenter: &impl AccountView,
+) -> Result<SegmenterProof, OverlayRejection> {
+    if *registry.owner() != Registry::PROGRAM_ID {
+        return Err(SegmenterRejection::InvalidRegistryOwner.into());
+    }
+    if !segmenter.is_signer() {
+        return Err(SegmenterRejection::SegmenterNotSigner.into());
+    }
+
+    if *overlay.owner() != Registry::PROGRAM_ID {
+        return Err(OverlayRejection::InvalidOverlay);
+    }
+    let overlay = overlay
+        .try_borrow_data()
+        .and_then(|data| Overlay::try_deserialize(&data))
+        .ok_or(OverlayRejection::InvalidOverlay)?;
+    if overlay.authority != *overlay_authority || overlay.registry != *registry.key() {
+        return Err(OverlayRejection::OverlayMismatch);
+    }
+
+    if overlay.is_denied(segmenter.key()) {
+        return Err(OverlayRejection::SegmenterDenied);
+    }
+    if !overlay.is_allowed(segmenter.key()) {
+        check_registered(registry, segmenter.key())?;
+    }
+
+    Ok(SegmenterProof {
+        registry: *registry.key(),
+        segmenter: *segmenter.key(),
+    })
+}
+
+/// Checks whether the invocation was signed by a segmenter, with `overlay_authority`'s overlay
+/// applied on top of the registry. See [`check_segmenter_with_overlay`].
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::is_invoked_by_segmenter_with_overlay;
+/// use solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+///
+/// fn handler(
+///     overlay_authority: &Pubkey,
+///     registry: &AccountInfo<'_>,
+///     overlay: &AccountInfo<'_>,
+///     segmenter: &AccountInfo<'_>,
+/// ) {
+///     if !is_invoked_by_segmenter_with_overlay(registry, overlay, overlay_authority, segmenter) {
+///         // The invocation wasn't signed by a segmenter this DEX trusts
+///         return;
+///     }
+///
+///     // Do stuff that you only allow when the invocation was signed by a segmenter
+/// }
+/// ```
+pub fn is_invoked_by_segmenter_with_overlay(
+    registry: &impl AccountView,
+    overlay: &impl AccountView,
+    overlay_authority: &Pubkey,
+    segmenter: &impl AccountView,
+) -> bool {
+    check_segmenter_with_overlay(registry, overlay, overlay_authority, segmenter).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::account_info::AccountInfo;
+
+    use crate::test_utils::{registry_data, SEGMENTER};
+
+    use super::*;
+
+    #[test]
+    fn test_overlay_entry_instruction() {
+        let overlay = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let instruction =
+            add_overlay_entry_instruction(&overlay, &authority, OverlayList::Allowed, &SEGMENTER);
+        assert_eq!(instruction.data[8], 1);
+        assert_eq!(&instruction.data[9..], SEGMENTER.as_ref());
+        assert!(instruction.accounts[1].is_signer);
+    }
+
+    #[test]
+    fn test_check_segmenter_with_overlay() {
+        let authority = Pubkey::new_unique();
+        let registry_address = Pubkey::new_unique();
+        let allowed = Pubkey::new_unique();
+
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &registry_address,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let empty = Overlay {
+            authority,
+            registry: registry_address,
+            denied_segmenters: [Pubkey::default(); MAX_ITEMS],
+            allowed_segmenters: [Pubkey::default(); MAX_ITEMS],
+        };
+        let mut denying = empty;
+        denying.denied_segmenters[MAX_ITEMS - 1] = SEGMENTER;
+        let mut allowing = empty;
+        allowing.allowed_segmenters[MAX_ITEMS - 1] = allowed;
+
+        let (overlay_address, _) = Overlay::find_address(&authority, &registry_address);
+        let check = |overlay: &Overlay, owner: &Pubkey, segmenter_key: &Pubkey, is_signer| {
+            let mut overlay_data = Overlay::DISCRIMINATOR.to_vec();
+            overlay_data.extend_from_slice(bytemuck::bytes_of(overlay));
+            let mut overlay_lamports = 0;
+            let overlay = AccountInfo {
+                key: &overlay_address,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut overlay_lamports)),
+                data: Rc::new(RefCell::new(&mut overlay_data)),
+                owner,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+
+            let mut segmenter_lamports = 0;
+            let mut segmenter_data = [];
+            let segmenter = AccountInfo {
+                key: segmenter_key,
+                is_signer,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+                data: Rc::new(RefCell::new(&mut segmenter_data)),
+                owner: &system_program::ID,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+            check_segmenter_with_overlay(&registry, &overlay, &authority, &segmenter).map(|_| ())
+        };
+        let program_id = Registry::PROGRAM_ID;
+
+        // An empty overlay applies the registry as is
+        assert_eq!(check(&empty, &program_id, &SEGMENTER, true), Ok(()));
+        assert_eq!(
+            check(&empty, &program_id, &allowed, true),
+            Err(OverlayRejection::Segmenter(
+                SegmenterRejection::SegmenterNotRegistered
+            ))
+        );
+
+        // Denied registered segmenter
+        assert_eq!(
+            check(&denying, &program_id, &SEGMENTER, true),
+            Err(OverlayRejection::SegmenterDenied)
+        );
+
+        // Allowed unregistered segmenter, which must still sign
+        assert_eq!(check(&allowing, &program_id, &allowed, true), Ok(()));
+        assert_eq!(
+            check(&allowing, &program_id, &allowed, false),
+            Err(OverlayRejection::Segmenter(
+                SegmenterRejection::SegmenterNotSigner
+            ))
+        );
+
+        // Denying takes precedence over allowing
+        let mut both = allowing;
+        both.denied_segmenters[MAX_ITEMS - 1] = allowed;
+        assert_eq!(
+            check(&both, &program_id, &allowed, true),
+            Err(OverlayRejection::SegmenterDenied)
+        );
+
+        // Someone else's overlay
+        let foreign = Overlay {
+            authority: Pubkey::new_unique(),
+            ..allowing
+        };
+        assert_eq!(
+            check(&foreign, &program_id, &allowed, true),
+            Err(OverlayRejection::OverlayMismatch)
+        );
+
+        // An overlay on another registry
+        let other_registry = Overlay {
+            registry: Pubkey::new_unique(),
+            ..allowing
+        };
+        assert_eq!(
+            check(&other_registry, &program_id, &allowed, true),
+            Err(OverlayRejection::OverlayMismatch)
+        );
+
+        // Not owned by the registry program
+        assert_eq!(
+            check(&allowing, &system_program::ID, &allowed, true),
+            Err(OverlayRejection::InvalidOverlay)
+        );
+    }
+}
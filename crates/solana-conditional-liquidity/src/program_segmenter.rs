@@ -0,0 +1,333 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    registry::check_registered, AccountView, Registry, SegmenterProof, SegmenterRejection,
+};
+
+/// A program segmenter account of the segmenter registry program, which names a program and the
+/// PDA of the program that is registered as a segmenter. The program signs as the segmenter with
+/// `invoke_signed` and the seeds `[seed, bump]`.
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct ProgramSegmenter {
+    /// The registry the program is a segmenter in
+    pub registry: Pubkey,
+    /// The program that signs as the segmenter
+    pub program_id: Pubkey,
+    /// The PDA of the program with the seed, which is registered in the registry
+    pub segmenter: Pubkey,
+    /// The number of bytes of `seed` in use
+    pub seed_len: u8,
+    /// The only seed of the segmenter PDA, besides the bump. Use [`ProgramSegmenter::seed`] for the
+    /// bytes in use.
+    pub seed: [u8; 32],
+    /// The bump seed of the segmenter PDA
+    pub bump: u8,
+}
+
+impl ProgramSegmenter {
+    pub const DISCRIMINATOR: [u8; 8] = [29, 19, 41, 195, 44, 83, 8, 246];
+    pub const SEED: &'static [u8] = b"program_segmenter";
+
+    /// The address of the program segmenter entry for `program_id` in `registry`
+    pub fn find_address(registry: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, registry.as_ref(), program_id.as_ref()],
+            &Registry::PROGRAM_ID,
+        )
+    }
+
+    /// Deserializes program segmenter account data, including the discriminator. Returns `None` if
+    /// the data isn't a program segmenter. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+
+    /// The only seed of the segmenter PDA, besides the bump
+    pub fn seed(&self) -> &[u8] {
+        let len = usize::from(self.seed_len).min(self.seed.len());
+        &self.seed[..len]
+    }
+}
+
+/// The reason an invocation was not treated as signed by a program segmenter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramSegmenterRejection {
+    /// The registry account is invalid, or the segmenter account didn't sign the invocation
+    Segmenter(SegmenterRejection),
+    /// The entry account isn't a program segmenter of the segmenter registry program
+    InvalidEntry,
+    /// The entry is for another registry
+    RegistryMismatch,
+    /// The segmenter account isn't the entry's segmenter PDA
+    NotProgramAddress,
+}
+
+impl std::fmt::Display for ProgramSegmenterRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::Segmenter(rejection) => return rejection.fmt(f),
+            Self::InvalidEntry => "entry is not a program segmenter account",
+            Self::RegistryMismatch => "program segmenter is for another registry",
+            Self::NotProgramAddress => "segmenter is not the PDA of the program segmenter",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for ProgramSegmenterRejection {}
+
+impl From<SegmenterRejection> for ProgramSegmenterRejection {
+    fn from(rejection: SegmenterRejection) -> Self {
+        Self::Segmenter(rejection)
+    }
+}
+
+/// Checks whether the invocation was signed by a program segmenter in the registry, i.e. whether
+/// `segmenter` signed, is the PDA that `entry` names and is registered in the registry.
+///
+/// The PDA is registered like any other segmenter, so
+/// [`is_invoked_by_segmenter`](crate::is_invoked_by_segmenter) accepts it without the entry. Use
+/// this when you also need to know that the segmenter is a program rather than a key.
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::check_program_segmenter;
+/// use solana_program::{account_info::AccountInfo, msg};
+///
+/// fn handler(
+///     registry: &AccountInfo<'_>,
+///     program_segmenter: &AccountInfo<'_>,
+///     segmenter: &AccountInfo<'_>,
+/// ) {
+///     let proof = match check_program_segmenter(registry, program_segmenter, segmenter) {
+///         Ok(proof) => proof,
+///         Err(rejection) => {
+///             msg!("Not invoked by a program segmenter: {}", rejection);
+///             return;
+///         }
+///     };
+///
+///     msg!("Invoked by program segmenter PDA {}", proof.segmenter);
+/// }
+/// ```
+pub fn check_program_segmenter(
+    registry: &impl AccountView,
+    entry: &impl AccountView,
+    segmenter: &impl AccountView,
+) -> Result<SegmenterProof, ProgramSegmenterRejection> {
+    if *registry.owner() != Registry::PROGRAM_ID {
+        return Err(SegmenterRejection::InvalidRegistryOwner.into());
+    }
+    if !segmenter.is_signer() {
+        return Err(SegmenterRejection::SegmenterNotSigner.into());
+    }
+
+    if *entry.owner() != Registry::PROGRAM_ID {
+        return Err(ProgramSegmenterRejection::InvalidEntry);
+    }
+    let entry = entry
+        .try_borrow_data()
+        .and_then(|data| ProgramSegmenter::try_deserialize(&data))
+        .ok_or(ProgramSegmenterRejection::InvalidEntry)?;
+    if entry.registry != *registry.key() {
+        return Err(ProgramSegmenterRejection::RegistryMismatch);
+    }
+    if entry.segmenter != *segmenter.key() {
+        return Err(ProgramSegmenterRejection::NotProgramAddress);
+    }
+    check_registered(registry, segmenter.key())?;
+
+    Ok(SegmenterProof {
+        registry: *registry.key(),
+        segmenter: *segmenter.key(),
+    })
+}
+
+/// Checks whether the invocation was signed by a program segmenter in the registry. See
+/// [`check_program_segmenter`].
+pub fn is_invoked_by_program_segmenter(
+    registry: &impl AccountView,
+    entry: &impl AccountView,
+    segmenter: &impl AccountView,
+) -> bool {
+    check_program_segmenter(registry, entry, segmenter).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::{account_info::AccountInfo, system_program};
+
+    use crate::test_utils::registry_data_with;
+
+    use super::*;
+
+    fn entry_data(entry: &ProgramSegmenter) -> Vec<u8> {
+        let mut data = ProgramSegmenter::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(entry));
+        data
+    }
+
+    fn signer(key: &Pubkey) -> AccountInfo<'_> {
+        AccountInfo {
+            key,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(Box::leak(Box::new(0)))),
+            data: Rc::new(RefCell::new(&mut [])),
+            owner: &system_program::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        }
+    }
+
+    #[test]
+    fn test_check_program_segmenter() {
+        let registry_address = Pubkey::new_unique();
+        let router = Pubkey::new_unique();
+        let (segmenter_address, bump) = Pubkey::find_program_address(&[b"segmenter"], &router);
+        let other_segmenter = Pubkey::new_unique();
+
+        let mut registry_lamports = 0;
+        let mut registry_data = registry_data_with(&[segmenter_address, other_segmenter]);
+        let registry = AccountInfo {
+            key: &registry_address,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+        let segmenter = signer(&segmenter_address);
+
+        let mut seed = [0; 32];
+        seed[..9].copy_from_slice(b"segmenter");
+        let entry = ProgramSegmenter {
+            registry: registry_address,
+            program_id: router,
+            segmenter: segmenter_address,
+            seed_len: 9,
+            seed,
+            bump,
+        };
+        let (entry_address, _) = ProgramSegmenter::find_address(&registry_address, &router);
+        let check = |entry: &ProgramSegmenter,
+                     owner: &Pubkey,
+                     registry: &AccountInfo<'_>,
+                     segmenter: &AccountInfo<'_>| {
+            let mut data = entry_data(entry);
+            let mut lamports = 0;
+            let entry = AccountInfo {
+                key: &entry_address,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut lamports)),
+                data: Rc::new(RefCell::new(&mut data)),
+                owner,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+            check_program_segmenter(registry, &entry, segmenter)
+        };
+
+        // The router signed for its PDA
+        assert_eq!(
+            check(&entry, &Registry::PROGRAM_ID, &registry, &segmenter),
+            Ok(SegmenterProof {
+                registry: registry_address,
+                segmenter: segmenter_address,
+            })
+        );
+
+        // Another segmenter of the registry isn't the router's PDA
+        assert_eq!(
+            check(
+                &entry,
+                &Registry::PROGRAM_ID,
+                &registry,
+                &signer(&other_segmenter)
+            ),
+            Err(ProgramSegmenterRejection::NotProgramAddress)
+        );
+
+        // Another PDA of the router, whose seeds start with the entry's seed
+        let (other_pda, _) = Pubkey::find_program_address(&[b"segmenter", b"other"], &router);
+        assert_eq!(
+            check(
+                &entry,
+                &Registry::PROGRAM_ID,
+                &registry,
+                &signer(&other_pda)
+            ),
+            Err(ProgramSegmenterRejection::NotProgramAddress)
+        );
+
+        // The entry is for another registry
+        let other_registry = ProgramSegmenter {
+            registry: Pubkey::new_unique(),
+            ..entry
+        };
+        assert_eq!(
+            check(
+                &other_registry,
+                &Registry::PROGRAM_ID,
+                &registry,
+                &segmenter
+            ),
+            Err(ProgramSegmenterRejection::RegistryMismatch)
+        );
+
+        // The entry isn't owned by the registry program
+        assert_eq!(
+            check(&entry, &system_program::ID, &registry, &segmenter),
+            Err(ProgramSegmenterRejection::InvalidEntry)
+        );
+
+        // The router didn't sign for its PDA
+        let unsigned = AccountInfo {
+            is_signer: false,
+            ..segmenter.clone()
+        };
+        assert_eq!(
+            check(&entry, &Registry::PROGRAM_ID, &registry, &unsigned),
+            Err(ProgramSegmenterRejection::Segmenter(
+                SegmenterRejection::SegmenterNotSigner
+            ))
+        );
+
+        // The PDA was removed from the registry
+        let mut removed_lamports = 0;
+        let mut removed_data = registry_data_with(&[other_segmenter]);
+        let removed = AccountInfo {
+            lamports: Rc::new(RefCell::new(&mut removed_lamports)),
+            data: Rc::new(RefCell::new(&mut removed_data)),
+            ..registry.clone()
+        };
+        assert_eq!(
+            check(&entry, &Registry::PROGRAM_ID, &removed, &segmenter),
+            Err(ProgramSegmenterRejection::Segmenter(
+                SegmenterRejection::SegmenterNotRegistered
+            ))
+        );
+    }
+
+    #[test]
+    fn test_seed_never_panics() {
+        let entry = ProgramSegmenter {
+            seed_len: u8::MAX,
+            seed: [1; 32],
+            ..ProgramSegmenter::zeroed()
+        };
+        assert_eq!(entry.seed(), [1; 32]);
+    }
+}
@@ -0,0 +1,323 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    origin_identity, AccountView, Origin, Registry, SegmenterRejection, VerifyOriginResult,
+    ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+};
+
+/// The reason an invocation was not treated as signed by a quorum of segmenters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuorumRejection {
+    /// The registry account is invalid. Only the registry variants of [`SegmenterRejection`] are
+    /// used.
+    InvalidRegistry(SegmenterRejection),
+    /// The same segmenter account was passed more than once
+    DuplicateSegmenter(Pubkey),
+    /// Fewer than the required number of distinct registered segmenters signed the invocation
+    QuorumNotReached { signers: usize, required: usize },
+}
+
+impl std::fmt::Display for QuorumRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidRegistry(rejection) => rejection.fmt(f),
+            Self::DuplicateSegmenter(segmenter) => {
+                write!(f, "segmenter {segmenter} was passed more than once")
+            }
+            Self::QuorumNotReached { signers, required } => write!(
+                f,
+                "{signers} registered segmenters signed the invocation but {required} are required"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QuorumRejection {}
+
+/// Checks whether at least `k` distinct segmenters in the registry signed the invocation, returning
+/// the number that did. Accounts that didn't sign or aren't registered aren't counted, but passing
+/// the same account more than once is rejected. A `k` of 0 is treated as 1.
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::verify_segmenter_quorum;
+/// use solana_program::{account_info::AccountInfo, msg};
+///
+/// fn handler(registry: &AccountInfo<'_>, segmenters: &[AccountInfo<'_>], notional: u64) {
+///     // Require two segmenters to sign large trades
+///     let k = if notional >= 1_000_000 { 2 } else { 1 };
+///     if let Err(rejection) = verify_segmenter_quorum(registry, segmenters, k) {
+///         msg!("Not invoked by a quorum of segmenters: {}", rejection);
+///         return;
+///     }
+///
+///     // Fill the trade here
+/// }
+/// ```
+pub fn verify_segmenter_quorum<A: AccountView>(
+    registry: &impl AccountView,
+    segmenters: &[A],
+    k: usize,
+) -> Result<usize, QuorumRejection> {
+    let members = quorum_members(registry, segmenters)?;
+    let required = k.max(1);
+    if members.len() < required {
+        return Err(QuorumRejection::QuorumNotReached {
+            signers: members.len(),
+            required,
+        });
+    }
+    Ok(members.len())
+}
+
+/// Checks whether at least `k` distinct segmenters in the registry signed the invocation and
+/// verifies the origin they claimed, like [`verify_origin`](crate::verify_origin).
+/// `claimed_origins` holds the origin each of `segmenters` claimed, in the same order. The origin
+/// is only trusted if every counted segmenter claimed the same one; otherwise it is
+/// [`origin_identity::UNKNOWN`].
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::{verify_origin_quorum, Origin, VerifyOriginResult};
+/// use solana_program::account_info::AccountInfo;
+///
+/// fn handler(
+///     registry: &AccountInfo<'_>,
+///     segmenters: &[AccountInfo<'_>],
+///     claimed_origins: &[Origin],
+/// ) {
+///     let VerifyOriginResult::InvokedBySegmenter(origin) =
+///         verify_origin_quorum(claimed_origins, registry, segmenters, 2)
+///     else {
+///         // The invocation wasn't signed by two segmenters
+///         return;
+///     };
+///
+///     // Branch on the origin here
+/// }
+/// ```
+pub fn verify_origin_quorum<A: AccountView>(
+    claimed_origins: &[Origin],
+    registry: &impl AccountView,
+    segmenters: &[A],
+    k: usize,
+) -> VerifyOriginResult {
+    if claimed_origins.len() != segmenters.len() {
+        return VerifyOriginResult::NotInvokedBySegmenter;
+    }
+    let Ok(members) = quorum_members(registry, segmenters) else {
+        return VerifyOriginResult::NotInvokedBySegmenter;
+    };
+    if members.len() < k.max(1) {
+        return VerifyOriginResult::NotInvokedBySegmenter;
+    }
+
+    if registry.key() != &ORIGIN_VERIFYING_SEGMENTER_REGISTRY {
+        return VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN);
+    }
+
+    let origin = claimed_origins[members[0]];
+    if members.iter().any(|&i| claimed_origins[i] != origin) {
+        return VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN);
+    }
+    VerifyOriginResult::InvokedBySegmenter(origin)
+}
+
+/// The indexes of the segmenters that signed the invocation and are registered in the registry
+fn quorum_members<A: AccountView>(
+    registry: &impl AccountView,
+    segmenters: &[A],
+) -> Result<Vec<usize>, QuorumRejection> {
+    if *registry.owner() != Registry::PROGRAM_ID {
+        return Err(QuorumRejection::InvalidRegistry(
+            SegmenterRejection::InvalidRegistryOwner,
+        ));
+    }
+    for (i, segmenter) in segmenters.iter().enumerate() {
+        if segmenters[..i]
+            .iter()
+            .any(|other| other.key() == segmenter.key())
+        {
+            return Err(QuorumRejection::DuplicateSegmenter(*segmenter.key()));
+        }
+    }
+
+    let Some(registry_account_data) = registry.try_borrow_data() else {
+        let rejection = SegmenterRejection::RegistryAlreadyBorrowed;
+        return Err(QuorumRejection::InvalidRegistry(rejection));
+    };
+    let registry_state = Registry::try_deserialize(&registry_account_data)
+        .map_err(|error| QuorumRejection::InvalidRegistry(error.into()))?;
+
+    Ok(segmenters
+        .iter()
+        .enumerate()
+        .filter(|(_, segmenter)| {
+            segmenter.is_signer() && registry_state.is_segmenter_registered(segmenter.key())
+        })
+        .map(|(i, _)| i)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::{account_info::AccountInfo, system_program};
+
+    use crate::test_utils::registry_data_with;
+
+    use super::*;
+
+    #[test]
+    fn test_segmenter_quorum() {
+        let keys = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let unregistered = Pubkey::new_unique();
+
+        let mut registry_data = registry_data_with(&keys);
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut lamports = [0; 4];
+        let mut data: [[u8; 0]; 4] = [[]; 4];
+        let [l0, l1, l2, l3] = &mut lamports;
+        let [d0, d1, d2, d3] = &mut data;
+        let segmenter = |key, is_signer, lamports, data| AccountInfo {
+            key,
+            is_signer,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(lamports)),
+            data: Rc::new(RefCell::new(data)),
+            owner: &system_program::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+        let a = segmenter(&keys[0], true, l0, d0);
+        let b = segmenter(&keys[1], true, l1, d1);
+        let c = segmenter(&keys[2], false, l2, d2);
+        let d = segmenter(&unregistered, true, l3, d3);
+
+        // Two distinct registered signers
+        let segmenters = [a.clone(), b.clone(), c.clone(), d.clone()];
+        assert_eq!(verify_segmenter_quorum(&registry, &segmenters, 2), Ok(2));
+        assert_eq!(
+            verify_segmenter_quorum(&registry, &segmenters, 3),
+            Err(QuorumRejection::QuorumNotReached {
+                signers: 2,
+                required: 3,
+            })
+        );
+
+        // A quorum of 0 still requires a signer
+        assert_eq!(
+            verify_segmenter_quorum(&registry, &[c.clone()], 0),
+            Err(QuorumRejection::QuorumNotReached {
+                signers: 0,
+                required: 1,
+            })
+        );
+
+        // The same segmenter can't count twice
+        assert_eq!(
+            verify_segmenter_quorum(&registry, &[a.clone(), b.clone(), a.clone()], 2),
+            Err(QuorumRejection::DuplicateSegmenter(keys[0]))
+        );
+
+        // Invalid registry
+        let wrong_owner = AccountInfo {
+            owner: &system_program::ID,
+            ..registry.clone()
+        };
+        assert_eq!(
+            verify_segmenter_quorum(&wrong_owner, &segmenters, 1),
+            Err(QuorumRejection::InvalidRegistry(
+                SegmenterRejection::InvalidRegistryOwner
+            ))
+        );
+    }
+
+    #[test]
+    fn test_origin_quorum() {
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let mut registry_data = registry_data_with(&keys);
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut lamports = [0; 2];
+        let mut data: [[u8; 0]; 2] = [[]; 2];
+        let [l0, l1] = &mut lamports;
+        let [d0, d1] = &mut data;
+        let segmenter = |key, lamports, data| AccountInfo {
+            key,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(lamports)),
+            data: Rc::new(RefCell::new(data)),
+            owner: &system_program::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+        let segmenters = [segmenter(&keys[0], l0, d0), segmenter(&keys[1], l1, d1)];
+
+        // The segmenters agree
+        let agreeing = [origin_identity::DFLOW_WEB, origin_identity::DFLOW_WEB];
+        assert_eq!(
+            verify_origin_quorum(&agreeing, &registry, &segmenters, 2),
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::DFLOW_WEB)
+        );
+
+        // The segmenters disagree
+        let disagreeing = [origin_identity::DFLOW_WEB, Origin(3)];
+        assert_eq!(
+            verify_origin_quorum(&disagreeing, &registry, &segmenters, 2),
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN)
+        );
+
+        // Quorum not reached
+        assert_eq!(
+            verify_origin_quorum(&agreeing, &registry, &segmenters, 3),
+            VerifyOriginResult::NotInvokedBySegmenter
+        );
+
+        // A claimed origin is missing
+        assert_eq!(
+            verify_origin_quorum(&agreeing[..1], &registry, &segmenters, 1),
+            VerifyOriginResult::NotInvokedBySegmenter
+        );
+
+        // Non-verifying registry
+        let non_verifying = AccountInfo {
+            key: &Pubkey::new_unique(),
+            ..registry.clone()
+        };
+        assert_eq!(
+            verify_origin_quorum(&agreeing, &non_verifying, &segmenters, 2),
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN)
+        );
+    }
+}
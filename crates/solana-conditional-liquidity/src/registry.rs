@@ -1,5 +1,7 @@
 use bytemuck::{Pod, Zeroable};
-use solana_program::{account_info::AccountInfo, pubkey, pubkey::Pubkey};
+use solana_program::{pubkey, pubkey::Pubkey};
+
+use crate::{heartbeat::check_heartbeat, AccountView};
 
 const MAX_ITEMS: usize = 64;
 
@@ -18,22 +20,129 @@ impl Registry {
         self.registered_segmenters.binary_search(key).is_ok()
     }
 
-    pub fn deserialize(bytes: &[u8]) -> &Self {
-        bytemuck::from_bytes(&bytes[8..])
+    /// Deserializes registry account data, including the discriminator. Never panics, regardless of
+    /// the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Result<&Self, RegistryDeserializeError> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return Err(RegistryDeserializeError::InvalidDiscriminator);
+        }
+
+        // Like Anchor, tolerate trailing bytes beyond the registry state
+        let data = bytes
+            .get(8..8 + std::mem::size_of::<Self>())
+            .ok_or(RegistryDeserializeError::InvalidLength)?;
+        bytemuck::try_from_bytes(data).map_err(|_| RegistryDeserializeError::InvalidLength)
+    }
+}
+
+/// The reason registry account data could not be deserialized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryDeserializeError {
+    /// The account data doesn't start with the registry discriminator
+    InvalidDiscriminator,
+    /// The account data is too short to hold a registry
+    InvalidLength,
+}
+
+impl std::fmt::Display for RegistryDeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::InvalidDiscriminator => "registry account has an invalid discriminator",
+            Self::InvalidLength => "registry account data is too short",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for RegistryDeserializeError {}
+
+/// Proof that an invocation was signed by a segmenter, returned by [`check_segmenter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmenterProof {
+    /// The registry the segmenter was found in
+    pub registry: Pubkey,
+    /// The public key of the segmenter that signed the invocation
+    pub segmenter: Pubkey,
+}
+
+/// The reason an invocation was not treated as signed by a segmenter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmenterRejection {
+    /// The registry account isn't owned by the segmenter registry program
+    InvalidRegistryOwner,
+    /// The segmenter account didn't sign the invocation
+    SegmenterNotSigner,
+    /// The registry account data doesn't start with the registry discriminator
+    InvalidRegistryDiscriminator,
+    /// The registry account data is too short to hold a registry
+    InvalidRegistryData,
+    /// The registry account data is already mutably borrowed, e.g. because the same account was
+    /// passed more than once
+    RegistryAlreadyBorrowed,
+    /// The segmenter isn't registered in the registry
+    SegmenterNotRegistered,
+    /// The heartbeat tracker account is neither the registry's heartbeat tracker nor the address
+    /// of one that wasn't created
+    InvalidHeartbeatTracker,
+    /// The heartbeat tracker is of another registry
+    HeartbeatTrackerMismatch,
+    /// The segmenter's last heartbeat is older than the registry's maximum heartbeat age
+    SegmenterStale,
+}
+
+impl std::fmt::Display for SegmenterRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::InvalidRegistryOwner => "registry is not owned by the segmenter registry program",
+            Self::SegmenterNotSigner => "segmenter did not sign the invocation",
+            Self::InvalidRegistryDiscriminator => "registry account has an invalid discriminator",
+            Self::InvalidRegistryData => "registry account data is too short",
+            Self::RegistryAlreadyBorrowed => "registry account data is already mutably borrowed",
+            Self::SegmenterNotRegistered => "segmenter is not registered in the registry",
+            Self::InvalidHeartbeatTracker => "heartbeat tracker is not a heartbeat tracker account",
+            Self::HeartbeatTrackerMismatch => "heartbeat tracker is of another registry",
+            Self::SegmenterStale => "segmenter's last heartbeat is too old",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for SegmenterRejection {}
+
+impl From<RegistryDeserializeError> for SegmenterRejection {
+    fn from(error: RegistryDeserializeError) -> Self {
+        match error {
+            RegistryDeserializeError::InvalidDiscriminator => Self::InvalidRegistryDiscriminator,
+            RegistryDeserializeError::InvalidLength => Self::InvalidRegistryData,
+        }
     }
 }
 
 /// Checks whether the invocation was signed by a segmenter. Use this if you don't need to branch on
 /// the origin of the invocation.
 ///
+/// Program segmenters are accepted too: their PDA is registered like any other segmenter, and
+/// signs when the program invokes with `invoke_signed`. See
+/// [`check_program_segmenter`](crate::check_program_segmenter).
+///
+/// `heartbeat_tracker` must be the registry's heartbeat tracker, at
+/// [`HeartbeatTracker::find_address`](crate::HeartbeatTracker::find_address), even if it wasn't
+/// created. If the registry has a maximum heartbeat age, segmenters whose last heartbeat is older
+/// than that as of `current_slot` are treated as inactive.
+///
 /// Examples
 ///
 /// ```
 /// use solana_conditional_liquidity::is_invoked_by_segmenter;
-/// use solana_program::account_info::AccountInfo;
+/// use solana_program::{account_info::AccountInfo, clock::Clock, sysvar::Sysvar};
 ///
-/// fn handler(registry: &AccountInfo<'_>, segmenter: &AccountInfo<'_>) {
-///     if !is_invoked_by_segmenter(registry, segmenter) {
+/// fn handler(
+///     registry: &AccountInfo<'_>,
+///     heartbeat_tracker: &AccountInfo<'_>,
+///     segmenter: &AccountInfo<'_>,
+/// ) {
+///     let current_slot = Clock::get().unwrap().slot;
+///     if !is_invoked_by_segmenter(current_slot, registry, heartbeat_tracker, segmenter) {
 ///         // The invocation wasn't signed by a segmenter
 ///         return;
 ///     }
@@ -42,21 +151,93 @@ impl Registry {
 ///     // ...
 /// }
 /// ```
-pub fn is_invoked_by_segmenter(registry: &AccountInfo<'_>, segmenter: &AccountInfo<'_>) -> bool {
-    if *registry.owner != Registry::PROGRAM_ID {
-        return false;
+pub fn is_invoked_by_segmenter(
+    current_slot: u64,
+    registry: &impl AccountView,
+    heartbeat_tracker: &impl AccountView,
+    segmenter: &impl AccountView,
+) -> bool {
+    check_segmenter(current_slot, registry, heartbeat_tracker, segmenter).is_ok()
+}
+
+/// Checks whether the invocation was signed by a segmenter, returning the reason when it wasn't. Use
+/// this instead of [`is_invoked_by_segmenter`] if you want to log why an invocation was rejected.
+/// The heartbeat tracker is checked like in [`is_invoked_by_segmenter`].
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::check_segmenter;
+/// use solana_program::{account_info::AccountInfo, clock::Clock, msg, sysvar::Sysvar};
+///
+/// fn handler(
+///     registry: &AccountInfo<'_>,
+///     heartbeat_tracker: &AccountInfo<'_>,
+///     segmenter: &AccountInfo<'_>,
+/// ) {
+///     let current_slot = Clock::get().unwrap().slot;
+///     let proof = match check_segmenter(current_slot, registry, heartbeat_tracker, segmenter) {
+///         Ok(proof) => proof,
+///         Err(rejection) => {
+///             msg!("Not invoked by a segmenter: {}", rejection);
+///             return;
+///         }
+///     };
+///
+///     msg!("Invoked by segmenter {} from registry {}", proof.segmenter, proof.registry);
+/// }
+/// ```
+pub fn check_segmenter(
+    current_slot: u64,
+    registry: &impl AccountView,
+    heartbeat_tracker: &impl AccountView,
+    segmenter: &impl AccountView,
+) -> Result<SegmenterProof, SegmenterRejection> {
+    let proof = check_registered_signer(registry, segmenter)?;
+    check_heartbeat(
+        current_slot,
+        registry.key(),
+        heartbeat_tracker,
+        segmenter.key(),
+    )?;
+    Ok(proof)
+}
+
+/// Checks that `segmenter` signed the invocation and is registered in the registry, without
+/// checking its heartbeat
+pub(crate) fn check_registered_signer(
+    registry: &impl AccountView,
+    segmenter: &impl AccountView,
+) -> Result<SegmenterProof, SegmenterRejection> {
+    if *registry.owner() != Registry::PROGRAM_ID {
+        return Err(SegmenterRejection::InvalidRegistryOwner);
     }
-    if !segmenter.is_signer {
-        return false;
+    if !segmenter.is_signer() {
+        return Err(SegmenterRejection::SegmenterNotSigner);
     }
 
-    let registry_account_data = registry.data.borrow();
-    if registry_account_data[..8] != Registry::DISCRIMINATOR {
-        return false;
-    }
+    check_registered(registry, segmenter.key())?;
 
-    let registry_state = Registry::deserialize(&registry_account_data);
-    registry_state.is_segmenter_registered(segmenter.key)
+    Ok(SegmenterProof {
+        registry: *registry.key(),
+        segmenter: *segmenter.key(),
+    })
+}
+
+/// Checks that `segmenter` is registered in the registry. The caller must check the registry's
+/// owner and how the segmenter proved itself.
+pub(crate) fn check_registered(
+    registry: &impl AccountView,
+    segmenter: &Pubkey,
+) -> Result<(), SegmenterRejection> {
+    let registry_account_data = registry
+        .try_borrow_data()
+        .ok_or(SegmenterRejection::RegistryAlreadyBorrowed)?;
+    let registry_state = Registry::try_deserialize(&registry_account_data)?;
+    if !registry_state.is_segmenter_registered(segmenter) {
+        return Err(SegmenterRejection::SegmenterNotRegistered);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -64,7 +245,9 @@ mod tests {
     use std::{cell::RefCell, rc::Rc};
 
     use base64::Engine;
-    use solana_program::system_program;
+    use solana_program::{account_info::AccountInfo, system_program};
+
+    use crate::test_utils::{heartbeat_tracker, registry_data, SEGMENTER};
 
     use super::*;
 
@@ -87,30 +270,94 @@ mod tests {
         };
 
         let data = registry.data.borrow();
-        Registry::deserialize(&data);
+        let registry_state = Registry::try_deserialize(&data).unwrap();
+        assert!(registry_state
+            .is_segmenter_registered(&pubkey!("F2Me9XknvkPYjvoEgTXXKqARg58Ezo6ZmGhpYdS3UTmF")));
     }
 
     #[test]
-    #[should_panic]
     fn test_deserialize_invalid_registry_account() {
-        let mut registry_data = base64::engine::general_purpose::STANDARD
-            .decode("L65u9ri2/NoAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAADmpy9pZ0kSP2HFqsTSMjxFxburQjxQioc8A4BaVKbWtBAWsWrJs+pByyagNT2RTZ5E/wZAQB0FNhQpY/WTUev0FyJOST1AtAGCROxwJ16TmEm/91X11Lmzcymw9zcmibQnqAzYRp+mQPsmo+2htSt8O7nGFhRpTDQdq9qCtx9jA==")
-            .unwrap();
+        let registry_data = registry_data();
 
-        let mut registry_lamports = 15200640;
-        let registry = AccountInfo {
-            key: &pubkey!("Reg1Y127DNKYUTf3LinfEs3oiSiywJsyAobJMjqYqDE"),
-            is_signer: false,
-            is_writable: false,
-            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
-            data: Rc::new(RefCell::new(&mut registry_data)),
-            owner: &Registry::PROGRAM_ID,
-            executable: false,
-            rent_epoch: 18446744073709551615,
-        };
+        assert_eq!(
+            Registry::try_deserialize(&[]).err(),
+            Some(RegistryDeserializeError::InvalidDiscriminator)
+        );
+        assert_eq!(
+            Registry::try_deserialize(&registry_data[..7]).err(),
+            Some(RegistryDeserializeError::InvalidDiscriminator)
+        );
+        assert_eq!(
+            Registry::try_deserialize(&registry_data[..8]).err(),
+            Some(RegistryDeserializeError::InvalidLength)
+        );
+        assert_eq!(
+            Registry::try_deserialize(&registry_data[..registry_data.len() - 1]).err(),
+            Some(RegistryDeserializeError::InvalidLength)
+        );
 
-        let data = registry.data.borrow();
-        Registry::deserialize(&data);
+        let mut wrong_discriminator = registry_data.clone();
+        wrong_discriminator[7] ^= 0xff;
+        assert_eq!(
+            Registry::try_deserialize(&wrong_discriminator).err(),
+            Some(RegistryDeserializeError::InvalidDiscriminator)
+        );
+
+        // Trailing bytes are ignored
+        let mut extended = registry_data.clone();
+        extended.extend_from_slice(&[0xff; 32]);
+        assert!(Registry::try_deserialize(&extended).is_ok());
+
+        // Unaligned data deserializes too
+        let mut unaligned = vec![0];
+        unaligned.extend_from_slice(&registry_data);
+        assert!(Registry::try_deserialize(&unaligned[1..]).is_ok());
+    }
+
+    #[test]
+    fn test_check_segmenter_never_panics_on_registry_data() {
+        let segmenter_key = SEGMENTER;
+        let registry_data = registry_data();
+
+        let mut candidates = vec![];
+        for len in 0..=registry_data.len() + 1 {
+            let mut data = registry_data.clone();
+            data.resize(len, 0xff);
+            candidates.push(data);
+        }
+        for idx in (0..registry_data.len()).step_by(7) {
+            let mut data = registry_data.clone();
+            data[idx] = data[idx].wrapping_add(1);
+            candidates.push(data);
+        }
+
+        for mut data in candidates {
+            let registry_key = Pubkey::new_unique();
+            let mut registry_lamports = 0;
+            let registry = AccountInfo {
+                key: &registry_key,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+                data: Rc::new(RefCell::new(&mut data)),
+                owner: &Registry::PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            };
+            let mut segmenter_lamports = 0;
+            let mut segmenter_data = [];
+            let segmenter = AccountInfo {
+                key: &segmenter_key,
+                is_signer: true,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+                data: Rc::new(RefCell::new(&mut segmenter_data)),
+                owner: &system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            };
+            let _ = check_segmenter(0, &registry, &heartbeat_tracker(registry.key), &segmenter);
+        }
     }
 
     #[test]
@@ -144,7 +391,12 @@ mod tests {
             executable: false,
             rent_epoch: 18446744073709551615,
         };
-        assert!(is_invoked_by_segmenter(&registry, &segmenter));
+        assert!(is_invoked_by_segmenter(
+            0,
+            &registry,
+            &heartbeat_tracker(registry.key),
+            &segmenter
+        ));
 
         // Didn't sign but exists in registry
         let mut segmenter_lamports = 0;
@@ -159,7 +411,12 @@ mod tests {
             executable: false,
             rent_epoch: 18446744073709551615,
         };
-        assert!(!is_invoked_by_segmenter(&registry, &segmenter));
+        assert!(!is_invoked_by_segmenter(
+            0,
+            &registry,
+            &heartbeat_tracker(registry.key),
+            &segmenter
+        ));
 
         // Signed but doesn't exist in registry
         let mut segmenter_lamports = 0;
@@ -174,7 +431,12 @@ mod tests {
             executable: false,
             rent_epoch: 18446744073709551615,
         };
-        assert!(!is_invoked_by_segmenter(&registry, &segmenter));
+        assert!(!is_invoked_by_segmenter(
+            0,
+            &registry,
+            &heartbeat_tracker(registry.key),
+            &segmenter
+        ));
     }
 
     #[test]
@@ -208,7 +470,12 @@ mod tests {
             executable: false,
             rent_epoch: 18446744073709551615,
         };
-        assert!(!is_invoked_by_segmenter(&registry, &segmenter));
+        assert!(!is_invoked_by_segmenter(
+            0,
+            &registry,
+            &heartbeat_tracker(registry.key),
+            &segmenter
+        ));
 
         // Didn't sign but exists in registry
         let mut segmenter_lamports = 0;
@@ -223,7 +490,12 @@ mod tests {
             executable: false,
             rent_epoch: 18446744073709551615,
         };
-        assert!(!is_invoked_by_segmenter(&registry, &segmenter));
+        assert!(!is_invoked_by_segmenter(
+            0,
+            &registry,
+            &heartbeat_tracker(registry.key),
+            &segmenter
+        ));
 
         // Signed but doesn't exist in registry
         let mut segmenter_lamports = 0;
@@ -238,6 +510,191 @@ mod tests {
             executable: false,
             rent_epoch: 18446744073709551615,
         };
-        assert!(!is_invoked_by_segmenter(&registry, &segmenter));
+        assert!(!is_invoked_by_segmenter(
+            0,
+            &registry,
+            &heartbeat_tracker(registry.key),
+            &segmenter
+        ));
+    }
+
+    #[test]
+    fn test_check_segmenter() {
+        let registry_key = Pubkey::new_unique();
+        let segmenter_key = SEGMENTER;
+
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 15200640;
+        let registry = AccountInfo {
+            key: &registry_key,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: 18446744073709551615,
+        };
+
+        // Signed and exists in registry
+        let mut segmenter_lamports = 0;
+        let mut segmenter_data = [];
+        let segmenter = AccountInfo {
+            key: &segmenter_key,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+            data: Rc::new(RefCell::new(&mut segmenter_data)),
+            owner: &system_program::id(),
+            executable: false,
+            rent_epoch: 18446744073709551615,
+        };
+        assert_eq!(
+            check_segmenter(0, &registry, &heartbeat_tracker(registry.key), &segmenter),
+            Ok(SegmenterProof {
+                registry: registry_key,
+                segmenter: segmenter_key,
+            })
+        );
+
+        // Didn't sign but exists in registry
+        let unsigned_segmenter = AccountInfo {
+            is_signer: false,
+            ..segmenter.clone()
+        };
+        assert_eq!(
+            check_segmenter(
+                0,
+                &registry,
+                &heartbeat_tracker(registry.key),
+                &unsigned_segmenter
+            ),
+            Err(SegmenterRejection::SegmenterNotSigner)
+        );
+
+        // Signed but doesn't exist in registry
+        let unregistered_key = Pubkey::new_unique();
+        let unregistered_segmenter = AccountInfo {
+            key: &unregistered_key,
+            ..segmenter.clone()
+        };
+        assert_eq!(
+            check_segmenter(
+                0,
+                &registry,
+                &heartbeat_tracker(registry.key),
+                &unregistered_segmenter
+            ),
+            Err(SegmenterRejection::SegmenterNotRegistered)
+        );
+
+        // Registry isn't owned by the segmenter registry program
+        let wrong_owner = Pubkey::new_unique();
+        let wrongly_owned_registry = AccountInfo {
+            owner: &wrong_owner,
+            ..registry.clone()
+        };
+        assert_eq!(
+            check_segmenter(
+                0,
+                &wrongly_owned_registry,
+                &heartbeat_tracker(wrongly_owned_registry.key),
+                &segmenter
+            ),
+            Err(SegmenterRejection::InvalidRegistryOwner)
+        );
+
+        // Registry data has the wrong discriminator
+        registry.data.borrow_mut()[0] ^= 0xff;
+        assert_eq!(
+            check_segmenter(0, &registry, &heartbeat_tracker(registry.key), &segmenter),
+            Err(SegmenterRejection::InvalidRegistryDiscriminator)
+        );
+    }
+
+    #[test]
+    fn test_check_segmenter_aliased_accounts() {
+        let registry_key = Pubkey::new_unique();
+        let segmenter_key = SEGMENTER;
+
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 15200640;
+        let registry = AccountInfo {
+            key: &registry_key,
+            is_signer: false,
+            is_writable: true,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: 18446744073709551615,
+        };
+
+        let mut segmenter_lamports = 0;
+        let mut segmenter_data = [];
+        let segmenter = AccountInfo {
+            key: &segmenter_key,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+            data: Rc::new(RefCell::new(&mut segmenter_data)),
+            owner: &system_program::id(),
+            executable: false,
+            rent_epoch: 18446744073709551615,
+        };
+
+        // The same registry account passed twice, with the caller holding a mutable borrow of it
+        let registry_alias = registry.clone();
+        {
+            let _held = registry_alias.data.borrow_mut();
+            assert_eq!(
+                check_segmenter(0, &registry, &heartbeat_tracker(registry.key), &segmenter),
+                Err(SegmenterRejection::RegistryAlreadyBorrowed)
+            );
+            assert!(!is_invoked_by_segmenter(
+                0,
+                &registry,
+                &heartbeat_tracker(registry.key),
+                &segmenter
+            ));
+        }
+
+        // Shared borrows held by the caller don't prevent the check
+        {
+            let _held = registry_alias.data.borrow();
+            assert!(is_invoked_by_segmenter(
+                0,
+                &registry,
+                &heartbeat_tracker(registry.key),
+                &segmenter
+            ));
+        }
+
+        // The registry account passed as its own segmenter
+        let signing_registry = AccountInfo {
+            is_signer: true,
+            ..registry.clone()
+        };
+        assert_eq!(
+            check_segmenter(
+                0,
+                &signing_registry,
+                &heartbeat_tracker(signing_registry.key),
+                &signing_registry
+            ),
+            Err(SegmenterRejection::SegmenterNotRegistered)
+        );
+        {
+            let _held = registry_alias.data.borrow_mut();
+            assert_eq!(
+                check_segmenter(
+                    0,
+                    &signing_registry,
+                    &heartbeat_tracker(signing_registry.key),
+                    &signing_registry
+                ),
+                Err(SegmenterRejection::RegistryAlreadyBorrowed)
+            );
+        }
     }
 }
@@ -0,0 +1,171 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{
+    directory::{config_address, padded_str},
+    AuditLog, Registry,
+};
+
+const MAX_TAGS: usize = 8;
+
+/// Descriptive metadata of a registry, owned by the segmenter registry program and set by the
+/// account that created the registry. The strings are padded with zero bytes; use the accessors
+/// for the strings themselves.
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct RegistryMetadata {
+    pub registry: Pubkey,
+    /// The account that can update the metadata
+    pub authority: Pubkey,
+    pub name: [u8; 32],
+    /// A URI of a longer description of the registry
+    pub description_uri: [u8; 256],
+    /// Tags describing what the registry is for
+    pub tags: [[u8; 16]; MAX_TAGS],
+}
+
+impl RegistryMetadata {
+    pub const DISCRIMINATOR: [u8; 8] = [194, 192, 202, 183, 171, 176, 49, 178];
+    pub const SEED: &'static [u8] = b"registry_metadata";
+
+    /// The address of the metadata of `registry`
+    pub fn find_address(registry: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, registry.as_ref()], &Registry::PROGRAM_ID)
+    }
+
+    /// Deserializes registry metadata account data, including the discriminator. Returns `None` if
+    /// the data isn't registry metadata. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+
+    /// The name of the registry, empty if unset or not valid UTF-8
+    pub fn name(&self) -> &str {
+        padded_str(&self.name)
+    }
+
+    /// The description URI of the registry, empty if unset or not valid UTF-8
+    pub fn description_uri(&self) -> &str {
+        padded_str(&self.description_uri)
+    }
+
+    /// The tags of the registry that are set
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.tags
+            .iter()
+            .map(|tag| padded_str(tag))
+            .filter(|tag| !tag.is_empty())
+    }
+}
+
+/// Builds the segmenter registry instruction that creates the metadata of `registry`, with
+/// `authority` as the account that can update it. `authority` must be the account that created the
+/// registry according to `directory_page`, the directory page listing the registry, or the admin,
+/// in which case `directory_page` can be omitted. `authority` and `payer` must sign.
+pub fn create_registry_metadata_instruction(
+    registry: &Pubkey,
+    directory_page: Option<&Pubkey>,
+    authority: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [50, 127, 25, 61, 243, 215, 65, 133];
+
+    let (registry_metadata, _) = RegistryMetadata::find_address(registry);
+    // Anchor treats the program id as a missing optional account
+    let directory_page = directory_page.unwrap_or(&Registry::PROGRAM_ID);
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(registry_metadata, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new(AuditLog::find_address(registry).0, false),
+            AccountMeta::new_readonly(*directory_page, false),
+            AccountMeta::new_readonly(config_address(), false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: DISCRIMINATOR.to_vec(),
+    }
+}
+
+/// Builds the segmenter registry instruction that sets the name, description URI and tags of
+/// `registry`, which must be at most 32, 256 and 16 bytes long, with at most 8 tags. `authority`
+/// must sign.
+pub fn set_registry_metadata_instruction(
+    registry: &Pubkey,
+    authority: &Pubkey,
+    name: &str,
+    description_uri: &str,
+    tags: &[&str],
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [104, 73, 160, 79, 18, 169, 205, 24];
+
+    let (registry_metadata, _) = RegistryMetadata::find_address(registry);
+    let mut data = DISCRIMINATOR.to_vec();
+    for value in [name, description_uri] {
+        data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        data.extend_from_slice(value.as_bytes());
+    }
+    data.extend_from_slice(&(tags.len() as u32).to_le_bytes());
+    for tag in tags {
+        data.extend_from_slice(&(tag.len() as u32).to_le_bytes());
+        data.extend_from_slice(tag.as_bytes());
+    }
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(registry_metadata, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_metadata() {
+        let mut metadata = RegistryMetadata::zeroed();
+        metadata.name[..5].copy_from_slice(b"dflow");
+        metadata.description_uri[..19].copy_from_slice(b"https://dflow.net/r");
+        metadata.tags[0][..6].copy_from_slice(b"origin");
+        metadata.tags[2][..4].copy_from_slice(b"spot");
+
+        let mut data = RegistryMetadata::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&metadata));
+        let metadata = RegistryMetadata::try_deserialize(&data).unwrap();
+        assert_eq!(metadata.name(), "dflow");
+        assert_eq!(metadata.description_uri(), "https://dflow.net/r");
+        assert_eq!(metadata.tags().collect::<Vec<_>>(), ["origin", "spot"]);
+
+        // Truncated or for another account type
+        assert!(RegistryMetadata::try_deserialize(&data[..data.len() - 1]).is_none());
+        assert!(RegistryMetadata::try_deserialize(&data[8..]).is_none());
+    }
+
+    #[test]
+    fn test_set_registry_metadata_instruction() {
+        let instruction = set_registry_metadata_instruction(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            "a",
+            "b",
+            &["c", "d"],
+        );
+        assert_eq!(
+            &instruction.data[8..],
+            b"\x01\0\0\0a\x01\0\0\0b\x02\0\0\0\x01\0\0\0c\x01\0\0\0d"
+        );
+    }
+}
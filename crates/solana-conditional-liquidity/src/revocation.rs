@@ -0,0 +1,179 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::Registry;
+
+/// Why a segmenter was removed from a registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevocationReason {
+    /// The segmenter's key was compromised
+    Compromised,
+    /// The segmenter was retired by its operator
+    Retired,
+    /// The segmenter misbehaved
+    Misbehavior,
+}
+
+/// A tombstone for a segmenter removed from a registry, recording its latest removal. Revocations
+/// are owned by the segmenter registry program, created by the first removal of the segmenter and
+/// never closed, so they are kept however many segmenters are removed.
+#[derive(Pod, Zeroable, Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct Revocation {
+    pub registry: Pubkey,
+    /// The segmenter that was removed
+    pub key: Pubkey,
+    /// The slot of the latest removal
+    pub slot: u64,
+    /// The number of times the segmenter was removed, 0 if it wasn't registered when removed
+    pub count: u64,
+    /// The [`RevocationReason`] of the latest removal as a `u8`. Use [`Revocation::reason`] for
+    /// the reason.
+    pub reason: u8,
+    pub padding: [u8; 7],
+}
+
+impl Revocation {
+    pub const DISCRIMINATOR: [u8; 8] = [128, 117, 129, 229, 11, 159, 79, 234];
+    pub const SEED: &'static [u8] = b"revocation";
+
+    /// The address of the revocation of `key` in `registry`
+    pub fn find_address(registry: &Pubkey, key: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, registry.as_ref(), key.as_ref()],
+            &Registry::PROGRAM_ID,
+        )
+    }
+
+    /// Deserializes revocation account data, including the discriminator. Returns `None` if the
+    /// data isn't a revocation. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+
+    /// The reason for the latest removal, `None` if it is a reason this version doesn't know about
+    pub fn reason(&self) -> Option<RevocationReason> {
+        match self.reason {
+            0 => Some(RevocationReason::Compromised),
+            1 => Some(RevocationReason::Retired),
+            2 => Some(RevocationReason::Misbehavior),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a key is a segmenter of a registry, returned by [`segmenter_status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmenterStatus {
+    /// The key is registered
+    Registered,
+    /// The key isn't registered, and was removed from the registry
+    Revoked(Revocation),
+    /// The key isn't registered and was never removed, so it was never registered
+    NotRegistered,
+}
+
+/// The status of `key` in `registry`, distinguishing keys that were revoked from keys that were
+/// never registered, e.g. for logs and analytics. `revocation` must be the revocation of `key` in
+/// `registry`, at [`Revocation::find_address`], or `None` if that account doesn't exist. A key
+/// that was revoked and registered again is [`SegmenterStatus::Registered`].
+pub fn segmenter_status(
+    registry: &Registry,
+    revocation: Option<&Revocation>,
+    key: &Pubkey,
+) -> SegmenterStatus {
+    if *key != Pubkey::default() && registry.is_segmenter_registered(key) {
+        return SegmenterStatus::Registered;
+    }
+    match revocation {
+        Some(revocation) if revocation.key == *key && revocation.count > 0 => {
+            SegmenterStatus::Revoked(*revocation)
+        }
+        _ => SegmenterStatus::NotRegistered,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::SEGMENTER;
+
+    use super::*;
+
+    #[test]
+    fn test_segmenter_status() {
+        let mut registry = Registry {
+            registered_segmenters: [Pubkey::default(); 64],
+        };
+        let revocation = Revocation {
+            key: SEGMENTER,
+            slot: 12,
+            count: 2,
+            reason: RevocationReason::Compromised as u8,
+            ..Revocation::zeroed()
+        };
+
+        let SegmenterStatus::Revoked(revoked) =
+            segmenter_status(&registry, Some(&revocation), &SEGMENTER)
+        else {
+            panic!("segmenter should be revoked");
+        };
+        assert_eq!(revoked.slot, 12);
+        assert_eq!(revoked.reason(), Some(RevocationReason::Compromised));
+        assert_eq!(
+            segmenter_status(&registry, None, &SEGMENTER),
+            SegmenterStatus::NotRegistered
+        );
+        assert_eq!(
+            segmenter_status(&registry, None, &Pubkey::default()),
+            SegmenterStatus::NotRegistered
+        );
+
+        // A revocation of another key, or of a key that wasn't registered when removed, is ignored
+        assert_eq!(
+            segmenter_status(&registry, Some(&revocation), &Pubkey::new_unique()),
+            SegmenterStatus::NotRegistered
+        );
+        let never_revoked = Revocation {
+            count: 0,
+            ..revocation
+        };
+        assert_eq!(
+            segmenter_status(&registry, Some(&never_revoked), &SEGMENTER),
+            SegmenterStatus::NotRegistered
+        );
+
+        // Registering a revoked segmenter again takes precedence over its revocation
+        registry.registered_segmenters[63] = SEGMENTER;
+        assert_eq!(
+            segmenter_status(&registry, Some(&revocation), &SEGMENTER),
+            SegmenterStatus::Registered
+        );
+    }
+
+    #[test]
+    fn test_try_deserialize() {
+        let revocation = Revocation {
+            key: SEGMENTER,
+            count: 1,
+            ..Revocation::zeroed()
+        };
+        let mut data = Revocation::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&revocation));
+        assert_eq!(Revocation::try_deserialize(&data), Some(revocation));
+        assert_eq!(Revocation::try_deserialize(&data[..data.len() - 1]), None);
+        assert_eq!(Revocation::try_deserialize(&data[8..]), None);
+    }
+
+    #[test]
+    fn test_unknown_reason() {
+        let revocation = Revocation {
+            reason: u8::MAX,
+            ..Revocation::zeroed()
+        };
+        assert_eq!(revocation.reason(), None);
+    }
+}
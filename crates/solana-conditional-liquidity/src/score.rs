@@ -0,0 +1,89 @@
+use bytemuck::{Pod, Zeroable};
+
+/// A graded signal for how toxic a segmenter judged the flow of an invocation, from
+/// [`FlowScore::MIN`] (retail) to [`FlowScore::MAX`] (most informed or toxic)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct FlowScore(pub u8);
+
+impl FlowScore {
+    pub const MIN: Self = Self(0);
+    pub const MAX: Self = Self(u8::MAX);
+
+    /// The LP policy tier this score falls in
+    pub fn policy_tier(self, bands: &ScoreBands) -> PolicyTier {
+        bands.tier(self)
+    }
+}
+
+/// An LP policy tier, ordered from the most to the least favorable flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PolicyTier {
+    Retail,
+    Neutral,
+    Toxic,
+}
+
+/// The score bands an LP maps flow scores to policy tiers with. Scores up to and including
+/// `retail_max` are [`PolicyTier::Retail`], scores up to and including `neutral_max` are
+/// [`PolicyTier::Neutral`], and higher scores are [`PolicyTier::Toxic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreBands {
+    pub retail_max: FlowScore,
+    pub neutral_max: FlowScore,
+}
+
+impl ScoreBands {
+    /// Splits the score range into three roughly equal bands
+    pub const DEFAULT: Self = Self {
+        retail_max: FlowScore(85),
+        neutral_max: FlowScore(170),
+    };
+
+    pub fn tier(&self, score: FlowScore) -> PolicyTier {
+        if score <= self.retail_max {
+            PolicyTier::Retail
+        } else if score <= self.neutral_max {
+            PolicyTier::Neutral
+        } else {
+            PolicyTier::Toxic
+        }
+    }
+}
+
+impl Default for ScoreBands {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_bands() {
+        let bands = ScoreBands::DEFAULT;
+        assert_eq!(FlowScore::MIN.policy_tier(&bands), PolicyTier::Retail);
+        assert_eq!(FlowScore(85).policy_tier(&bands), PolicyTier::Retail);
+        assert_eq!(FlowScore(86).policy_tier(&bands), PolicyTier::Neutral);
+        assert_eq!(FlowScore(170).policy_tier(&bands), PolicyTier::Neutral);
+        assert_eq!(FlowScore(171).policy_tier(&bands), PolicyTier::Toxic);
+        assert_eq!(FlowScore::MAX.policy_tier(&bands), PolicyTier::Toxic);
+
+        // An LP that only distinguishes retail from everything else
+        let bands = ScoreBands {
+            retail_max: FlowScore(50),
+            neutral_max: FlowScore(50),
+        };
+        assert_eq!(FlowScore(50).policy_tier(&bands), PolicyTier::Retail);
+        assert_eq!(FlowScore(51).policy_tier(&bands), PolicyTier::Toxic);
+
+        // Every score is retail
+        let bands = ScoreBands {
+            retail_max: FlowScore::MAX,
+            neutral_max: FlowScore::MAX,
+        };
+        assert_eq!(FlowScore::MAX.policy_tier(&bands), PolicyTier::Retail);
+    }
+}
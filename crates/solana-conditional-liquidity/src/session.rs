@@ -0,0 +1,554 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{
+    origin_identity, registry::check_registered, AccountView, Origin, Registry, SegmenterProof,
+    SegmenterRejection, VerifyOriginResult, ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+};
+
+/// A session delegation account of the segmenter registry program, through which a registered
+/// segmenter lets a short-lived session key sign on its behalf
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct SessionDelegation {
+    /// The registered segmenter that delegated its signing rights
+    pub segmenter: Pubkey,
+    /// The key that may sign on behalf of the segmenter
+    pub session_key: Pubkey,
+    /// The DEX program the session key may sign for, or the default public key for any program
+    pub program_scope: Pubkey,
+    /// The last slot in which the session key may sign
+    pub expiry_slot: u64,
+}
+
+impl SessionDelegation {
+    pub const DISCRIMINATOR: [u8; 8] = [22, 217, 208, 40, 208, 22, 211, 200];
+    pub const SEED: &'static [u8] = b"session";
+    /// The longest a session key can be valid for, about a day
+    pub const MAX_DURATION_SLOTS: u64 = 216_000;
+
+    /// The address of the session delegation from `segmenter` to `session_key`
+    pub fn find_address(segmenter: &Pubkey, session_key: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, segmenter.as_ref(), session_key.as_ref()],
+            &Registry::PROGRAM_ID,
+        )
+    }
+
+    /// Deserializes session delegation account data, including the discriminator. Returns `None` if
+    /// the data isn't a session delegation. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+
+    /// The DEX program the session key may sign for, or `None` if it may sign for any program
+    pub fn program_scope(&self) -> Option<Pubkey> {
+        (self.program_scope != Pubkey::default()).then_some(self.program_scope)
+    }
+}
+
+/// Builds the segmenter registry instruction that delegates `segmenter`'s signing rights to
+/// `session_key` until `expiry_slot`, optionally only for the `program_scope` DEX program.
+/// `segmenter` must be registered in `registry` and sign, along with `payer`.
+pub fn register_session_key_instruction(
+    registry: &Pubkey,
+    segmenter: &Pubkey,
+    payer: &Pubkey,
+    session_key: &Pubkey,
+    expiry_slot: u64,
+    program_scope: Option<Pubkey>,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [69, 94, 60, 44, 49, 199, 183, 233];
+
+    let mut data = DISCRIMINATOR.to_vec();
+    data.extend_from_slice(session_key.as_ref());
+    data.extend_from_slice(&expiry_slot.to_le_bytes());
+    match program_scope {
+        Some(program_scope) => {
+            data.push(1);
+            data.extend_from_slice(program_scope.as_ref());
+        }
+        None => data.push(0),
+    }
+
+    let (session_delegation, _) = SessionDelegation::find_address(segmenter, session_key);
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(session_delegation, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new_readonly(*segmenter, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+/// Builds the segmenter registry instruction that revokes `segmenter`'s delegation to
+/// `session_key` before it expires. Closes the session delegation and refunds its rent to
+/// `segmenter`, which must sign.
+pub fn revoke_session_key_instruction(segmenter: &Pubkey, session_key: &Pubkey) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [81, 192, 32, 110, 104, 116, 144, 151];
+
+    let mut data = DISCRIMINATOR.to_vec();
+    data.extend_from_slice(session_key.as_ref());
+
+    let (session_delegation, _) = SessionDelegation::find_address(segmenter, session_key);
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(session_delegation, false),
+            AccountMeta::new(*segmenter, true),
+        ],
+        data,
+    }
+}
+
+/// The reason an invocation was not treated as signed by a segmenter's session key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionKeyRejection {
+    /// The registry or the delegating segmenter is invalid, or the session key didn't sign the
+    /// invocation
+    Segmenter(SegmenterRejection),
+    /// The delegation account isn't a session delegation of the segmenter registry program
+    InvalidDelegation,
+    /// The delegation is for another session key
+    SessionKeyMismatch,
+    /// The session key expired before the current slot
+    SessionExpired,
+    /// The session key may only sign for another DEX program
+    OutOfScope,
+}
+
+impl std::fmt::Display for SessionKeyRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::Segmenter(rejection) => return rejection.fmt(f),
+            Self::InvalidDelegation => "delegation is not a session delegation account",
+            Self::SessionKeyMismatch => "delegation is for another session key",
+            Self::SessionExpired => "session key has expired",
+            Self::OutOfScope => "session key may not sign for this program",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for SessionKeyRejection {}
+
+impl From<SegmenterRejection> for SessionKeyRejection {
+    fn from(rejection: SegmenterRejection) -> Self {
+        Self::Segmenter(rejection)
+    }
+}
+
+/// Checks whether the invocation of `program_id` was signed by a session key that a segmenter in
+/// the registry delegated its signing rights to, and that hasn't expired as of `current_slot`. The
+/// returned proof names the delegating segmenter. Use this in place of
+/// [`check_segmenter`](crate::check_segmenter) to also accept session keys.
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::check_session_key;
+/// use solana_program::{
+///     account_info::AccountInfo, clock::Clock, msg, pubkey::Pubkey, sysvar::Sysvar,
+/// };
+///
+/// fn handler(
+///     program_id: &Pubkey,
+///     registry: &AccountInfo<'_>,
+///     session_key: &AccountInfo<'_>,
+///     delegation: &AccountInfo<'_>,
+/// ) {
+///     let current_slot = Clock::get().unwrap().slot;
+///     let proof =
+///         match check_session_key(program_id, current_slot, registry, session_key, delegation) {
+///             Ok(proof) => proof,
+///             Err(rejection) => {
+///                 msg!("Not invoked by a session key: {}", rejection);
+///                 return;
+///             }
+///         };
+///
+///     msg!("Invoked on behalf of segmenter {}", proof.segmenter);
+/// }
+/// ```
+pub fn check_session_key(
+    program_id: &Pubkey,
+    current_slot: u64,
+    registry: &impl AccountView,
+    session_key: &impl AccountView,
+    delegation: &impl AccountView,
+) -> Result<SegmenterProof, SessionKeyRejection> {
+    if *registry.owner() != Registry::PROGRAM_ID {
+        return Err(SegmenterRejection::InvalidRegistryOwner.into());
+    }
+    if !session_key.is_signer() {
+        return Err(SegmenterRejection::SegmenterNotSigner.into());
+    }
+
+    if *delegation.owner() != Registry::PROGRAM_ID {
+        return Err(SessionKeyRejection::InvalidDelegation);
+    }
+    let delegation = delegation
+        .try_borrow_data()
+        .and_then(|data| SessionDelegation::try_deserialize(&data))
+        .ok_or(SessionKeyRejection::InvalidDelegation)?;
+    if delegation.session_key != *session_key.key() {
+        return Err(SessionKeyRejection::SessionKeyMismatch);
+    }
+    if current_slot > delegation.expiry_slot {
+        return Err(SessionKeyRejection::SessionExpired);
+    }
+    if delegation
+        .program_scope()
+        .is_some_and(|program_scope| program_scope != *program_id)
+    {
+        return Err(SessionKeyRejection::OutOfScope);
+    }
+
+    // Removing the segmenter from the registry revokes its session keys
+    check_registered(registry, &delegation.segmenter)?;
+
+    Ok(SegmenterProof {
+        registry: *registry.key(),
+        segmenter: delegation.segmenter,
+    })
+}
+
+/// Checks whether the invocation was signed by a segmenter's session key, like
+/// [`check_session_key`], and verifies the origin of the invocation, like
+/// [`verify_origin`](crate::verify_origin)
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::{verify_origin_with_session_key, Origin, VerifyOriginResult};
+/// use solana_program::{account_info::AccountInfo, clock::Clock, pubkey::Pubkey, sysvar::Sysvar};
+///
+/// fn handler(
+///     program_id: &Pubkey,
+///     registry: &AccountInfo<'_>,
+///     session_key: &AccountInfo<'_>,
+///     delegation: &AccountInfo<'_>,
+///     claimed_origin: Origin,
+/// ) {
+///     let current_slot = Clock::get().unwrap().slot;
+///     let VerifyOriginResult::InvokedBySegmenter(origin) = verify_origin_with_session_key(
+///         claimed_origin,
+///         program_id,
+///         current_slot,
+///         registry,
+///         session_key,
+///         delegation,
+///     ) else {
+///         // The invocation wasn't signed by a segmenter's session key
+///         return;
+///     };
+///
+///     // Branch on the origin here
+/// }
+/// ```
+pub fn verify_origin_with_session_key(
+    claimed_origin: Origin,
+    program_id: &Pubkey,
+    current_slot: u64,
+    registry: &impl AccountView,
+    session_key: &impl AccountView,
+    delegation: &impl AccountView,
+) -> VerifyOriginResult {
+    if check_session_key(program_id, current_slot, registry, session_key, delegation).is_err() {
+        return VerifyOriginResult::NotInvokedBySegmenter;
+    }
+
+    if registry.key() != &ORIGIN_VERIFYING_SEGMENTER_REGISTRY {
+        return VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN);
+    }
+
+    VerifyOriginResult::InvokedBySegmenter(claimed_origin)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::account_info::AccountInfo;
+
+    use crate::test_utils::{registry_data, SEGMENTER};
+
+    use super::*;
+
+    fn delegation_data(delegation: &SessionDelegation) -> Vec<u8> {
+        let mut data = SessionDelegation::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(delegation));
+        data
+    }
+
+    #[test]
+    fn test_register_session_key_instruction() {
+        let registry = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let session_key = Pubkey::new_unique();
+        let program_scope = Pubkey::new_unique();
+
+        let instruction = register_session_key_instruction(
+            &registry,
+            &SEGMENTER,
+            &payer,
+            &session_key,
+            100,
+            Some(program_scope),
+        );
+        let (session_delegation, _) = SessionDelegation::find_address(&SEGMENTER, &session_key);
+        assert_eq!(instruction.accounts[0].pubkey, session_delegation);
+        assert!(instruction.accounts[2].is_signer);
+        assert_eq!(instruction.data.len(), 8 + 32 + 8 + 1 + 32);
+        assert_eq!(instruction.data[48], 1);
+        assert_eq!(&instruction.data[49..], program_scope.as_ref());
+
+        let instruction = register_session_key_instruction(
+            &registry,
+            &SEGMENTER,
+            &payer,
+            &session_key,
+            100,
+            None,
+        );
+        assert_eq!(instruction.data.len(), 8 + 32 + 8 + 1);
+        assert_eq!(instruction.data[48], 0);
+    }
+
+    #[test]
+    fn test_revoke_session_key_instruction() {
+        let session_key = Pubkey::new_unique();
+        let instruction = revoke_session_key_instruction(&SEGMENTER, &session_key);
+        let (session_delegation, _) = SessionDelegation::find_address(&SEGMENTER, &session_key);
+        assert_eq!(instruction.accounts[0].pubkey, session_delegation);
+        assert!(instruction.accounts[0].is_writable);
+        assert_eq!(instruction.accounts[1].pubkey, SEGMENTER);
+        assert!(instruction.accounts[1].is_signer && instruction.accounts[1].is_writable);
+        assert_eq!(&instruction.data[8..], session_key.as_ref());
+    }
+
+    #[test]
+    fn test_check_session_key() {
+        let program_id = Pubkey::new_unique();
+        let session_key_address = Pubkey::new_unique();
+
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut session_key_lamports = 0;
+        let mut session_key_data = [];
+        let session_key = AccountInfo {
+            key: &session_key_address,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut session_key_lamports)),
+            data: Rc::new(RefCell::new(&mut session_key_data)),
+            owner: &system_program::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let (delegation_address, _) =
+            SessionDelegation::find_address(&SEGMENTER, &session_key_address);
+        let check = |delegation: &SessionDelegation, owner: &Pubkey, current_slot: u64| {
+            let mut data = delegation_data(delegation);
+            let mut lamports = 0;
+            let delegation = AccountInfo {
+                key: &delegation_address,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut lamports)),
+                data: Rc::new(RefCell::new(&mut data)),
+                owner,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+            check_session_key(
+                &program_id,
+                current_slot,
+                &registry,
+                &session_key,
+                &delegation,
+            )
+        };
+
+        let delegation = SessionDelegation {
+            segmenter: SEGMENTER,
+            session_key: session_key_address,
+            program_scope: Pubkey::default(),
+            expiry_slot: 100,
+        };
+        let proof = SegmenterProof {
+            registry: ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            segmenter: SEGMENTER,
+        };
+
+        // Valid for any program until the expiry slot
+        assert_eq!(check(&delegation, &Registry::PROGRAM_ID, 100), Ok(proof));
+        assert_eq!(
+            check(&delegation, &Registry::PROGRAM_ID, 101),
+            Err(SessionKeyRejection::SessionExpired)
+        );
+
+        // Scoped to this program or another one
+        let scoped = SessionDelegation {
+            program_scope: program_id,
+            ..delegation
+        };
+        assert_eq!(check(&scoped, &Registry::PROGRAM_ID, 0), Ok(proof));
+        let other_scope = SessionDelegation {
+            program_scope: Pubkey::new_unique(),
+            ..delegation
+        };
+        assert_eq!(
+            check(&other_scope, &Registry::PROGRAM_ID, 0),
+            Err(SessionKeyRejection::OutOfScope)
+        );
+
+        // Delegated to another session key
+        let other_key = SessionDelegation {
+            session_key: Pubkey::new_unique(),
+            ..delegation
+        };
+        assert_eq!(
+            check(&other_key, &Registry::PROGRAM_ID, 0),
+            Err(SessionKeyRejection::SessionKeyMismatch)
+        );
+
+        // Delegated by a segmenter that isn't registered
+        let unregistered = SessionDelegation {
+            segmenter: Pubkey::new_unique(),
+            ..delegation
+        };
+        assert_eq!(
+            check(&unregistered, &Registry::PROGRAM_ID, 0),
+            Err(SessionKeyRejection::Segmenter(
+                SegmenterRejection::SegmenterNotRegistered
+            ))
+        );
+
+        // The delegation isn't owned by the registry program
+        assert_eq!(
+            check(&delegation, &system_program::ID, 0),
+            Err(SessionKeyRejection::InvalidDelegation)
+        );
+    }
+
+    #[test]
+    fn test_verify_origin_with_session_key() {
+        let program_id = Pubkey::new_unique();
+        let session_key_address = Pubkey::new_unique();
+
+        let mut registry_data = registry_data();
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &ORIGIN_VERIFYING_SEGMENTER_REGISTRY,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut session_key_lamports = 0;
+        let mut session_key_data = [];
+        let session_key = AccountInfo {
+            key: &session_key_address,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut session_key_lamports)),
+            data: Rc::new(RefCell::new(&mut session_key_data)),
+            owner: &system_program::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut delegation_data = delegation_data(&SessionDelegation {
+            segmenter: SEGMENTER,
+            session_key: session_key_address,
+            program_scope: Pubkey::default(),
+            expiry_slot: 100,
+        });
+        let mut delegation_lamports = 0;
+        let delegation = AccountInfo {
+            key: &SessionDelegation::find_address(&SEGMENTER, &session_key_address).0,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut delegation_lamports)),
+            data: Rc::new(RefCell::new(&mut delegation_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        assert_eq!(
+            verify_origin_with_session_key(
+                origin_identity::DFLOW_WEB,
+                &program_id,
+                0,
+                &registry,
+                &session_key,
+                &delegation,
+            ),
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::DFLOW_WEB)
+        );
+
+        // Non-verifying registry
+        let non_verifying = AccountInfo {
+            key: &Pubkey::new_unique(),
+            ..registry.clone()
+        };
+        assert_eq!(
+            verify_origin_with_session_key(
+                origin_identity::DFLOW_WEB,
+                &program_id,
+                0,
+                &non_verifying,
+                &session_key,
+                &delegation,
+            ),
+            VerifyOriginResult::InvokedBySegmenter(origin_identity::UNKNOWN)
+        );
+
+        // Not signed by the session key
+        let unsigned = AccountInfo {
+            is_signer: false,
+            ..session_key.clone()
+        };
+        assert_eq!(
+            verify_origin_with_session_key(
+                origin_identity::DFLOW_WEB,
+                &program_id,
+                0,
+                &registry,
+                &unsigned,
+                &delegation,
+            ),
+            VerifyOriginResult::NotInvokedBySegmenter
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{AccountView, AuditLog, Registry};
+
+const MAX_ITEMS: usize = 64;
+
+/// An immutable copy of the segmenters of a registry at a slot, owned by the segmenter registry
+/// program, for proving historical segmenter status
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct RegistrySnapshot {
+    pub registry: Pubkey,
+    /// The number of changes in the registry's [`AuditLog`] when the snapshot was taken, which
+    /// identifies the membership the snapshot holds
+    pub version: u64,
+    /// The slot the snapshot was taken at
+    pub slot: u64,
+    pub registered_segmenters: [Pubkey; MAX_ITEMS],
+}
+
+impl RegistrySnapshot {
+    pub const DISCRIMINATOR: [u8; 8] = [220, 179, 23, 120, 67, 204, 152, 145];
+    pub const SEED: &'static [u8] = b"snapshot";
+
+    /// The address of the snapshot of `registry` at `version`
+    pub fn find_address(registry: &Pubkey, version: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, registry.as_ref(), &version.to_le_bytes()],
+            &Registry::PROGRAM_ID,
+        )
+    }
+
+    /// Deserializes snapshot account data, including the discriminator. Returns `None` if the data
+    /// isn't a snapshot. Never panics, regardless of the contents of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_pod_read_unaligned(data).ok()
+    }
+
+    /// Whether `key` was registered when the snapshot was taken. Unlike
+    /// [`Registry::is_segmenter_registered`], the default pubkey filling the empty slots is never
+    /// registered, since snapshot membership is checked without a signature.
+    pub fn is_segmenter_registered(&self, key: &Pubkey) -> bool {
+        *key != Pubkey::default() && self.registered_segmenters.binary_search(key).is_ok()
+    }
+}
+
+/// Builds the segmenter registry instruction that snapshots the current segmenters of `registry`.
+/// `version` must be the `head` of the registry's [`AuditLog`], and `payer` must sign.
+pub fn snapshot_registry_instruction(
+    registry: &Pubkey,
+    version: u64,
+    payer: &Pubkey,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [75, 205, 165, 165, 168, 184, 19, 67];
+
+    let (snapshot, _) = RegistrySnapshot::find_address(registry, version);
+    let (audit_log, _) = AuditLog::find_address(registry);
+    let mut data = DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&version.to_le_bytes());
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(snapshot, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new_readonly(audit_log, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+/// The reason a segmenter was not proven registered by a snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotRejection {
+    /// The snapshot account isn't a snapshot of the segmenter registry program
+    InvalidSnapshot,
+    /// The snapshot is of another registry
+    RegistryMismatch,
+    /// The segmenter wasn't registered when the snapshot was taken
+    SegmenterNotRegistered,
+}
+
+impl std::fmt::Display for SnapshotRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::InvalidSnapshot => "snapshot is not a registry snapshot account",
+            Self::RegistryMismatch => "snapshot is of another registry",
+            Self::SegmenterNotRegistered => "segmenter was not registered in the snapshot",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for SnapshotRejection {}
+
+/// Checks whether `segmenter` was registered in `registry` when `snapshot` was taken, returning the
+/// slot the snapshot was taken at. The segmenter doesn't need to sign, since this proves historical
+/// status rather than authorizing the invocation.
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::check_snapshot_membership;
+/// use solana_program::{account_info::AccountInfo, msg, pubkey::Pubkey};
+///
+/// fn handler(registry: &Pubkey, snapshot: &AccountInfo<'_>, segmenter: &Pubkey) {
+///     match check_snapshot_membership(registry, snapshot, segmenter) {
+///         Ok(slot) => msg!("{} was registered at slot {}", segmenter, slot),
+///         Err(rejection) => msg!("Not proven registered: {}", rejection),
+///     }
+/// }
+/// ```
+pub fn check_snapshot_membership(
+    registry: &Pubkey,
+    snapshot: &impl AccountView,
+    segmenter: &Pubkey,
+) -> Result<u64, SnapshotRejection> {
+    if *snapshot.owner() != Registry::PROGRAM_ID {
+        return Err(SnapshotRejection::InvalidSnapshot);
+    }
+    let snapshot = snapshot
+        .try_borrow_data()
+        .and_then(|data| RegistrySnapshot::try_deserialize(&data))
+        .ok_or(SnapshotRejection::InvalidSnapshot)?;
+    if snapshot.registry != *registry {
+        return Err(SnapshotRejection::RegistryMismatch);
+    }
+    if !snapshot.is_segmenter_registered(segmenter) {
+        return Err(SnapshotRejection::SegmenterNotRegistered);
+    }
+    Ok(snapshot.slot)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::account_info::AccountInfo;
+
+    use crate::test_utils::SEGMENTER;
+
+    use super::*;
+
+    #[test]
+    fn test_check_snapshot_membership() {
+        let registry = Pubkey::new_unique();
+        let mut snapshot = RegistrySnapshot {
+            registry,
+            version: 3,
+            slot: 1234,
+            registered_segmenters: [Pubkey::default(); MAX_ITEMS],
+        };
+        snapshot.registered_segmenters[63] = SEGMENTER;
+
+        let (snapshot_address, _) = RegistrySnapshot::find_address(&registry, 3);
+        let check = |owner: &Pubkey, registry: &Pubkey, segmenter: &Pubkey| {
+            let mut data = RegistrySnapshot::DISCRIMINATOR.to_vec();
+            data.extend_from_slice(bytemuck::bytes_of(&snapshot));
+            let mut lamports = 0;
+            let snapshot = AccountInfo {
+                key: &snapshot_address,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut lamports)),
+                data: Rc::new(RefCell::new(&mut data)),
+                owner,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+            check_snapshot_membership(registry, &snapshot, segmenter)
+        };
+        let program_id = Registry::PROGRAM_ID;
+
+        assert_eq!(check(&program_id, &registry, &SEGMENTER), Ok(1234));
+        assert_eq!(
+            check(&program_id, &registry, &Pubkey::new_unique()),
+            Err(SnapshotRejection::SegmenterNotRegistered)
+        );
+        assert_eq!(
+            check(&program_id, &registry, &Pubkey::default()),
+            Err(SnapshotRejection::SegmenterNotRegistered)
+        );
+        assert_eq!(
+            check(&program_id, &Pubkey::new_unique(), &SEGMENTER),
+            Err(SnapshotRejection::RegistryMismatch)
+        );
+        assert_eq!(
+            check(&system_program::ID, &registry, &SEGMENTER),
+            Err(SnapshotRejection::InvalidSnapshot)
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use solana_program::{account_info::AccountInfo, pubkey, pubkey::Pubkey, system_program};
+
+use crate::{HeartbeatTracker, Registry};
+
+/// The segmenter registered in [`registry_data`]
+pub(crate) const SEGMENTER: Pubkey = pubkey!("F2Me9XknvkPYjvoEgTXXKqARg58Ezo6ZmGhpYdS3UTmF");
+
+/// Registry account data, including the discriminator, with [`SEGMENTER`] as its only segmenter
+pub(crate) fn registry_data() -> Vec<u8> {
+    registry_data_with(&[SEGMENTER])
+}
+
+/// Registry account data, including the discriminator, with `segmenters` as its segmenters
+pub(crate) fn registry_data_with(segmenters: &[Pubkey]) -> Vec<u8> {
+    let mut registry = Registry {
+        registered_segmenters: [Pubkey::default(); 64],
+    };
+    registry.registered_segmenters[64 - segmenters.len()..].copy_from_slice(segmenters);
+    registry.registered_segmenters.sort();
+
+    let mut data = Registry::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&registry));
+    data
+}
+
+/// The account at the address of the heartbeat tracker of `registry`, which wasn't created. Leaked
+/// so it can be passed alongside accounts of any lifetime.
+pub(crate) fn heartbeat_tracker(registry: &Pubkey) -> AccountInfo<'static> {
+    AccountInfo::new(
+        Box::leak(Box::new(HeartbeatTracker::find_address(registry).0)),
+        false,
+        false,
+        Box::leak(Box::new(0)),
+        Box::leak(Box::new([])),
+        &system_program::ID,
+        false,
+        0,
+    )
+}
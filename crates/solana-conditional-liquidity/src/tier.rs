@@ -0,0 +1,342 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{
+    directory::config_address, registry::check_registered, AccountView, Registry,
+    SegmenterRejection,
+};
+
+const MAX_ITEMS: usize = 64;
+
+/// How much a DEX may trust a segmenter, ordered from the most to the least trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SegmenterTier {
+    Gold,
+    Silver,
+    Probation,
+}
+
+/// The tier of a segmenter
+#[derive(Pod, Zeroable, Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct TierEntry {
+    pub segmenter: Pubkey,
+    /// The [`SegmenterTier`] as a `u8`. Use [`TierEntry::tier`] for the tier.
+    pub tier: u8,
+}
+
+impl TierEntry {
+    /// The tier of the entry, `None` if it is a tier this version doesn't know about
+    pub fn tier(&self) -> Option<SegmenterTier> {
+        match self.tier {
+            0 => Some(SegmenterTier::Gold),
+            1 => Some(SegmenterTier::Silver),
+            2 => Some(SegmenterTier::Probation),
+            _ => None,
+        }
+    }
+}
+
+/// The tiers the admin assigned to the segmenters of a registry, owned by the segmenter registry
+/// program
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+pub struct SegmenterTiers {
+    pub registry: Pubkey,
+    pub entries: [TierEntry; MAX_ITEMS],
+}
+
+impl SegmenterTiers {
+    pub const DISCRIMINATOR: [u8; 8] = [241, 128, 27, 136, 159, 178, 135, 117];
+    pub const SEED: &'static [u8] = b"tiers";
+
+    /// The address of the segmenter tiers of `registry`
+    pub fn find_address(registry: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, registry.as_ref()], &Registry::PROGRAM_ID)
+    }
+
+    /// Deserializes segmenter tiers account data, including the discriminator, without copying it.
+    /// Returns `None` if the data isn't segmenter tiers. Never panics, regardless of the contents
+    /// of `bytes`.
+    pub fn try_deserialize(bytes: &[u8]) -> Option<&Self> {
+        if bytes.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return None;
+        }
+        let data = bytes.get(8..8 + std::mem::size_of::<Self>())?;
+        bytemuck::try_from_bytes(data).ok()
+    }
+
+    /// The tier of `segmenter`, or `None` if it has none
+    pub fn tier(&self, segmenter: &Pubkey) -> Option<SegmenterTier> {
+        self.entries
+            .iter()
+            .find(|entry| entry.segmenter == *segmenter && *segmenter != Pubkey::default())
+            .and_then(TierEntry::tier)
+    }
+}
+
+/// Builds the segmenter registry instruction that creates the segmenter tiers of `registry`.
+/// `payer` must sign.
+pub fn create_segmenter_tiers_instruction(registry: &Pubkey, payer: &Pubkey) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [103, 131, 161, 16, 236, 196, 17, 134];
+
+    let (segmenter_tiers, _) = SegmenterTiers::find_address(registry);
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(segmenter_tiers, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: DISCRIMINATOR.to_vec(),
+    }
+}
+
+/// Builds the segmenter registry instruction that assigns `tier` to `segmenter`, which must be
+/// registered in `registry`, or clears its tier if `tier` is `None`. `admin` must be the
+/// deployment's admin and sign.
+pub fn set_segmenter_tier_instruction(
+    registry: &Pubkey,
+    admin: &Pubkey,
+    segmenter: &Pubkey,
+    tier: Option<SegmenterTier>,
+) -> Instruction {
+    const DISCRIMINATOR: [u8; 8] = [86, 103, 15, 53, 122, 253, 47, 85];
+
+    let (segmenter_tiers, _) = SegmenterTiers::find_address(registry);
+    let mut data = DISCRIMINATOR.to_vec();
+    data.extend_from_slice(segmenter.as_ref());
+    match tier {
+        Some(tier) => data.extend_from_slice(&[1, tier as u8]),
+        None => data.push(0),
+    }
+    Instruction {
+        program_id: Registry::PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(segmenter_tiers, false),
+            AccountMeta::new_readonly(*registry, false),
+            AccountMeta::new_readonly(config_address(), false),
+            AccountMeta::new_readonly(*admin, true),
+        ],
+        data,
+    }
+}
+
+/// Proof that an invocation was signed by a segmenter, with the segmenter's tier, returned by
+/// [`check_segmenter_with_tier`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TieredSegmenterProof {
+    /// The registry the segmenter was found in
+    pub registry: Pubkey,
+    /// The public key of the segmenter that signed the invocation
+    pub segmenter: Pubkey,
+    /// The tier of the segmenter, or `None` if the admin didn't assign it one
+    pub tier: Option<SegmenterTier>,
+}
+
+/// The reason an invocation was not treated as signed by a tiered segmenter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TierRejection {
+    /// The registry is invalid, or the segmenter didn't sign or isn't registered
+    Segmenter(SegmenterRejection),
+    /// The segmenter tiers account isn't segmenter tiers of the segmenter registry program
+    InvalidTiers,
+    /// The segmenter tiers are of another registry
+    TiersMismatch,
+}
+
+impl std::fmt::Display for TierRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::Segmenter(rejection) => return rejection.fmt(f),
+            Self::InvalidTiers => "segmenter tiers is not a segmenter tiers account",
+            Self::TiersMismatch => "segmenter tiers are of another registry",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for TierRejection {}
+
+impl From<SegmenterRejection> for TierRejection {
+    fn from(rejection: SegmenterRejection) -> Self {
+        Self::Segmenter(rejection)
+    }
+}
+
+/// Checks whether the invocation was signed by a segmenter, like
+/// [`check_segmenter`](crate::check_segmenter), returning the segmenter's tier from the registry's
+/// segmenter tiers on success. Use this to grant top-tier segmenters more than probationary ones.
+///
+/// Examples
+///
+/// ```
+/// use solana_conditional_liquidity::{check_segmenter_with_tier, SegmenterTier};
+/// use solana_program::{account_info::AccountInfo, msg};
+///
+/// fn handler(
+///     registry: &AccountInfo<'_>,
+///     segmenter_tiers: &AccountInfo<'_>,
+///     segmenter: &AccountInfo<'_>,
+/// ) {
+///     let proof = match check_segmenter_with_tier(registry, segmenter_tiers, segmenter) {
+///         Ok(proof) => proof,
+///         Err(rejection) => {
+///             msg!("Not invoked by a segmenter: {}", rejection);
+///             return;
+///         }
+///     };
+///
+///     match proof.tier {
+///         Some(SegmenterTier::Gold) => {
+///             // Grant full conditional liquidity
+///         }
+///         Some(SegmenterTier::Silver | SegmenterTier::Probation) | None => {
+///             // Grant partial conditional liquidity
+///         }
+///     }
+/// }
+/// ```
+pub fn check_segmenter_with_tier(
+    registry: &impl AccountView,
+    segmenter_tiers: &impl AccountView,
+    segmenter: &impl AccountView,
+) -> Result<TieredSegmenterProof, TierRejection> {
+    if *registry.owner() != Registry::PROGRAM_ID {
+        return Err(SegmenterRejection::InvalidRegistryOwner.into());
+    }
+    if !segmenter.is_signer() {
+        return Err(SegmenterRejection::SegmenterNotSigner.into());
+    }
+
+    check_registered(registry, segmenter.key())?;
+
+    if *segmenter_tiers.owner() != Registry::PROGRAM_ID {
+        return Err(TierRejection::InvalidTiers);
+    }
+    let segmenter_tiers_data = segmenter_tiers
+        .try_borrow_data()
+        .ok_or(TierRejection::InvalidTiers)?;
+    let segmenter_tiers = SegmenterTiers::try_deserialize(&segmenter_tiers_data)
+        .ok_or(TierRejection::InvalidTiers)?;
+    if segmenter_tiers.registry != *registry.key() {
+        return Err(TierRejection::TiersMismatch);
+    }
+
+    Ok(TieredSegmenterProof {
+        registry: *registry.key(),
+        segmenter: *segmenter.key(),
+        tier: segmenter_tiers.tier(segmenter.key()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use solana_program::account_info::AccountInfo;
+
+    use crate::test_utils::SEGMENTER;
+
+    use super::*;
+
+    #[test]
+    fn test_set_segmenter_tier_instruction() {
+        let registry = Pubkey::new_unique();
+        let admin = Pubkey::new_unique();
+
+        let instruction = set_segmenter_tier_instruction(
+            &registry,
+            &admin,
+            &SEGMENTER,
+            Some(SegmenterTier::Probation),
+        );
+        assert_eq!(&instruction.data[8..40], SEGMENTER.as_ref());
+        assert_eq!(&instruction.data[40..], [1, 2]);
+
+        let instruction = set_segmenter_tier_instruction(&registry, &admin, &SEGMENTER, None);
+        assert_eq!(&instruction.data[40..], [0]);
+    }
+
+    #[test]
+    fn test_check_segmenter_with_tier() {
+        let registry_address = Pubkey::new_unique();
+        let mut registry_state = Registry {
+            registered_segmenters: [Pubkey::default(); 64],
+        };
+        registry_state.registered_segmenters[63] = SEGMENTER;
+        let mut registry_data = Registry::DISCRIMINATOR.to_vec();
+        registry_data.extend_from_slice(bytemuck::bytes_of(&registry_state));
+        let mut registry_lamports = 0;
+        let registry = AccountInfo {
+            key: &registry_address,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut registry_lamports)),
+            data: Rc::new(RefCell::new(&mut registry_data)),
+            owner: &Registry::PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+
+        let mut untiered = SegmenterTiers::zeroed();
+        untiered.registry = registry_address;
+        let mut tiered = untiered;
+        tiered.entries[7] = TierEntry {
+            segmenter: SEGMENTER,
+            tier: SegmenterTier::Silver as u8,
+        };
+
+        let (tiers_address, _) = SegmenterTiers::find_address(&registry_address);
+        let check = |tiers: &SegmenterTiers, owner: &Pubkey| {
+            let mut tiers_data = SegmenterTiers::DISCRIMINATOR.to_vec();
+            tiers_data.extend_from_slice(bytemuck::bytes_of(tiers));
+            let mut tiers_lamports = 0;
+            let segmenter_tiers = AccountInfo {
+                key: &tiers_address,
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut tiers_lamports)),
+                data: Rc::new(RefCell::new(&mut tiers_data)),
+                owner,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+
+            let mut segmenter_lamports = 0;
+            let mut segmenter_data = [];
+            let segmenter = AccountInfo {
+                key: &SEGMENTER,
+                is_signer: true,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut segmenter_lamports)),
+                data: Rc::new(RefCell::new(&mut segmenter_data)),
+                owner: &system_program::ID,
+                executable: false,
+                rent_epoch: u64::MAX,
+            };
+            check_segmenter_with_tier(&registry, &segmenter_tiers, &segmenter)
+                .map(|proof| proof.tier)
+        };
+        let program_id = Registry::PROGRAM_ID;
+
+        assert_eq!(check(&tiered, &program_id), Ok(Some(SegmenterTier::Silver)));
+        assert_eq!(check(&untiered, &program_id), Ok(None));
+        assert_eq!(
+            check(&tiered, &system_program::ID),
+            Err(TierRejection::InvalidTiers)
+        );
+
+        let mut other_registry = tiered;
+        other_registry.registry = Pubkey::new_unique();
+        assert_eq!(
+            check(&other_registry, &program_id),
+            Err(TierRejection::TiersMismatch)
+        );
+    }
+}
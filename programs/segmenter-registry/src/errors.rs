@@ -8,4 +8,42 @@ pub enum SegmenterRegistryError {
     DuplicateEntry = 9001,
     #[msg("The admin specified is not authorized to invoke this instruction")]
     InvalidAdminSpecified = 9002,
+    #[msg("The nonce has already been consumed")]
+    NonceAlreadyConsumed = 9003,
+    #[msg("The nonce is older than the nonce tracker's window")]
+    NonceTooOld = 9004,
+    #[msg("The authority specified is not the nonce tracker's authority")]
+    InvalidNonceAuthority = 9005,
+    #[msg("The segmenter is not registered in the registry")]
+    SegmenterNotRegistered = 9006,
+    #[msg("The session key must expire in the future and within the maximum session duration")]
+    InvalidSessionExpiry = 9007,
+    #[msg("The seed is longer than the maximum seed length")]
+    SeedTooLong = 9008,
+    #[msg("The overlay list is at capacity")]
+    OverlaySaturated = 9009,
+    #[msg("The authority specified is not the overlay's authority")]
+    InvalidOverlayAuthority = 9010,
+    #[msg("The directory page is full")]
+    DirectoryPageFull = 9011,
+    #[msg("A directory page can only be created once the previous page is full")]
+    PreviousDirectoryPageNotFull = 9012,
+    #[msg("The registry name is longer than the maximum name length")]
+    RegistryNameTooLong = 9013,
+    #[msg("The creation policy does not allow the signer to create a registry")]
+    RegistryCreationNotAllowed = 9014,
+    #[msg("The treasury specified is not the creation policy's treasury")]
+    InvalidTreasury = 9015,
+    #[msg("The authority specified is not the registry's authority")]
+    InvalidRegistryAuthority = 9016,
+    #[msg("The description URI is longer than the maximum URI length")]
+    DescriptionUriTooLong = 9017,
+    #[msg("There are more tags than the maximum, or a tag is longer than the maximum tag length")]
+    InvalidTags = 9018,
+    #[msg("The snapshot version is not the number of changes in the registry's audit log")]
+    InvalidSnapshotVersion = 9019,
+    #[msg("The heartbeat tracker is at capacity")]
+    HeartbeatTrackerSaturated = 9020,
+    #[msg("The segmenter tiers are at capacity")]
+    SegmenterTiersSaturated = 9021,
 }
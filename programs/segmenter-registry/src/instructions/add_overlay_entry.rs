@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct AddOverlayEntry<'info> {
+    #[account(
+        mut,
+        has_one = authority @ SegmenterRegistryError::InvalidOverlayAuthority,
+    )]
+    pub overlay: AccountLoader<'info, Overlay>,
+
+    /// The authority of the overlay
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AddOverlayEntry>, list: OverlayList, key: Pubkey) -> Result<()> {
+    ctx.accounts.overlay.load_mut()?.add(list, key)
+}
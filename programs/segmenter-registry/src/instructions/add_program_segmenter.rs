@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct AddProgramSegmenter<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = ProgramSegmenter::LEN,
+        seeds = [
+            ProgramSegmenter::SEED,
+            registry.key().as_ref(),
+            program_id.as_ref(),
+        ],
+        bump,
+    )]
+    pub program_segmenter: AccountLoader<'info, ProgramSegmenter>,
+
+    /// The registry the program's segmenter PDA is added to
+    #[account(mut)]
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The audit log of the registry, which the change is appended to, created if it doesn't exist
+    /// yet
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AuditLog::LEN,
+        seeds = [
+            AuditLog::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified)]
+    pub config: Account<'info, Config>,
+
+    /// The admin for the deployment, who pays for the program segmenter and for creating the audit
+    /// log if it doesn't exist yet
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddProgramSegmenter>, program_id: Pubkey, seed: Vec<u8>) -> Result<()> {
+    require!(
+        seed.len() <= ProgramSegmenter::MAX_SEED_LEN,
+        SegmenterRegistryError::SeedTooLong
+    );
+
+    // Only the PDA of exactly this seed is registered, so the program can't sign as the segmenter
+    // with any other seeds
+    let (segmenter, bump) = Pubkey::find_program_address(&[&seed], &program_id);
+    ctx.accounts.registry.load_mut()?.add(segmenter)?;
+
+    let mut program_segmenter = ctx.accounts.program_segmenter.load_init()?;
+    program_segmenter.registry = ctx.accounts.registry.key();
+    program_segmenter.program_id = program_id;
+    program_segmenter.segmenter = segmenter;
+    program_segmenter.seed_len = seed.len() as u8;
+    program_segmenter.seed[..seed.len()].copy_from_slice(&seed);
+    program_segmenter.bump = bump;
+
+    AuditLog::load_or_init(&ctx.accounts.audit_log, ctx.accounts.registry.key())?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::AddSegmenter,
+        segmenter,
+    )
+}
@@ -8,14 +8,35 @@ pub struct AddSegmenter<'info> {
     #[account(mut)]
     pub registry: AccountLoader<'info, Registry>,
 
+    /// The audit log of the registry, which the change is appended to, created if it doesn't exist
+    /// yet
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AuditLog::LEN,
+        seeds = [
+            AuditLog::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
     #[account(has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified)]
     pub config: Account<'info, Config>,
 
-    /// The admin for the deployment
+    /// The admin for the deployment, who pays for creating the audit log if it doesn't exist yet
+    #[account(mut)]
     pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<AddSegmenter>, new_segmenter: Pubkey) -> Result<()> {
     ctx.accounts.registry.load_mut()?.add(new_segmenter)?;
-    Ok(())
+    AuditLog::load_or_init(&ctx.accounts.audit_log, ctx.accounts.registry.key())?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::AddSegmenter,
+        new_segmenter,
+    )
 }
@@ -5,15 +5,43 @@ use crate::state::*;
 
 #[derive(Accounts)]
 pub struct ChangeAdmin<'info> {
-    #[account(mut, has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified)]
+    #[account(
+        mut,
+        has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified,
+        realloc = Config::LEN,
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
     pub config: Account<'info, Config>,
 
-    /// The current admin
+    /// The audit log of the deployment, which the change is appended to, created if it doesn't
+    /// exist yet
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AuditLog::LEN,
+        seeds = [
+            AuditLog::SEED,
+            config.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The current admin, who pays for growing configs initialized before creation policies and
+    /// for creating the audit log
+    #[account(mut)]
     pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<ChangeAdmin>, new_admin: Pubkey) -> Result<()> {
     ctx.accounts.config.admin = new_admin;
 
-    Ok(())
+    AuditLog::load_or_init(&ctx.accounts.audit_log, ctx.accounts.config.key())?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::ChangeAdmin,
+        new_admin,
+    )
 }
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ChangeFrontendKey<'info> {
+    #[account(mut)]
+    pub origin_entry: AccountLoader<'info, OriginEntry>,
+
+    #[account(has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified)]
+    pub config: Account<'info, Config>,
+
+    /// The admin for the deployment
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ChangeFrontendKey>, new_frontend_key: Pubkey) -> Result<()> {
+    ctx.accounts.origin_entry.load_mut()?.frontend_key = new_frontend_key;
+    Ok(())
+}
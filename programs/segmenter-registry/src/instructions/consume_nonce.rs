@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ConsumeNonce<'info> {
+    #[account(
+        mut,
+        has_one = authority @ SegmenterRegistryError::InvalidNonceAuthority,
+    )]
+    pub nonce_tracker: AccountLoader<'info, NonceTracker>,
+
+    /// The authority of the nonce tracker
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ConsumeNonce>, nonce: u64) -> Result<()> {
+    ctx.accounts.nonce_tracker.load_mut()?.consume(nonce)
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateAuditLog<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = AuditLog::LEN,
+        seeds = [
+            AuditLog::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The payer for the audit log account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateAuditLog>) -> Result<()> {
+    ctx.accounts.audit_log.load_init()?.registry = ctx.accounts.registry.key();
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(page_index: u32)]
+pub struct CreateDirectoryPage<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = DirectoryPage::LEN,
+        seeds = [
+            DirectoryPage::SEED,
+            &page_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub directory_page: AccountLoader<'info, DirectoryPage>,
+
+    /// The page before the new one, required unless the new page is the first
+    #[account(
+        seeds = [
+            DirectoryPage::SEED,
+            &page_index.wrapping_sub(1).to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub previous_page: Option<AccountLoader<'info, DirectoryPage>>,
+
+    /// The payer for the directory page account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateDirectoryPage>, page_index: u32) -> Result<()> {
+    check_previous_page(page_index, ctx.accounts.previous_page.as_ref())?;
+    ctx.accounts.directory_page.load_init()?.page_index = page_index;
+    Ok(())
+}
+
+/// Checks that the page before a new page at `page_index` is full, so pages are created in order
+pub(crate) fn check_previous_page(
+    page_index: u32,
+    previous_page: Option<&AccountLoader<DirectoryPage>>,
+) -> Result<()> {
+    if page_index > 0 {
+        let Some(previous_page) = previous_page else {
+            return Err(error!(SegmenterRegistryError::PreviousDirectoryPageNotFull));
+        };
+        require!(
+            previous_page.load()?.is_full(),
+            SegmenterRegistryError::PreviousDirectoryPageNotFull
+        );
+    }
+    Ok(())
+}
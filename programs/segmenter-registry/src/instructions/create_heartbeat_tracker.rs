@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateHeartbeatTracker<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = HeartbeatTracker::LEN,
+        seeds = [
+            HeartbeatTracker::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub heartbeat_tracker: AccountLoader<'info, HeartbeatTracker>,
+
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The payer for the heartbeat tracker account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateHeartbeatTracker>) -> Result<()> {
+    ctx.accounts.heartbeat_tracker.load_init()?.registry = ctx.accounts.registry.key();
+    Ok(())
+}
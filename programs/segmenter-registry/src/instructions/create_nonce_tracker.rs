@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(segmenter: Pubkey)]
+pub struct CreateNonceTracker<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = NonceTracker::LEN,
+        seeds = [
+            NonceTracker::SEED,
+            authority.key().as_ref(),
+            segmenter.as_ref(),
+        ],
+        bump,
+    )]
+    pub nonce_tracker: AccountLoader<'info, NonceTracker>,
+
+    /// The account that may consume nonces
+    pub authority: Signer<'info>,
+
+    /// The payer for the nonce tracker account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateNonceTracker>, segmenter: Pubkey) -> Result<()> {
+    let mut nonce_tracker = ctx.accounts.nonce_tracker.load_init()?;
+    nonce_tracker.authority = ctx.accounts.authority.key();
+    nonce_tracker.segmenter = segmenter;
+    Ok(())
+}
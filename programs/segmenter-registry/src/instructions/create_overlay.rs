@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateOverlay<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Overlay::LEN,
+        seeds = [
+            Overlay::SEED,
+            authority.key().as_ref(),
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub overlay: AccountLoader<'info, Overlay>,
+
+    /// The registry the overlay applies to
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The DEX that manages the overlay
+    pub authority: Signer<'info>,
+
+    /// The payer for the overlay account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateOverlay>) -> Result<()> {
+    let mut overlay = ctx.accounts.overlay.load_init()?;
+    overlay.authority = ctx.accounts.authority.key();
+    overlay.registry = ctx.accounts.registry.key();
+    Ok(())
+}
@@ -1,20 +1,136 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 
+use crate::errors::SegmenterRegistryError;
+use crate::instructions::create_directory_page::check_previous_page;
 use crate::state::*;
 
 #[derive(Accounts)]
+#[instruction(name: String, page_index: u32)]
 pub struct CreateRegistry<'info> {
     #[account(init, payer = payer, space = Registry::LEN)]
     pub registry: AccountLoader<'info, Registry>,
 
-    /// The payer for the registry account creation
+    /// The last page of the directory, which the registry is appended to, created if there are no
+    /// pages yet or the last page is full
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = DirectoryPage::LEN,
+        seeds = [
+            DirectoryPage::SEED,
+            &page_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub directory_page: AccountLoader<'info, DirectoryPage>,
+
+    /// The page before the last one, required if the last page is created
+    #[account(
+        seeds = [
+            DirectoryPage::SEED,
+            &page_index.wrapping_sub(1).to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub previous_page: Option<AccountLoader<'info, DirectoryPage>>,
+
+    /// The audit log of the registry, which the directory authority is appended to
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = AuditLog::LEN,
+        seeds = [
+            AuditLog::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        seeds = [
+            Config::SEED,
+        ],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The payer for the registry account creation, listed as the registry's authority in the
+    /// directory
     #[account(mut)]
     pub payer: Signer<'info>,
 
+    /// The treasury the creation fee is paid to, required if the creation policy requires a fee
+    /// CHECK: checked against the creation policy
+    #[account(mut)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<CreateRegistry>) -> Result<()> {
+pub fn handler(ctx: Context<CreateRegistry>, name: String, page_index: u32) -> Result<()> {
+    require!(
+        name.len() <= DirectoryPage::MAX_NAME_LEN,
+        SegmenterRegistryError::RegistryNameTooLong
+    );
+
+    match ctx.accounts.config.creation_policy {
+        CreationPolicy::Open => {}
+        CreationPolicy::AdminOnly => require_keys_eq!(
+            ctx.accounts.payer.key(),
+            ctx.accounts.config.admin,
+            SegmenterRegistryError::RegistryCreationNotAllowed
+        ),
+        CreationPolicy::FeeRequired {
+            fee_lamports,
+            treasury,
+        } => {
+            let Some(treasury_account) = &ctx.accounts.treasury else {
+                return Err(error!(SegmenterRegistryError::InvalidTreasury));
+            };
+            require_keys_eq!(
+                treasury_account.key(),
+                treasury,
+                SegmenterRegistryError::InvalidTreasury
+            );
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: treasury_account.to_account_info(),
+                    },
+                ),
+                fee_lamports,
+            )?;
+        }
+    }
+
     *ctx.accounts.registry.load_init()? = Registry::new();
-    Ok(())
+
+    let mut entry = DirectoryEntry {
+        registry: ctx.accounts.registry.key(),
+        authority: ctx.accounts.payer.key(),
+        name: [0; 32],
+        created_at_slot: Clock::get()?.slot,
+    };
+    entry.name[..name.len()].copy_from_slice(name.as_bytes());
+
+    let mut directory_page = match ctx.accounts.directory_page.load_init() {
+        // The page was just created by `init_if_needed`
+        Ok(mut directory_page) => {
+            check_previous_page(page_index, ctx.accounts.previous_page.as_ref())?;
+            directory_page.page_index = page_index;
+            directory_page
+        }
+        Err(_) => ctx.accounts.directory_page.load_mut()?,
+    };
+    directory_page.push(entry)?;
+
+    AuditLog::load_or_init(&ctx.accounts.audit_log, ctx.accounts.registry.key())?.append(
+        ctx.accounts.payer.key(),
+        AuditAction::SetDirectoryAuthority,
+        ctx.accounts.payer.key(),
+    )
 }
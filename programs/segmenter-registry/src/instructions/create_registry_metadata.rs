@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateRegistryMetadata<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = RegistryMetadata::LEN,
+        seeds = [
+            RegistryMetadata::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub registry_metadata: AccountLoader<'info, RegistryMetadata>,
+
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The audit log of the registry, which the metadata authority is appended to, created if it
+    /// doesn't exist yet
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = AuditLog::LEN,
+        seeds = [
+            AuditLog::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The directory page listing the registry, required unless the authority is the admin
+    pub directory_page: Option<AccountLoader<'info, DirectoryPage>>,
+
+    #[account(
+        seeds = [
+            Config::SEED,
+        ],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The account that created the registry according to the directory, or the admin for
+    /// registries created before the directory
+    pub authority: Signer<'info>,
+
+    /// The payer for the registry metadata account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateRegistryMetadata>) -> Result<()> {
+    let registry = ctx.accounts.registry.key();
+    let authority = ctx.accounts.authority.key();
+
+    let is_directory_authority = match &ctx.accounts.directory_page {
+        Some(directory_page) => directory_page
+            .load()?
+            .entries()
+            .iter()
+            .any(|entry| entry.registry == registry && entry.authority == authority),
+        None => false,
+    };
+    require!(
+        is_directory_authority || authority == ctx.accounts.config.admin,
+        SegmenterRegistryError::InvalidRegistryAuthority
+    );
+
+    let mut registry_metadata = ctx.accounts.registry_metadata.load_init()?;
+    registry_metadata.registry = registry;
+    registry_metadata.authority = authority;
+
+    AuditLog::load_or_init(&ctx.accounts.audit_log, registry)?.append(
+        authority,
+        AuditAction::SetMetadataAuthority,
+        authority,
+    )
+}
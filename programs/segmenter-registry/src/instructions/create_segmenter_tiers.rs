@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateSegmenterTiers<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = SegmenterTiers::LEN,
+        seeds = [
+            SegmenterTiers::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub segmenter_tiers: AccountLoader<'info, SegmenterTiers>,
+
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The payer for the segmenter tiers account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateSegmenterTiers>) -> Result<()> {
+    ctx.accounts.segmenter_tiers.load_init()?.registry = ctx.accounts.registry.key();
+    Ok(())
+}
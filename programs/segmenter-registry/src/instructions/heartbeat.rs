@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    #[account(
+        mut,
+        seeds = [
+            HeartbeatTracker::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub heartbeat_tracker: AccountLoader<'info, HeartbeatTracker>,
+
+    /// The registry the segmenter is registered in
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The segmenter sending the heartbeat
+    pub segmenter: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<Heartbeat>) -> Result<()> {
+    let segmenter = ctx.accounts.segmenter.key();
+    let registry = ctx.accounts.registry.load()?;
+    require!(
+        registry.is_segmenter_registered(&segmenter),
+        SegmenterRegistryError::SegmenterNotRegistered
+    );
+
+    ctx.accounts
+        .heartbeat_tracker
+        .load_mut()?
+        .record(&registry, segmenter, Clock::get()?.slot)
+}
@@ -24,5 +24,6 @@ pub struct Initialize<'info> {
 
 pub fn handler(ctx: Context<Initialize>) -> Result<()> {
     ctx.accounts.config.admin = ctx.accounts.admin.key();
+    ctx.accounts.config.creation_policy = CreationPolicy::Open;
     Ok(())
 }
@@ -1,11 +1,55 @@
+pub mod add_overlay_entry;
+pub mod add_program_segmenter;
 pub mod add_segmenter;
 pub mod change_admin;
+pub mod change_frontend_key;
+pub mod consume_nonce;
+pub mod create_audit_log;
+pub mod create_directory_page;
+pub mod create_heartbeat_tracker;
+pub mod create_nonce_tracker;
+pub mod create_overlay;
 pub mod create_registry;
+pub mod create_registry_metadata;
+pub mod create_segmenter_tiers;
+pub mod heartbeat;
 pub mod initialize;
+pub mod register_origin;
+pub mod register_session_key;
+pub mod remove_overlay_entry;
+pub mod remove_program_segmenter;
 pub mod remove_segmenter;
+pub mod revoke_session_key;
+pub mod set_creation_policy;
+pub mod set_max_heartbeat_age;
+pub mod set_registry_metadata;
+pub mod set_segmenter_tier;
+pub mod snapshot_registry;
 
-pub use add_segmenter::*;
-pub use change_admin::*;
-pub use create_registry::*;
-pub use initialize::*;
-pub use remove_segmenter::*;
+pub(crate) use add_overlay_entry::*;
+pub(crate) use add_program_segmenter::*;
+pub(crate) use add_segmenter::*;
+pub(crate) use change_admin::*;
+pub(crate) use change_frontend_key::*;
+pub(crate) use consume_nonce::*;
+pub(crate) use create_audit_log::*;
+pub(crate) use create_directory_page::*;
+pub(crate) use create_heartbeat_tracker::*;
+pub(crate) use create_nonce_tracker::*;
+pub(crate) use create_overlay::*;
+pub(crate) use create_registry::*;
+pub(crate) use create_registry_metadata::*;
+pub(crate) use create_segmenter_tiers::*;
+pub(crate) use heartbeat::*;
+pub(crate) use initialize::*;
+pub(crate) use register_origin::*;
+pub(crate) use register_session_key::*;
+pub(crate) use remove_overlay_entry::*;
+pub(crate) use remove_program_segmenter::*;
+pub(crate) use remove_segmenter::*;
+pub(crate) use revoke_session_key::*;
+pub(crate) use set_creation_policy::*;
+pub(crate) use set_max_heartbeat_age::*;
+pub(crate) use set_registry_metadata::*;
+pub(crate) use set_segmenter_tier::*;
+pub(crate) use snapshot_registry::*;
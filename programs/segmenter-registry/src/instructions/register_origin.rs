@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(origin: u16)]
+pub struct RegisterOrigin<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = OriginEntry::LEN,
+        seeds = [
+            OriginEntry::SEED,
+            origin.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub origin_entry: AccountLoader<'info, OriginEntry>,
+
+    #[account(has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified)]
+    pub config: Account<'info, Config>,
+
+    /// The admin for the deployment
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RegisterOrigin>, origin: u16, frontend_key: Pubkey) -> Result<()> {
+    let mut origin_entry = ctx.accounts.origin_entry.load_init()?;
+    origin_entry.origin = origin;
+    origin_entry.frontend_key = frontend_key;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct RegisterSessionKey<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = SessionDelegation::LEN,
+        seeds = [
+            SessionDelegation::SEED,
+            segmenter.key().as_ref(),
+            session_key.as_ref(),
+        ],
+        bump,
+    )]
+    pub session_delegation: AccountLoader<'info, SessionDelegation>,
+
+    /// A registry the segmenter is registered in
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The segmenter delegating its signing rights
+    pub segmenter: Signer<'info>,
+
+    /// The payer for the session delegation account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<RegisterSessionKey>,
+    session_key: Pubkey,
+    expiry_slot: u64,
+    program_scope: Option<Pubkey>,
+) -> Result<()> {
+    let segmenter = ctx.accounts.segmenter.key();
+    require!(
+        ctx.accounts
+            .registry
+            .load()?
+            .is_segmenter_registered(&segmenter),
+        SegmenterRegistryError::SegmenterNotRegistered
+    );
+
+    let current_slot = Clock::get()?.slot;
+    require!(
+        expiry_slot > current_slot
+            && expiry_slot - current_slot <= SessionDelegation::MAX_DURATION_SLOTS,
+        SegmenterRegistryError::InvalidSessionExpiry
+    );
+
+    let mut session_delegation = ctx.accounts.session_delegation.load_init()?;
+    session_delegation.segmenter = segmenter;
+    session_delegation.session_key = session_key;
+    session_delegation.program_scope = program_scope.unwrap_or_default();
+    session_delegation.expiry_slot = expiry_slot;
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct RemoveOverlayEntry<'info> {
+    #[account(
+        mut,
+        has_one = authority @ SegmenterRegistryError::InvalidOverlayAuthority,
+    )]
+    pub overlay: AccountLoader<'info, Overlay>,
+
+    /// The authority of the overlay
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RemoveOverlayEntry>, list: OverlayList, key: Pubkey) -> Result<()> {
+    if ctx.accounts.overlay.load_mut()?.remove(list, key).is_none() {
+        msg!("Overlay does not contain key: {}", key);
+    };
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct RemoveProgramSegmenter<'info> {
+    #[account(mut, close = admin, has_one = registry)]
+    pub program_segmenter: AccountLoader<'info, ProgramSegmenter>,
+
+    /// The registry the program's segmenter PDA is removed from
+    #[account(mut)]
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The audit log of the registry, which the change is appended to, created if it doesn't exist
+    /// yet
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AuditLog::LEN,
+        seeds = [
+            AuditLog::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The revocation of the program's segmenter PDA, which the removal and its reason are
+    /// recorded in, created if the PDA was never removed before
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = Revocation::LEN,
+        seeds = [
+            Revocation::SEED,
+            registry.key().as_ref(),
+            program_segmenter.load()?.segmenter.as_ref(),
+        ],
+        bump,
+    )]
+    pub revocation: AccountLoader<'info, Revocation>,
+
+    /// The segmenter tiers of the registry, which the PDA's tier is cleared from if they were
+    /// created
+    /// CHECK: only used if owned by this program, in which case it is segmenter tiers
+    #[account(
+        mut,
+        seeds = [
+            SegmenterTiers::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub segmenter_tiers: UncheckedAccount<'info>,
+
+    #[account(has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified)]
+    pub config: Account<'info, Config>,
+
+    /// The admin for the deployment, who pays for creating the audit log if it doesn't exist yet
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RemoveProgramSegmenter>, reason: RevocationReason) -> Result<()> {
+    let segmenter = ctx.accounts.program_segmenter.load()?.segmenter;
+    let mut revocation = Revocation::load_or_init(
+        &ctx.accounts.revocation,
+        ctx.accounts.registry.key(),
+        segmenter,
+    )?;
+    let removed = ctx.accounts.registry.load_mut()?.remove(segmenter);
+    if removed.is_none() {
+        msg!("Registry does not contain key: {}", segmenter);
+        return Ok(());
+    }
+    revocation.record(reason)?;
+    SegmenterTiers::clear_if_created(&ctx.accounts.segmenter_tiers, &segmenter)?;
+    AuditLog::load_or_init(&ctx.accounts.audit_log, ctx.accounts.registry.key())?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::RemoveSegmenter,
+        segmenter,
+    )
+}
@@ -4,20 +4,75 @@ use crate::errors::SegmenterRegistryError;
 use crate::state::*;
 
 #[derive(Accounts)]
+#[instruction(key: Pubkey)]
 pub struct RemoveSegmenter<'info> {
     #[account(mut)]
     pub registry: AccountLoader<'info, Registry>,
 
+    /// The audit log of the registry, which the change is appended to, created if it doesn't exist
+    /// yet
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AuditLog::LEN,
+        seeds = [
+            AuditLog::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The revocation of the segmenter, which the removal and its reason are recorded in, created
+    /// if the segmenter was never removed before
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = Revocation::LEN,
+        seeds = [
+            Revocation::SEED,
+            registry.key().as_ref(),
+            key.as_ref(),
+        ],
+        bump,
+    )]
+    pub revocation: AccountLoader<'info, Revocation>,
+
+    /// The segmenter tiers of the registry, which the segmenter's tier is cleared from if they
+    /// were created
+    /// CHECK: only used if owned by this program, in which case it is segmenter tiers
+    #[account(
+        mut,
+        seeds = [
+            SegmenterTiers::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub segmenter_tiers: UncheckedAccount<'info>,
+
     #[account(has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified)]
     pub config: Account<'info, Config>,
 
-    /// The admin for the deployment
+    /// The admin for the deployment, who pays for creating the audit log if it doesn't exist yet
+    #[account(mut)]
     pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<RemoveSegmenter>, key: Pubkey) -> Result<()> {
+pub fn handler(ctx: Context<RemoveSegmenter>, key: Pubkey, reason: RevocationReason) -> Result<()> {
+    let mut revocation =
+        Revocation::load_or_init(&ctx.accounts.revocation, ctx.accounts.registry.key(), key)?;
     if ctx.accounts.registry.load_mut()?.remove(key).is_none() {
         msg!("Registry does not contain key: {}", key);
+        return Ok(());
     };
-    Ok(())
+    revocation.record(reason)?;
+    SegmenterTiers::clear_if_created(&ctx.accounts.segmenter_tiers, &key)?;
+    AuditLog::load_or_init(&ctx.accounts.audit_log, ctx.accounts.registry.key())?.append(
+        ctx.accounts.admin.key(),
+        AuditAction::RemoveSegmenter,
+        key,
+    )
 }
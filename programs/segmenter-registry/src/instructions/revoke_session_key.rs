@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct RevokeSessionKey<'info> {
+    #[account(
+        mut,
+        close = segmenter,
+        seeds = [
+            SessionDelegation::SEED,
+            segmenter.key().as_ref(),
+            session_key.as_ref(),
+        ],
+        bump,
+    )]
+    pub session_delegation: AccountLoader<'info, SessionDelegation>,
+
+    /// The segmenter that delegated its signing rights, which receives the rent
+    #[account(mut)]
+    pub segmenter: Signer<'info>,
+}
+
+pub fn handler(_ctx: Context<RevokeSessionKey>, _session_key: Pubkey) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SetCreationPolicy<'info> {
+    #[account(
+        mut,
+        has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified,
+        seeds = [
+            Config::SEED,
+        ],
+        bump,
+        realloc = Config::LEN,
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The admin for the deployment, who pays for growing configs initialized before creation
+    /// policies
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SetCreationPolicy>, creation_policy: CreationPolicy) -> Result<()> {
+    ctx.accounts.config.creation_policy = creation_policy;
+    Ok(())
+}
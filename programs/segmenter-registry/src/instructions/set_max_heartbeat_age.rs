@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SetMaxHeartbeatAge<'info> {
+    #[account(
+        mut,
+        seeds = [
+            HeartbeatTracker::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub heartbeat_tracker: AccountLoader<'info, HeartbeatTracker>,
+
+    pub registry: AccountLoader<'info, Registry>,
+
+    #[account(has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified)]
+    pub config: Account<'info, Config>,
+
+    /// The admin for the deployment
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetMaxHeartbeatAge>, max_heartbeat_age_slots: u64) -> Result<()> {
+    ctx.accounts
+        .heartbeat_tracker
+        .load_mut()?
+        .max_heartbeat_age_slots = max_heartbeat_age_slots;
+    Ok(())
+}
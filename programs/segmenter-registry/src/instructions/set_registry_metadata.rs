@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SetRegistryMetadata<'info> {
+    #[account(
+        mut,
+        has_one = authority @ SegmenterRegistryError::InvalidRegistryAuthority,
+    )]
+    pub registry_metadata: AccountLoader<'info, RegistryMetadata>,
+
+    /// The authority of the registry metadata
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SetRegistryMetadata>,
+    name: String,
+    description_uri: String,
+    tags: Vec<String>,
+) -> Result<()> {
+    ctx.accounts
+        .registry_metadata
+        .load_mut()?
+        .set(&name, &description_uri, &tags)
+}
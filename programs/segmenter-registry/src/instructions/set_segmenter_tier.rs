@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SetSegmenterTier<'info> {
+    #[account(
+        mut,
+        seeds = [
+            SegmenterTiers::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub segmenter_tiers: AccountLoader<'info, SegmenterTiers>,
+
+    pub registry: AccountLoader<'info, Registry>,
+
+    #[account(has_one = admin @ SegmenterRegistryError::InvalidAdminSpecified)]
+    pub config: Account<'info, Config>,
+
+    /// The admin for the deployment
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SetSegmenterTier>,
+    segmenter: Pubkey,
+    tier: Option<SegmenterTier>,
+) -> Result<()> {
+    let registry = ctx.accounts.registry.load()?;
+    require!(
+        tier.is_none() || registry.is_segmenter_registered(&segmenter),
+        SegmenterRegistryError::SegmenterNotRegistered
+    );
+
+    ctx.accounts
+        .segmenter_tiers
+        .load_mut()?
+        .set(&registry, segmenter, tier)
+}
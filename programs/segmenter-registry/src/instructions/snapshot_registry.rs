@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(version: u64)]
+pub struct SnapshotRegistry<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = RegistrySnapshot::LEN,
+        seeds = [
+            RegistrySnapshot::SEED,
+            registry.key().as_ref(),
+            &version.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub snapshot: AccountLoader<'info, RegistrySnapshot>,
+
+    pub registry: AccountLoader<'info, Registry>,
+
+    /// The audit log of the registry, whose number of changes is the version of the snapshot
+    #[account(
+        seeds = [
+            AuditLog::SEED,
+            registry.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    /// The payer for the snapshot account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SnapshotRegistry>, version: u64) -> Result<()> {
+    require_eq!(
+        version,
+        ctx.accounts.audit_log.load()?.head,
+        SegmenterRegistryError::InvalidSnapshotVersion
+    );
+
+    let mut snapshot = ctx.accounts.snapshot.load_init()?;
+    snapshot.registry = ctx.accounts.registry.key();
+    snapshot.version = version;
+    snapshot.slot = Clock::get()?.slot;
+    snapshot.registered_segmenters = ctx.accounts.registry.load()?.registered_segmenters;
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 use instructions::*;
+use state::{CreationPolicy, OverlayList, RevocationReason, SegmenterTier};
 
 pub mod errors;
 pub mod instructions;
@@ -17,9 +18,24 @@ pub mod segmenter_registry {
         instructions::initialize::handler(ctx)
     }
 
-    /// Creates a new registry
-    pub fn create_registry(ctx: Context<CreateRegistry>) -> Result<()> {
-        instructions::create_registry::handler(ctx)
+    /// Creates a new registry and lists it in the directory under a name, on the last page or on a
+    /// new page if the last one is full
+    pub fn create_registry(
+        ctx: Context<CreateRegistry>,
+        name: String,
+        page_index: u32,
+    ) -> Result<()> {
+        instructions::create_registry::handler(ctx, name, page_index)
+    }
+
+    /// Creates the next page of the directory of registries once the last page is full
+    pub fn create_directory_page(ctx: Context<CreateDirectoryPage>, page_index: u32) -> Result<()> {
+        instructions::create_directory_page::handler(ctx, page_index)
+    }
+
+    /// Creates the audit log of a registry ahead of the first change that is logged
+    pub fn create_audit_log(ctx: Context<CreateAuditLog>) -> Result<()> {
+        instructions::create_audit_log::handler(ctx)
     }
 
     /// Allows the admin to add a new segmenter to a registry
@@ -27,13 +43,167 @@ pub mod segmenter_registry {
         instructions::add_segmenter::handler(ctx, new_segmenter)
     }
 
-    /// Allows the admin to remove an existing segmenter from a registry
-    pub fn remove_segmenter(ctx: Context<RemoveSegmenter>, key: Pubkey) -> Result<()> {
-        instructions::remove_segmenter::handler(ctx, key)
+    /// Allows the admin to remove an existing segmenter from a registry, recording why in the
+    /// segmenter's revocation
+    pub fn remove_segmenter(
+        ctx: Context<RemoveSegmenter>,
+        key: Pubkey,
+        reason: RevocationReason,
+    ) -> Result<()> {
+        instructions::remove_segmenter::handler(ctx, key, reason)
+    }
+
+    /// Allows the admin to choose who may create registries
+    pub fn set_creation_policy(
+        ctx: Context<SetCreationPolicy>,
+        creation_policy: CreationPolicy,
+    ) -> Result<()> {
+        instructions::set_creation_policy::handler(ctx, creation_policy)
     }
 
     /// Allows the admin to appoint a new admin
     pub fn change_admin(ctx: Context<ChangeAdmin>, new_admin: Pubkey) -> Result<()> {
         instructions::change_admin::handler(ctx, new_admin)
     }
+
+    /// Creates a nonce tracker for a segmenter's attestations, whose nonces only the authority can
+    /// consume
+    pub fn create_nonce_tracker(ctx: Context<CreateNonceTracker>, segmenter: Pubkey) -> Result<()> {
+        instructions::create_nonce_tracker::handler(ctx, segmenter)
+    }
+
+    /// Allows the authority of a nonce tracker to mark an attestation nonce consumed. Fails if the
+    /// nonce has already been consumed or is older than the tracker's window.
+    pub fn consume_nonce(ctx: Context<ConsumeNonce>, nonce: u64) -> Result<()> {
+        instructions::consume_nonce::handler(ctx, nonce)
+    }
+
+    /// Allows the admin to register an origin in the origin directory with the signing key of its
+    /// frontend
+    pub fn register_origin(
+        ctx: Context<RegisterOrigin>,
+        origin: u16,
+        frontend_key: Pubkey,
+    ) -> Result<()> {
+        instructions::register_origin::handler(ctx, origin, frontend_key)
+    }
+
+    /// Allows the admin to change the frontend signing key of a registered origin
+    pub fn change_frontend_key(
+        ctx: Context<ChangeFrontendKey>,
+        new_frontend_key: Pubkey,
+    ) -> Result<()> {
+        instructions::change_frontend_key::handler(ctx, new_frontend_key)
+    }
+
+    /// Allows a registered segmenter to delegate its signing rights to a session key until the
+    /// expiry slot, optionally only for one DEX program
+    pub fn register_session_key(
+        ctx: Context<RegisterSessionKey>,
+        session_key: Pubkey,
+        expiry_slot: u64,
+        program_scope: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::register_session_key::handler(ctx, session_key, expiry_slot, program_scope)
+    }
+
+    /// Allows a segmenter to revoke a session key before it expires, refunding the rent of the
+    /// session delegation to the segmenter
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>, session_key: Pubkey) -> Result<()> {
+        instructions::revoke_session_key::handler(ctx, session_key)
+    }
+
+    /// Allows the admin to add a program as a segmenter to a registry. The program's PDA with
+    /// exactly the seed is registered, so it counts as a segmenter when the program signs for it.
+    pub fn add_program_segmenter(
+        ctx: Context<AddProgramSegmenter>,
+        program_id: Pubkey,
+        seed: Vec<u8>,
+    ) -> Result<()> {
+        instructions::add_program_segmenter::handler(ctx, program_id, seed)
+    }
+
+    /// Allows the admin to remove a program segmenter, and its PDA, from a registry
+    pub fn remove_program_segmenter(
+        ctx: Context<RemoveProgramSegmenter>,
+        reason: RevocationReason,
+    ) -> Result<()> {
+        instructions::remove_program_segmenter::handler(ctx, reason)
+    }
+
+    /// Creates the segmenter tiers of a registry, which only the admin can update
+    pub fn create_segmenter_tiers(ctx: Context<CreateSegmenterTiers>) -> Result<()> {
+        instructions::create_segmenter_tiers::handler(ctx)
+    }
+
+    /// Allows the admin to assign a tier to a registered segmenter, or to clear its tier
+    pub fn set_segmenter_tier(
+        ctx: Context<SetSegmenterTier>,
+        segmenter: Pubkey,
+        tier: Option<SegmenterTier>,
+    ) -> Result<()> {
+        instructions::set_segmenter_tier::handler(ctx, segmenter, tier)
+    }
+
+    /// Creates the heartbeat tracker of a registry, which records when its segmenters were last seen
+    pub fn create_heartbeat_tracker(ctx: Context<CreateHeartbeatTracker>) -> Result<()> {
+        instructions::create_heartbeat_tracker::handler(ctx)
+    }
+
+    /// Allows a registered segmenter to record that it is online in the current slot
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        instructions::heartbeat::handler(ctx)
+    }
+
+    /// Allows the admin to set how many slots old a segmenter's last heartbeat may be before the
+    /// segmenter is treated as inactive, or 0 to never treat segmenters as inactive
+    pub fn set_max_heartbeat_age(
+        ctx: Context<SetMaxHeartbeatAge>,
+        max_heartbeat_age_slots: u64,
+    ) -> Result<()> {
+        instructions::set_max_heartbeat_age::handler(ctx, max_heartbeat_age_slots)
+    }
+
+    /// Copies the current segmenters of a registry into an immutable snapshot
+    pub fn snapshot_registry(ctx: Context<SnapshotRegistry>, version: u64) -> Result<()> {
+        instructions::snapshot_registry::handler(ctx, version)
+    }
+
+    /// Creates the metadata of a registry, which only the registry's authority can update
+    pub fn create_registry_metadata(ctx: Context<CreateRegistryMetadata>) -> Result<()> {
+        instructions::create_registry_metadata::handler(ctx)
+    }
+
+    /// Allows the authority of a registry to set its name, description URI and tags
+    pub fn set_registry_metadata(
+        ctx: Context<SetRegistryMetadata>,
+        name: String,
+        description_uri: String,
+        tags: Vec<String>,
+    ) -> Result<()> {
+        instructions::set_registry_metadata::handler(ctx, name, description_uri, tags)
+    }
+
+    /// Creates a DEX's overlay on a registry, which only the authority can update
+    pub fn create_overlay(ctx: Context<CreateOverlay>) -> Result<()> {
+        instructions::create_overlay::handler(ctx)
+    }
+
+    /// Allows the authority of an overlay to add a key to its denied or allowed segmenters
+    pub fn add_overlay_entry(
+        ctx: Context<AddOverlayEntry>,
+        list: OverlayList,
+        key: Pubkey,
+    ) -> Result<()> {
+        instructions::add_overlay_entry::handler(ctx, list, key)
+    }
+
+    /// Allows the authority of an overlay to remove a key from its denied or allowed segmenters
+    pub fn remove_overlay_entry(
+        ctx: Context<RemoveOverlayEntry>,
+        list: OverlayList,
+        key: Pubkey,
+    ) -> Result<()> {
+        instructions::remove_overlay_entry::handler(ctx, list, key)
+    }
 }
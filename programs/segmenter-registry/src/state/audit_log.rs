@@ -0,0 +1,78 @@
+use std::cell::RefMut;
+
+use anchor_lang::prelude::*;
+
+const MAX_ITEMS: usize = 64;
+
+/// A change to a registry or to the deployment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AuditAction {
+    AddSegmenter,
+    RemoveSegmenter,
+    /// The admin of the deployment was changed, recorded in the deployment's audit log
+    ChangeAdmin,
+    /// The registry was listed in the directory with an authority
+    SetDirectoryAuthority,
+    /// The metadata of the registry was created with an authority
+    SetMetadataAuthority,
+}
+
+/// A change recorded in an audit log
+#[zero_copy]
+pub struct AuditEntry {
+    /// The slot of the change
+    pub slot: u64,
+    /// The account that made the change
+    pub actor: Pubkey,
+    /// The segmenter that was added or removed, or the new admin or authority
+    pub key: Pubkey,
+    /// The [`AuditAction`] as a `u8`
+    pub action: u8,
+    pub padding: [u8; 7],
+}
+
+/// The most recent changes to a registry, as a ring buffer that overwrites the oldest entry once
+/// full. Audit logs are PDAs with seeds `["audit_log", registry]`, created by the first change that
+/// is logged. Admin changes are logged in the deployment's audit log, with seeds
+/// `["audit_log", config]`.
+#[account(zero_copy)]
+pub struct AuditLog {
+    /// The registry, or the config for the deployment's audit log
+    pub registry: Pubkey,
+    /// The number of entries ever appended. The next entry is written at `head % 64`.
+    pub head: u64,
+    pub entries: [AuditEntry; MAX_ITEMS],
+}
+
+impl AuditLog {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"audit_log";
+
+    /// Borrows an audit log that `init_if_needed` may have just created, initializing it for
+    /// `registry` if so
+    pub fn load_or_init<'a>(
+        audit_log: &'a AccountLoader<Self>,
+        registry: Pubkey,
+    ) -> Result<RefMut<'a, Self>> {
+        match audit_log.load_init() {
+            Ok(mut new_audit_log) => {
+                new_audit_log.registry = registry;
+                Ok(new_audit_log)
+            }
+            Err(_) => audit_log.load_mut(),
+        }
+    }
+
+    pub fn append(&mut self, actor: Pubkey, action: AuditAction, key: Pubkey) -> Result<()> {
+        self.entries[(self.head % MAX_ITEMS as u64) as usize] = AuditEntry {
+            slot: Clock::get()?.slot,
+            actor,
+            key,
+            action: action as u8,
+            padding: [0; 7],
+        };
+        self.head += 1;
+        Ok(())
+    }
+}
@@ -5,9 +5,50 @@ use anchor_lang::prelude::*;
 pub struct Config {
     /// The public key of the account that has permission to modify the Registry accounts.
     pub admin: Pubkey,
+    /// Who may create registries
+    pub creation_policy: CreationPolicy,
 }
 
 impl Config {
-    pub const LEN: usize = 8 + 32;
+    pub const LEN: usize = 8 + 32 + CreationPolicy::MAX_LEN;
     pub const SEED: &'static [u8] = b"config";
 }
+
+/// Who may create registries with `create_registry`
+#[derive(AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CreationPolicy {
+    /// Any signer may create a registry
+    Open,
+    /// Only the admin may create a registry
+    AdminOnly,
+    /// Any signer may create a registry by paying a fee to the treasury
+    FeeRequired { fee_lamports: u64, treasury: Pubkey },
+}
+
+impl CreationPolicy {
+    pub const MAX_LEN: usize = 1 + 8 + 32;
+}
+
+impl AnchorDeserialize for CreationPolicy {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut variant = [0];
+        // Configs initialized before creation policies end after the admin, and are open until
+        // `set_creation_policy` reallocates them
+        if reader.read(&mut variant)? == 0 {
+            return Ok(Self::Open);
+        }
+
+        match variant[0] {
+            0 => Ok(Self::Open),
+            1 => Ok(Self::AdminOnly),
+            2 => Ok(Self::FeeRequired {
+                fee_lamports: AnchorDeserialize::deserialize_reader(reader)?,
+                treasury: AnchorDeserialize::deserialize_reader(reader)?,
+            }),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "invalid creation policy",
+            )),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+
+const MAX_ITEMS: usize = 32;
+
+/// A registry listed in the directory
+#[zero_copy]
+pub struct DirectoryEntry {
+    pub registry: Pubkey,
+    /// The account that created the registry
+    pub authority: Pubkey,
+    /// The name of the registry, padded with zero bytes
+    pub name: [u8; 32],
+    /// The slot the registry was created at
+    pub created_at_slot: u64,
+}
+
+/// A page of the directory of registries, which `create_registry` appends to. Pages are PDAs with
+/// seeds `["directory", page_index]`, and a page can only be created once the previous one is full,
+/// so only the last page has room for more registries.
+#[account(zero_copy)]
+pub struct DirectoryPage {
+    pub page_index: u32,
+    /// The number of entries in use
+    pub len: u32,
+    pub entries: [DirectoryEntry; MAX_ITEMS],
+}
+
+impl DirectoryPage {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"directory";
+    pub const MAX_NAME_LEN: usize = 32;
+
+    /// The entries in use
+    pub fn entries(&self) -> &[DirectoryEntry] {
+        &self.entries[..(self.len as usize).min(MAX_ITEMS)]
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len as usize >= MAX_ITEMS
+    }
+
+    pub fn push(&mut self, entry: DirectoryEntry) -> Result<()> {
+        if self.is_full() {
+            return Err(error!(SegmenterRegistryError::DirectoryPageFull));
+        }
+        self.entries[self.len as usize] = entry;
+        self.len += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::Registry;
+
+const MAX_ITEMS: usize = 64;
+
+/// The last slot a segmenter sent a heartbeat in
+#[zero_copy]
+pub struct HeartbeatEntry {
+    pub segmenter: Pubkey,
+    pub last_seen_slot: u64,
+}
+
+/// The heartbeats of the segmenters of a registry, through which segmenters that went offline can
+/// be treated as inactive without removing them from the registry
+#[account(zero_copy)]
+pub struct HeartbeatTracker {
+    pub registry: Pubkey,
+    /// Segmenters whose last heartbeat is more than this many slots old are inactive. 0 disables
+    /// staleness detection.
+    pub max_heartbeat_age_slots: u64,
+    pub entries: [HeartbeatEntry; MAX_ITEMS],
+}
+
+impl HeartbeatTracker {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"heartbeat";
+
+    /// Records a heartbeat of `segmenter`, which must be registered in `registry`. Entries of
+    /// segmenters no longer in the registry are reused, so there is always room for every
+    /// registered segmenter.
+    pub fn record(&mut self, registry: &Registry, segmenter: Pubkey, slot: u64) -> Result<()> {
+        let Some(idx) = self
+            .entries
+            .iter()
+            .position(|entry| entry.segmenter == segmenter)
+            .or_else(|| {
+                self.entries.iter().position(|entry| {
+                    entry.segmenter == Pubkey::default()
+                        || !registry.is_segmenter_registered(&entry.segmenter)
+                })
+            })
+        else {
+            return Err(error!(SegmenterRegistryError::HeartbeatTrackerSaturated));
+        };
+        self.entries[idx] = HeartbeatEntry {
+            segmenter,
+            last_seen_slot: slot,
+        };
+        Ok(())
+    }
+}
@@ -1,5 +1,29 @@
+pub mod audit_log;
 pub mod config;
+pub mod directory;
+pub mod heartbeat_tracker;
+pub mod nonce_tracker;
+pub mod origin_entry;
+pub mod overlay;
+pub mod program_segmenter;
 pub mod registry;
+pub mod registry_metadata;
+pub mod registry_snapshot;
+pub mod revocation;
+pub mod segmenter_tiers;
+pub mod session_delegation;
 
+pub use audit_log::*;
 pub use config::*;
+pub use directory::*;
+pub use heartbeat_tracker::*;
+pub use nonce_tracker::*;
+pub use origin_entry::*;
+pub use overlay::*;
+pub use program_segmenter::*;
 pub use registry::*;
+pub use registry_metadata::*;
+pub use registry_snapshot::*;
+pub use revocation::*;
+pub use segmenter_tiers::*;
+pub use session_delegation::*;
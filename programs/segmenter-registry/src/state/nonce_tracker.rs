@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+
+const WORDS: usize = 64;
+
+/// Tracks which of a segmenter's attestation nonces an authority has consumed, so that an
+/// attestation can't be replayed. Only the most recent `WINDOW` nonces are tracked: consuming a nonce
+/// beyond the window slides the window forward, and nonces that fall behind it can't be consumed.
+#[account(zero_copy)]
+pub struct NonceTracker {
+    /// The account that may consume nonces, usually a PDA of the program that verifies attestations
+    pub authority: Pubkey,
+    /// The segmenter whose attestation nonces are tracked
+    pub segmenter: Pubkey,
+    /// The lowest nonce in the window
+    pub base: u64,
+    /// Bit `nonce % WINDOW` is set if `nonce` has been consumed
+    pub bitmap: [u64; WORDS],
+}
+
+impl NonceTracker {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"nonce_tracker";
+    pub const WINDOW: u64 = (WORDS * 64) as u64;
+
+    pub fn consume(&mut self, nonce: u64) -> Result<()> {
+        if nonce < self.base {
+            return Err(error!(SegmenterRegistryError::NonceTooOld));
+        }
+
+        if nonce - self.base >= Self::WINDOW {
+            let new_base = nonce - Self::WINDOW + 1;
+            if new_base - self.base >= Self::WINDOW {
+                self.bitmap = [0; WORDS];
+            } else {
+                self.clear(self.base, new_base);
+            }
+            self.base = new_base;
+        }
+
+        let bit = nonce % Self::WINDOW;
+        let word = &mut self.bitmap[(bit / 64) as usize];
+        let mask = 1 << (bit % 64);
+        if *word & mask != 0 {
+            return Err(error!(SegmenterRegistryError::NonceAlreadyConsumed));
+        }
+        *word |= mask;
+        Ok(())
+    }
+
+    /// Clears the bits of the nonces in `start..end`, a range shorter than `WINDOW`, a whole word
+    /// at a time where it can
+    fn clear(&mut self, start: u64, end: u64) {
+        let mut nonce = start;
+        while nonce < end {
+            let bit = nonce % Self::WINDOW;
+            let offset = bit % 64;
+            let len = (64 - offset).min(end - nonce);
+            let mask = if len == 64 {
+                u64::MAX
+            } else {
+                ((1 << len) - 1) << offset
+            };
+            self.bitmap[(bit / 64) as usize] &= !mask;
+            nonce += len;
+        }
+    }
+}
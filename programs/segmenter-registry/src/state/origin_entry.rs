@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+/// An entry in the origin directory, which registers the signing key of the frontend an origin
+/// identifies. Programs can require the frontend to co-sign claims of its origin.
+#[account(zero_copy)]
+pub struct OriginEntry {
+    /// The key the frontend signs origin claims with
+    pub frontend_key: Pubkey,
+    /// The origin identifier
+    pub origin: u16,
+}
+
+impl OriginEntry {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"origin";
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+
+const MAX_ITEMS: usize = 16;
+
+/// A DEX's local overlay on a registry, which denies registered segmenters it distrusts and allows
+/// additional segmenters. A key in both lists is denied.
+#[account(zero_copy)]
+pub struct Overlay {
+    /// The DEX that manages the overlay
+    pub authority: Pubkey,
+    /// The registry the overlay applies to
+    pub registry: Pubkey,
+    pub denied_segmenters: [Pubkey; MAX_ITEMS],
+    pub allowed_segmenters: [Pubkey; MAX_ITEMS],
+}
+
+/// One of the lists of an overlay
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayList {
+    Denied,
+    Allowed,
+}
+
+impl Overlay {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"overlay";
+
+    fn list_mut(&mut self, list: OverlayList) -> &mut [Pubkey; MAX_ITEMS] {
+        match list {
+            OverlayList::Denied => &mut self.denied_segmenters,
+            OverlayList::Allowed => &mut self.allowed_segmenters,
+        }
+    }
+
+    pub fn add(&mut self, list: OverlayList, key: Pubkey) -> Result<()> {
+        let keys = self.list_mut(list);
+        if keys.binary_search(&key).is_ok() {
+            return Err(error!(SegmenterRegistryError::DuplicateEntry));
+        }
+
+        let Some(insert_idx) = keys.iter().position(|item| item == &Pubkey::default()) else {
+            return Err(error!(SegmenterRegistryError::OverlaySaturated));
+        };
+
+        keys[insert_idx] = key;
+        keys.sort_unstable();
+        Ok(())
+    }
+
+    pub fn remove(&mut self, list: OverlayList, key: Pubkey) -> Option<Pubkey> {
+        let keys = self.list_mut(list);
+        let idx = keys.binary_search(&key).ok()?;
+        keys[idx] = Pubkey::default();
+        keys.sort_unstable();
+        Some(key)
+    }
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+/// A segmenter that is a program rather than a fixed key. The segmenter is the program's PDA with
+/// exactly one seed, which is registered in the registry, so it counts as a segmenter when the
+/// program signs for it with `invoke_signed`.
+#[account(zero_copy)]
+pub struct ProgramSegmenter {
+    /// The registry the program is a segmenter in
+    pub registry: Pubkey,
+    /// The program that signs as the segmenter
+    pub program_id: Pubkey,
+    /// The PDA of the program with the seed, which is registered in the registry
+    pub segmenter: Pubkey,
+    /// The number of bytes of `seed` in use
+    pub seed_len: u8,
+    /// The only seed of the segmenter PDA, besides the bump
+    pub seed: [u8; 32],
+    /// The bump seed of the segmenter PDA
+    pub bump: u8,
+}
+
+impl ProgramSegmenter {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"program_segmenter";
+    pub const MAX_SEED_LEN: usize = 32;
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SegmenterRegistryError;
+
+const MAX_TAGS: usize = 8;
+
+/// Descriptive metadata of a registry, for explorers and tooling. The strings are padded with zero
+/// bytes.
+#[account(zero_copy)]
+pub struct RegistryMetadata {
+    pub registry: Pubkey,
+    /// The account that can update the metadata
+    pub authority: Pubkey,
+    pub name: [u8; 32],
+    /// A URI of a longer description of the registry
+    pub description_uri: [u8; 256],
+    /// Tags describing what the registry is for
+    pub tags: [[u8; 16]; MAX_TAGS],
+}
+
+impl RegistryMetadata {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"registry_metadata";
+
+    pub fn set(&mut self, name: &str, description_uri: &str, tags: &[String]) -> Result<()> {
+        require!(
+            name.len() <= self.name.len(),
+            SegmenterRegistryError::RegistryNameTooLong
+        );
+        require!(
+            description_uri.len() <= self.description_uri.len(),
+            SegmenterRegistryError::DescriptionUriTooLong
+        );
+        require!(
+            tags.len() <= MAX_TAGS && tags.iter().all(|tag| tag.len() <= self.tags[0].len()),
+            SegmenterRegistryError::InvalidTags
+        );
+
+        pad(&mut self.name, name);
+        pad(&mut self.description_uri, description_uri);
+        self.tags = [[0; 16]; MAX_TAGS];
+        for (padded, tag) in self.tags.iter_mut().zip(tags) {
+            pad(padded, tag);
+        }
+        Ok(())
+    }
+}
+
+/// Copies `value` into `padded`, padding it with zero bytes
+fn pad(padded: &mut [u8], value: &str) {
+    padded.fill(0);
+    padded[..value.len()].copy_from_slice(value.as_bytes());
+}
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+const MAX_ITEMS: usize = 64;
+
+/// An immutable copy of the segmenters of a registry at a slot, for proving historical segmenter
+/// status
+#[account(zero_copy)]
+pub struct RegistrySnapshot {
+    pub registry: Pubkey,
+    /// The number of changes in the registry's audit log when the snapshot was taken, which
+    /// identifies the membership the snapshot holds
+    pub version: u64,
+    /// The slot the snapshot was taken at
+    pub slot: u64,
+    pub registered_segmenters: [Pubkey; MAX_ITEMS],
+}
+
+impl RegistrySnapshot {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"snapshot";
+}
@@ -0,0 +1,61 @@
+use std::cell::RefMut;
+
+use anchor_lang::prelude::*;
+
+/// Why a segmenter was removed from a registry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RevocationReason {
+    /// The segmenter's key was compromised
+    Compromised,
+    /// The segmenter was retired by its operator
+    Retired,
+    /// The segmenter misbehaved
+    Misbehavior,
+}
+
+/// A tombstone for a segmenter removed from a registry, recording its latest removal. Revocations
+/// are PDAs with seeds `["revocation", registry, key]`, created by the first removal of the key and
+/// never closed, so they are kept however many segmenters are removed.
+#[account(zero_copy)]
+pub struct Revocation {
+    pub registry: Pubkey,
+    /// The segmenter that was removed
+    pub key: Pubkey,
+    /// The slot of the latest removal
+    pub slot: u64,
+    /// The number of times the segmenter was removed, 0 if it wasn't registered when removed
+    pub count: u64,
+    /// The [`RevocationReason`] of the latest removal as a `u8`
+    pub reason: u8,
+    pub padding: [u8; 7],
+}
+
+impl Revocation {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"revocation";
+
+    /// Borrows a revocation that `init_if_needed` may have just created, initializing it for `key`
+    /// in `registry` if so
+    pub fn load_or_init<'a>(
+        revocation: &'a AccountLoader<Self>,
+        registry: Pubkey,
+        key: Pubkey,
+    ) -> Result<RefMut<'a, Self>> {
+        match revocation.load_init() {
+            Ok(mut new_revocation) => {
+                new_revocation.registry = registry;
+                new_revocation.key = key;
+                Ok(new_revocation)
+            }
+            Err(_) => revocation.load_mut(),
+        }
+    }
+
+    pub fn record(&mut self, reason: RevocationReason) -> Result<()> {
+        self.slot = Clock::get()?.slot;
+        self.count += 1;
+        self.reason = reason as u8;
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::SegmenterRegistryError;
+use crate::state::Registry;
+
+const MAX_ITEMS: usize = 64;
+
+/// How much a DEX may trust a segmenter, from the most to the least trusted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SegmenterTier {
+    Gold,
+    Silver,
+    Probation,
+}
+
+/// The tier of a segmenter
+#[zero_copy]
+pub struct TierEntry {
+    pub segmenter: Pubkey,
+    /// The [`SegmenterTier`] as a `u8`
+    pub tier: u8,
+}
+
+/// The tiers the admin assigned to the segmenters of a registry. Segmenters without an entry have
+/// no tier.
+#[account(zero_copy)]
+pub struct SegmenterTiers {
+    pub registry: Pubkey,
+    pub entries: [TierEntry; MAX_ITEMS],
+}
+
+impl SegmenterTiers {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"tiers";
+
+    /// Sets the tier of `segmenter`, which must be registered in `registry`, or clears it if
+    /// `tier` is `None`. Entries of segmenters no longer in the registry are reused, so there is
+    /// always room for every registered segmenter.
+    pub fn set(
+        &mut self,
+        registry: &Registry,
+        segmenter: Pubkey,
+        tier: Option<SegmenterTier>,
+    ) -> Result<()> {
+        let existing = self
+            .entries
+            .iter()
+            .position(|entry| entry.segmenter == segmenter);
+        let Some(tier) = tier else {
+            self.clear(&segmenter);
+            return Ok(());
+        };
+
+        let Some(idx) = existing.or_else(|| {
+            self.entries.iter().position(|entry| {
+                entry.segmenter == Pubkey::default()
+                    || !registry.is_segmenter_registered(&entry.segmenter)
+            })
+        }) else {
+            return Err(error!(SegmenterRegistryError::SegmenterTiersSaturated));
+        };
+        self.entries[idx] = TierEntry {
+            segmenter,
+            tier: tier as u8,
+        };
+        Ok(())
+    }
+
+    /// Clears the tier of `segmenter`, if it has one
+    pub fn clear(&mut self, segmenter: &Pubkey) {
+        for entry in self
+            .entries
+            .iter_mut()
+            .filter(|entry| entry.segmenter == *segmenter)
+        {
+            *entry = TierEntry {
+                segmenter: Pubkey::default(),
+                tier: 0,
+            };
+        }
+    }
+
+    /// Clears the tier of `segmenter` from the segmenter tiers in `segmenter_tiers`, which must be
+    /// the address of the registry's segmenter tiers. Does nothing if they weren't created, since
+    /// segmenter tiers are optional.
+    pub fn clear_if_created(segmenter_tiers: &AccountInfo, segmenter: &Pubkey) -> Result<()> {
+        if *segmenter_tiers.owner != crate::ID {
+            return Ok(());
+        }
+        let mut data = segmenter_tiers.try_borrow_mut_data()?;
+        let segmenter_tiers = data
+            .get_mut(..Self::LEN)
+            .filter(|data| data.starts_with(&Self::DISCRIMINATOR))
+            .and_then(|data| bytemuck::try_from_bytes_mut::<Self>(&mut data[8..]).ok())
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        segmenter_tiers.clear(segmenter);
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+/// Delegates the signing rights of a registered segmenter to a short-lived session key, so the
+/// segmenter's registered key doesn't have to sign every transaction
+#[account(zero_copy)]
+pub struct SessionDelegation {
+    /// The registered segmenter that delegated its signing rights
+    pub segmenter: Pubkey,
+    /// The key that may sign on behalf of the segmenter
+    pub session_key: Pubkey,
+    /// The DEX program the session key may sign for, or the default public key for any program
+    pub program_scope: Pubkey,
+    /// The last slot in which the session key may sign
+    pub expiry_slot: u64,
+}
+
+impl SessionDelegation {
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+    pub const SEED: &'static [u8] = b"session";
+    /// The longest a session key can be valid for, about a day
+    pub const MAX_DURATION_SLOTS: u64 = 216_000;
+}
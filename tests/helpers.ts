@@ -11,6 +11,80 @@ type Registry = Awaited<
   ReturnType<anchor.Program<SegmenterRegistry>["account"]["registry"]["fetch"]>
 >;
 
+type NonceTracker = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["nonceTracker"]["fetch"]
+  >
+>;
+
+type OriginEntry = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["originEntry"]["fetch"]
+  >
+>;
+
+type SessionDelegation = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["sessionDelegation"]["fetch"]
+  >
+>;
+
+type ProgramSegmenter = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["programSegmenter"]["fetch"]
+  >
+>;
+
+type AuditLog = Awaited<
+  ReturnType<anchor.Program<SegmenterRegistry>["account"]["auditLog"]["fetch"]>
+>;
+
+type AuditEntry = AuditLog["entries"][number];
+
+type Revocation = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["revocation"]["fetch"]
+  >
+>;
+
+type DirectoryPage = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["directoryPage"]["fetch"]
+  >
+>;
+
+type DirectoryEntry = DirectoryPage["entries"][number];
+
+type RegistryMetadata = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["registryMetadata"]["fetch"]
+  >
+>;
+
+type SegmenterTiers = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["segmenterTiers"]["fetch"]
+  >
+>;
+
+type HeartbeatTracker = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["heartbeatTracker"]["fetch"]
+  >
+>;
+
+type RegistrySnapshot = Awaited<
+  ReturnType<
+    anchor.Program<SegmenterRegistry>["account"]["registrySnapshot"]["fetch"]
+  >
+>;
+
+type Overlay = Awaited<
+  ReturnType<anchor.Program<SegmenterRegistry>["account"]["overlay"]["fetch"]>
+>;
+
+type OverlayList = { denied: {} } | { allowed: {} };
+
 type InitializeInstructionArgs = {
   program: anchor.Program<SegmenterRegistry>;
   admin: PublicKey;
@@ -29,26 +103,100 @@ export async function initializeInstruction({
     .instruction();
 }
 
-type CreateRegistryInstructionArgs = {
+type CreateRegistryInstructionsArgs = {
   program: anchor.Program<SegmenterRegistry>;
   payer: PublicKey;
   registry: PublicKey;
+  name: string;
+  /** Defaults to the last directory page, or the next page if the last one is full or there are no
+   * pages yet */
+  pageIndex?: number;
+};
+
+/** Creates the registry on a directory page, creating the page if it doesn't exist yet */
+export async function createRegistryInstructions({
+  program,
+  registry,
+  payer,
+  name,
+  pageIndex,
+}: CreateRegistryInstructionsArgs): Promise<TransactionInstruction[]> {
+  const instructions: TransactionInstruction[] = [];
+  const { creationPolicy } = await getConfigState(program);
+  if (pageIndex === undefined) {
+    const pages = await getDirectoryPageStates(program);
+    const lastPage = pages[pages.length - 1];
+    pageIndex = pages.length - 1;
+    if (lastPage === undefined || lastPage.len >= DIRECTORY_PAGE_LENGTH) {
+      pageIndex = pages.length;
+    }
+  }
+
+  instructions.push(
+    await program.methods
+      .createRegistry(name, pageIndex)
+      .accounts({
+        registry,
+        directoryPage: getDirectoryPageAccount(program.programId, pageIndex),
+        previousPage:
+          pageIndex > 0
+            ? getDirectoryPageAccount(program.programId, pageIndex - 1)
+            : null,
+        auditLog: getAuditLogAccount(program.programId, registry),
+        config: getConfigAccount(program.programId),
+        payer,
+        treasury: creationPolicy.feeRequired?.treasury ?? null,
+      })
+      .instruction()
+  );
+  return instructions;
+}
+
+type CreateAuditLogInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  payer: PublicKey;
 };
 
-export async function createRegistryInstruction({
+export async function createAuditLogInstruction({
   program,
   registry,
   payer,
-}: CreateRegistryInstructionArgs): Promise<TransactionInstruction> {
+}: CreateAuditLogInstructionArgs): Promise<TransactionInstruction> {
   return await program.methods
-    .createRegistry()
+    .createAuditLog()
     .accounts({
+      auditLog: getAuditLogAccount(program.programId, registry),
       registry,
       payer,
     })
     .instruction();
 }
 
+type CreateDirectoryPageInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  payer: PublicKey;
+  pageIndex: number;
+};
+
+export async function createDirectoryPageInstruction({
+  program,
+  payer,
+  pageIndex,
+}: CreateDirectoryPageInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .createDirectoryPage(pageIndex)
+    .accounts({
+      directoryPage: getDirectoryPageAccount(program.programId, pageIndex),
+      previousPage:
+        pageIndex > 0
+          ? getDirectoryPageAccount(program.programId, pageIndex - 1)
+          : null,
+      payer,
+    })
+    .instruction();
+}
+
 type AddSegmenterInstructionArgs = {
   program: anchor.Program<SegmenterRegistry>;
   admin: PublicKey;
@@ -66,17 +214,24 @@ export async function addSegmenterInstruction({
     .addSegmenter(addKey)
     .accounts({
       registry,
+      auditLog: getAuditLogAccount(program.programId, registry),
       admin,
       config: getConfigAccount(program.programId),
     })
     .instruction();
 }
 
+export type RevocationReason =
+  | { compromised: {} }
+  | { retired: {} }
+  | { misbehavior: {} };
+
 type RemoveSegmenterInstructionArgs = {
   program: anchor.Program<SegmenterRegistry>;
   admin: PublicKey;
   registry: PublicKey;
   removeKey: PublicKey;
+  reason?: RevocationReason;
 };
 
 export async function removeSegmenterInstruction({
@@ -84,37 +239,542 @@ export async function removeSegmenterInstruction({
   admin,
   registry,
   removeKey,
+  reason = { retired: {} },
 }: RemoveSegmenterInstructionArgs): Promise<TransactionInstruction> {
   return await program.methods
-    .removeSegmenter(removeKey)
+    .removeSegmenter(removeKey, reason)
     .accounts({
       registry,
+      auditLog: getAuditLogAccount(program.programId, registry),
+      revocation: getRevocationAccount(program.programId, registry, removeKey),
+      segmenterTiers: getSegmenterTiersAccount(program.programId, registry),
       admin,
       config: getConfigAccount(program.programId),
     })
     .instruction();
 }
 
+export type CreationPolicy =
+  | { open: {} }
+  | { adminOnly: {} }
+  | { feeRequired: { feeLamports: anchor.BN; treasury: PublicKey } };
+
+type SetCreationPolicyInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  admin: PublicKey;
+  creationPolicy: CreationPolicy;
+};
+
+export async function setCreationPolicyInstruction({
+  program,
+  admin,
+  creationPolicy,
+}: SetCreationPolicyInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .setCreationPolicy(creationPolicy)
+    .accounts({
+      config: getConfigAccount(program.programId),
+      admin,
+    })
+    .instruction();
+}
+
 type ChangeAdminInstructionArgs = {
   program: anchor.Program<SegmenterRegistry>;
   admin: PublicKey;
   newAdmin: PublicKey;
+  /** Defaults to the deployment's config */
+  config?: PublicKey;
 };
 
 export async function changeAdminInstruction({
   program,
   admin,
   newAdmin,
+  config,
 }: ChangeAdminInstructionArgs): Promise<TransactionInstruction> {
+  config ??= getConfigAccount(program.programId);
   return await program.methods
     .changeAdmin(newAdmin)
     .accounts({
+      config,
+      auditLog: getAuditLogAccount(program.programId, config),
+      admin,
+    })
+    .instruction();
+}
+
+type CreateNonceTrackerInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  authority: PublicKey;
+  payer: PublicKey;
+  segmenter: PublicKey;
+};
+
+export async function createNonceTrackerInstruction({
+  program,
+  authority,
+  payer,
+  segmenter,
+}: CreateNonceTrackerInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .createNonceTracker(segmenter)
+    .accounts({
+      nonceTracker: getNonceTrackerAccount(
+        program.programId,
+        authority,
+        segmenter
+      ),
+      authority,
+      payer,
+    })
+    .instruction();
+}
+
+type ConsumeNonceInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  nonceTracker: PublicKey;
+  authority: PublicKey;
+  nonce: anchor.BN;
+};
+
+export async function consumeNonceInstruction({
+  program,
+  nonceTracker,
+  authority,
+  nonce,
+}: ConsumeNonceInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .consumeNonce(nonce)
+    .accounts({
+      nonceTracker,
+      authority,
+    })
+    .instruction();
+}
+
+type RegisterOriginInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  admin: PublicKey;
+  origin: number;
+  frontendKey: PublicKey;
+};
+
+export async function registerOriginInstruction({
+  program,
+  admin,
+  origin,
+  frontendKey,
+}: RegisterOriginInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .registerOrigin(origin, frontendKey)
+    .accounts({
+      originEntry: getOriginEntryAccount(program.programId, origin),
+      config: getConfigAccount(program.programId),
+      admin,
+    })
+    .instruction();
+}
+
+type ChangeFrontendKeyInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  admin: PublicKey;
+  origin: number;
+  newFrontendKey: PublicKey;
+};
+
+export async function changeFrontendKeyInstruction({
+  program,
+  admin,
+  origin,
+  newFrontendKey,
+}: ChangeFrontendKeyInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .changeFrontendKey(newFrontendKey)
+    .accounts({
+      originEntry: getOriginEntryAccount(program.programId, origin),
+      config: getConfigAccount(program.programId),
+      admin,
+    })
+    .instruction();
+}
+
+type RegisterSessionKeyInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  segmenter: PublicKey;
+  payer: PublicKey;
+  sessionKey: PublicKey;
+  expirySlot: anchor.BN;
+  programScope: PublicKey | null;
+};
+
+export async function registerSessionKeyInstruction({
+  program,
+  registry,
+  segmenter,
+  payer,
+  sessionKey,
+  expirySlot,
+  programScope,
+}: RegisterSessionKeyInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .registerSessionKey(sessionKey, expirySlot, programScope)
+    .accounts({
+      sessionDelegation: getSessionDelegationAccount(
+        program.programId,
+        segmenter,
+        sessionKey
+      ),
+      registry,
+      segmenter,
+      payer,
+    })
+    .instruction();
+}
+
+type RevokeSessionKeyInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  segmenter: PublicKey;
+  sessionKey: PublicKey;
+};
+
+export async function revokeSessionKeyInstruction({
+  program,
+  segmenter,
+  sessionKey,
+}: RevokeSessionKeyInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .revokeSessionKey(sessionKey)
+    .accounts({
+      sessionDelegation: getSessionDelegationAccount(
+        program.programId,
+        segmenter,
+        sessionKey
+      ),
+      segmenter,
+    })
+    .instruction();
+}
+
+type AddProgramSegmenterInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  admin: PublicKey;
+  registry: PublicKey;
+  programId: PublicKey;
+  seed: Buffer;
+};
+
+export async function addProgramSegmenterInstruction({
+  program,
+  admin,
+  registry,
+  programId,
+  seed,
+}: AddProgramSegmenterInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .addProgramSegmenter(programId, seed)
+    .accounts({
+      programSegmenter: getProgramSegmenterAccount(
+        program.programId,
+        registry,
+        programId
+      ),
+      registry,
+      auditLog: getAuditLogAccount(program.programId, registry),
+      config: getConfigAccount(program.programId),
+      admin,
+    })
+    .instruction();
+}
+
+type RemoveProgramSegmenterInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  admin: PublicKey;
+  registry: PublicKey;
+  programId: PublicKey;
+  segmenter: PublicKey;
+  reason?: RevocationReason;
+};
+
+export async function removeProgramSegmenterInstruction({
+  program,
+  admin,
+  registry,
+  programId,
+  segmenter,
+  reason = { retired: {} },
+}: RemoveProgramSegmenterInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .removeProgramSegmenter(reason)
+    .accounts({
+      programSegmenter: getProgramSegmenterAccount(
+        program.programId,
+        registry,
+        programId
+      ),
+      registry,
+      auditLog: getAuditLogAccount(program.programId, registry),
+      revocation: getRevocationAccount(program.programId, registry, segmenter),
+      segmenterTiers: getSegmenterTiersAccount(program.programId, registry),
+      config: getConfigAccount(program.programId),
+      admin,
+    })
+    .instruction();
+}
+
+type CreateSegmenterTiersInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  payer: PublicKey;
+};
+
+export async function createSegmenterTiersInstruction({
+  program,
+  registry,
+  payer,
+}: CreateSegmenterTiersInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .createSegmenterTiers()
+    .accounts({
+      segmenterTiers: getSegmenterTiersAccount(program.programId, registry),
+      registry,
+      payer,
+    })
+    .instruction();
+}
+
+export type SegmenterTier = { gold: {} } | { silver: {} } | { probation: {} };
+
+type SetSegmenterTierInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  admin: PublicKey;
+  segmenter: PublicKey;
+  tier: SegmenterTier | null;
+};
+
+export async function setSegmenterTierInstruction({
+  program,
+  registry,
+  admin,
+  segmenter,
+  tier,
+}: SetSegmenterTierInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .setSegmenterTier(segmenter, tier)
+    .accounts({
+      segmenterTiers: getSegmenterTiersAccount(program.programId, registry),
+      registry,
+      config: getConfigAccount(program.programId),
+      admin,
+    })
+    .instruction();
+}
+
+type CreateHeartbeatTrackerInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  payer: PublicKey;
+};
+
+export async function createHeartbeatTrackerInstruction({
+  program,
+  registry,
+  payer,
+}: CreateHeartbeatTrackerInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .createHeartbeatTracker()
+    .accounts({
+      heartbeatTracker: getHeartbeatTrackerAccount(program.programId, registry),
+      registry,
+      payer,
+    })
+    .instruction();
+}
+
+type HeartbeatInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  segmenter: PublicKey;
+};
+
+export async function heartbeatInstruction({
+  program,
+  registry,
+  segmenter,
+}: HeartbeatInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .heartbeat()
+    .accounts({
+      heartbeatTracker: getHeartbeatTrackerAccount(program.programId, registry),
+      registry,
+      segmenter,
+    })
+    .instruction();
+}
+
+type SetMaxHeartbeatAgeInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  admin: PublicKey;
+  maxHeartbeatAgeSlots: anchor.BN;
+};
+
+export async function setMaxHeartbeatAgeInstruction({
+  program,
+  registry,
+  admin,
+  maxHeartbeatAgeSlots,
+}: SetMaxHeartbeatAgeInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .setMaxHeartbeatAge(maxHeartbeatAgeSlots)
+    .accounts({
+      heartbeatTracker: getHeartbeatTrackerAccount(program.programId, registry),
+      registry,
       config: getConfigAccount(program.programId),
       admin,
     })
     .instruction();
 }
 
+type SnapshotRegistryInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  version: anchor.BN;
+  payer: PublicKey;
+};
+
+export async function snapshotRegistryInstruction({
+  program,
+  registry,
+  version,
+  payer,
+}: SnapshotRegistryInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .snapshotRegistry(version)
+    .accounts({
+      snapshot: getRegistrySnapshotAccount(program.programId, registry, version),
+      registry,
+      auditLog: getAuditLogAccount(program.programId, registry),
+      payer,
+    })
+    .instruction();
+}
+
+type CreateRegistryMetadataInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  directoryPage: PublicKey | null;
+  authority: PublicKey;
+  payer: PublicKey;
+};
+
+export async function createRegistryMetadataInstruction({
+  program,
+  registry,
+  directoryPage,
+  authority,
+  payer,
+}: CreateRegistryMetadataInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .createRegistryMetadata()
+    .accounts({
+      registryMetadata: getRegistryMetadataAccount(program.programId, registry),
+      registry,
+      auditLog: getAuditLogAccount(program.programId, registry),
+      directoryPage,
+      config: getConfigAccount(program.programId),
+      authority,
+      payer,
+    })
+    .instruction();
+}
+
+type SetRegistryMetadataInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  registry: PublicKey;
+  authority: PublicKey;
+  name: string;
+  descriptionUri: string;
+  tags: string[];
+};
+
+export async function setRegistryMetadataInstruction({
+  program,
+  registry,
+  authority,
+  name,
+  descriptionUri,
+  tags,
+}: SetRegistryMetadataInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .setRegistryMetadata(name, descriptionUri, tags)
+    .accounts({
+      registryMetadata: getRegistryMetadataAccount(program.programId, registry),
+      authority,
+    })
+    .instruction();
+}
+
+type CreateOverlayInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  authority: PublicKey;
+  registry: PublicKey;
+  payer: PublicKey;
+};
+
+export async function createOverlayInstruction({
+  program,
+  authority,
+  registry,
+  payer,
+}: CreateOverlayInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .createOverlay()
+    .accounts({
+      overlay: getOverlayAccount(program.programId, authority, registry),
+      registry,
+      authority,
+      payer,
+    })
+    .instruction();
+}
+
+type OverlayEntryInstructionArgs = {
+  program: anchor.Program<SegmenterRegistry>;
+  overlay: PublicKey;
+  authority: PublicKey;
+  list: OverlayList;
+  key: PublicKey;
+};
+
+export async function addOverlayEntryInstruction({
+  program,
+  overlay,
+  authority,
+  list,
+  key,
+}: OverlayEntryInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .addOverlayEntry(list, key)
+    .accounts({ overlay, authority })
+    .instruction();
+}
+
+export async function removeOverlayEntryInstruction({
+  program,
+  overlay,
+  authority,
+  list,
+  key,
+}: OverlayEntryInstructionArgs): Promise<TransactionInstruction> {
+  return await program.methods
+    .removeOverlayEntry(list, key)
+    .accounts({ overlay, authority })
+    .instruction();
+}
+
 export function getConfigAccount(programId: PublicKey): PublicKey {
   return PublicKey.findProgramAddressSync(
     [Buffer.from("config")],
@@ -122,6 +782,144 @@ export function getConfigAccount(programId: PublicKey): PublicKey {
   )[0];
 }
 
+export function getNonceTrackerAccount(
+  programId: PublicKey,
+  authority: PublicKey,
+  segmenter: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("nonce_tracker"), authority.toBuffer(), segmenter.toBuffer()],
+    programId
+  )[0];
+}
+
+export function getOriginEntryAccount(
+  programId: PublicKey,
+  origin: number
+): PublicKey {
+  const originBytes = Buffer.alloc(2);
+  originBytes.writeUInt16LE(origin);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("origin"), originBytes],
+    programId
+  )[0];
+}
+
+export function getSessionDelegationAccount(
+  programId: PublicKey,
+  segmenter: PublicKey,
+  sessionKey: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("session"), segmenter.toBuffer(), sessionKey.toBuffer()],
+    programId
+  )[0];
+}
+
+export function getProgramSegmenterAccount(
+  programId: PublicKey,
+  registry: PublicKey,
+  segmenterProgramId: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("program_segmenter"),
+      registry.toBuffer(),
+      segmenterProgramId.toBuffer(),
+    ],
+    programId
+  )[0];
+}
+
+export function getAuditLogAccount(
+  programId: PublicKey,
+  registry: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("audit_log"), registry.toBuffer()],
+    programId
+  )[0];
+}
+
+export function getRevocationAccount(
+  programId: PublicKey,
+  registry: PublicKey,
+  key: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("revocation"), registry.toBuffer(), key.toBuffer()],
+    programId
+  )[0];
+}
+
+export function getDirectoryPageAccount(
+  programId: PublicKey,
+  pageIndex: number
+): PublicKey {
+  const pageIndexBytes = Buffer.alloc(4);
+  pageIndexBytes.writeUInt32LE(pageIndex);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("directory"), pageIndexBytes],
+    programId
+  )[0];
+}
+
+export function getSegmenterTiersAccount(
+  programId: PublicKey,
+  registry: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("tiers"), registry.toBuffer()],
+    programId
+  )[0];
+}
+
+export function getHeartbeatTrackerAccount(
+  programId: PublicKey,
+  registry: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("heartbeat"), registry.toBuffer()],
+    programId
+  )[0];
+}
+
+export function getRegistrySnapshotAccount(
+  programId: PublicKey,
+  registry: PublicKey,
+  version: anchor.BN
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("snapshot"),
+      registry.toBuffer(),
+      version.toArrayLike(Buffer, "le", 8),
+    ],
+    programId
+  )[0];
+}
+
+export function getRegistryMetadataAccount(
+  programId: PublicKey,
+  registry: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("registry_metadata"), registry.toBuffer()],
+    programId
+  )[0];
+}
+
+export function getOverlayAccount(
+  programId: PublicKey,
+  authority: PublicKey,
+  registry: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("overlay"), authority.toBuffer(), registry.toBuffer()],
+    programId
+  )[0];
+}
+
 export async function getRegistryState(
   program: anchor.Program<SegmenterRegistry>,
   registry: PublicKey
@@ -137,6 +935,173 @@ export async function getConfigState(
   );
 }
 
+export async function getNonceTrackerState(
+  program: anchor.Program<SegmenterRegistry>,
+  nonceTracker: PublicKey
+): Promise<NonceTracker> {
+  return await program.account.nonceTracker.fetch(nonceTracker);
+}
+
+export async function getOriginEntryState(
+  program: anchor.Program<SegmenterRegistry>,
+  originEntry: PublicKey
+): Promise<OriginEntry> {
+  return await program.account.originEntry.fetch(originEntry);
+}
+
+export async function getSessionDelegationState(
+  program: anchor.Program<SegmenterRegistry>,
+  sessionDelegation: PublicKey
+): Promise<SessionDelegation> {
+  return await program.account.sessionDelegation.fetch(sessionDelegation);
+}
+
+export async function getProgramSegmenterState(
+  program: anchor.Program<SegmenterRegistry>,
+  programSegmenter: PublicKey
+): Promise<ProgramSegmenter> {
+  return await program.account.programSegmenter.fetch(programSegmenter);
+}
+
+export async function getOverlayState(
+  program: anchor.Program<SegmenterRegistry>,
+  overlay: PublicKey
+): Promise<Overlay> {
+  return await program.account.overlay.fetch(overlay);
+}
+
+export async function getSegmenterTiersState(
+  program: anchor.Program<SegmenterRegistry>,
+  segmenterTiers: PublicKey
+): Promise<SegmenterTiers> {
+  return await program.account.segmenterTiers.fetch(segmenterTiers);
+}
+
+export const SEGMENTER_TIER_GOLD = 0;
+export const SEGMENTER_TIER_SILVER = 1;
+export const SEGMENTER_TIER_PROBATION = 2;
+
+export async function getHeartbeatTrackerState(
+  program: anchor.Program<SegmenterRegistry>,
+  heartbeatTracker: PublicKey
+): Promise<HeartbeatTracker> {
+  return await program.account.heartbeatTracker.fetch(heartbeatTracker);
+}
+
+export async function getRegistrySnapshotState(
+  program: anchor.Program<SegmenterRegistry>,
+  snapshot: PublicKey
+): Promise<RegistrySnapshot> {
+  return await program.account.registrySnapshot.fetch(snapshot);
+}
+
+export async function getRegistryMetadataState(
+  program: anchor.Program<SegmenterRegistry>,
+  registryMetadata: PublicKey
+): Promise<RegistryMetadata> {
+  return await program.account.registryMetadata.fetch(registryMetadata);
+}
+
+export async function getAuditLogState(
+  program: anchor.Program<SegmenterRegistry>,
+  auditLog: PublicKey
+): Promise<AuditLog> {
+  return await program.account.auditLog.fetch(auditLog);
+}
+
+export const AUDIT_LOG_LENGTH = 64;
+
+export const AUDIT_ACTION_ADD_SEGMENTER = 0;
+export const AUDIT_ACTION_REMOVE_SEGMENTER = 1;
+export const AUDIT_ACTION_CHANGE_ADMIN = 2;
+export const AUDIT_ACTION_SET_DIRECTORY_AUTHORITY = 3;
+export const AUDIT_ACTION_SET_METADATA_AUTHORITY = 4;
+
+/** The entries still in the audit log, from oldest to newest */
+export function auditLogEntries(auditLog: AuditLog): AuditEntry[] {
+  const head = auditLog.head.toNumber();
+  const oldest = Math.max(head - AUDIT_LOG_LENGTH, 0);
+  const entries: AuditEntry[] = [];
+  for (let i = oldest; i < head; i++) {
+    entries.push(auditLog.entries[i % AUDIT_LOG_LENGTH]);
+  }
+  return entries;
+}
+
+export async function getRevocationState(
+  program: anchor.Program<SegmenterRegistry>,
+  revocation: PublicKey
+): Promise<Revocation> {
+  return await program.account.revocation.fetch(revocation);
+}
+
+export const REVOCATION_REASON_COMPROMISED = 0;
+export const REVOCATION_REASON_RETIRED = 1;
+export const REVOCATION_REASON_MISBEHAVIOR = 2;
+
+/** The directory pages in order, read until the first page that doesn't exist */
+export async function getDirectoryPageStates(
+  program: anchor.Program<SegmenterRegistry>
+): Promise<DirectoryPage[]> {
+  const pages: DirectoryPage[] = [];
+  for (;;) {
+    const page = await program.account.directoryPage.fetchNullable(
+      getDirectoryPageAccount(program.programId, pages.length)
+    );
+    if (page === null) {
+      return pages;
+    }
+    pages.push(page);
+  }
+}
+
+/** The directory page listing the registry, or null if it isn't in the directory */
+export async function findDirectoryPageAccount(
+  program: anchor.Program<SegmenterRegistry>,
+  registry: PublicKey
+): Promise<PublicKey | null> {
+  const pages = await getDirectoryPageStates(program);
+  const pageIndex = pages.findIndex((page) =>
+    page.entries
+      .slice(0, page.len)
+      .some((entry) => entry.registry.equals(registry))
+  );
+  return pageIndex === -1
+    ? null
+    : getDirectoryPageAccount(program.programId, pageIndex);
+}
+
+/** Lists the registries in the directory, in creation order */
+export async function listRegistries(
+  program: anchor.Program<SegmenterRegistry>
+): Promise<DirectoryEntry[]> {
+  const pages = await getDirectoryPageStates(program);
+  return pages.flatMap((page) => page.entries.slice(0, page.len));
+}
+
+/** The name of a directory entry, without the zero padding */
+export function directoryEntryName(entry: DirectoryEntry): string {
+  return paddedString(entry.name);
+}
+
+/** A string stored in an account, without the zero padding */
+export function paddedString(bytes: number[]): string {
+  return Buffer.from(bytes).toString("utf8").replace(/\0+$/, "");
+}
+
+export const DIRECTORY_PAGE_LENGTH = 32;
+
+export const SESSION_MAX_DURATION_SLOTS = 216_000;
+
+export const NONCE_TRACKER_WINDOW = 4096;
+
+/** Whether the nonce's bit is set in the nonce tracker's bitmap */
+export function isNonceBitSet(nonceTracker: NonceTracker, nonce: number): boolean {
+  const bit = nonce % NONCE_TRACKER_WINDOW;
+  const word = nonceTracker.bitmap[Math.floor(bit / 64)];
+  return word.testn(bit % 64);
+}
+
 const EXPECTED_REGISTRY_LENGTH = 64;
 const DEFAULT_PUBKEY = new PublicKey("11111111111111111111111111111111");
 
@@ -161,7 +1126,7 @@ export function checkRegistry(actual: PublicKey[], expected: PublicKey[]) {
 
 export function checkConfig(
   actual: any,
-  expected: Config,
+  expected: Pick<Config, "admin">,
 ) {
   checkPublicKey(actual.admin, expected.admin, "admin");
 }
@@ -0,0 +1,127 @@
+import { BN, Idl, Program } from "@coral-xyz/anchor";
+import { Connection, Keypair, Transaction } from "@solana/web3.js";
+import chai from "chai";
+import { assert, expect } from "chai";
+import chaiAsPromised from "chai-as-promised";
+import {
+  checkPublicKey,
+  consumeNonceInstruction,
+  createNonceTrackerInstruction,
+  getNonceTrackerAccount,
+  getNonceTrackerState,
+  isNonceBitSet,
+  NONCE_TRACKER_WINDOW,
+} from "./helpers";
+import { MockWallet } from "./mock/mockWallet";
+import { SegmenterRegistry } from "../target/types/segmenter_registry";
+import idl from "../target/idl/segmenter_registry.json";
+import { sendAndConfirm } from "./transaction";
+
+chai.config.includeStack = true;
+chai.use(chaiAsPromised);
+
+describe("Nonce Tracker", () => {
+  const program = new Program(idl as Idl) as unknown as Program<SegmenterRegistry>;
+  const connection = new Connection("http://localhost:8899", "confirmed");
+
+  let payer: MockWallet;
+
+  const authority = Keypair.generate();
+  const segmenter = Keypair.generate();
+  const nonceTracker = getNonceTrackerAccount(
+    program.programId,
+    authority.publicKey,
+    segmenter.publicKey
+  );
+
+  const consumeNonce = async (nonce: number, signer: Keypair = authority) => {
+    const tx = new Transaction().add(
+      await consumeNonceInstruction({
+        program,
+        nonceTracker,
+        authority: signer.publicKey,
+        nonce: new BN(nonce),
+      })
+    );
+    return await sendAndConfirm(tx, {
+      feePayer: payer,
+      signers: [payer, signer],
+      connection,
+    });
+  };
+
+  before(async () => {
+    payer = await MockWallet.createWithBalance(connection);
+  });
+
+  it("create nonce tracker", async () => {
+    const tx = new Transaction().add(
+      await createNonceTrackerInstruction({
+        program,
+        authority: authority.publicKey,
+        payer: payer.publicKey,
+        segmenter: segmenter.publicKey,
+      })
+    );
+    await sendAndConfirm(tx, {
+      feePayer: payer,
+      signers: [payer, authority],
+      connection,
+    });
+    const state = await getNonceTrackerState(program, nonceTracker);
+    checkPublicKey(state.authority, authority.publicKey, "authority");
+    checkPublicKey(state.segmenter, segmenter.publicKey, "segmenter");
+    assert.strictEqual(state.base.toNumber(), 0, "base");
+  });
+
+  it("authority can consume a nonce", async () => {
+    await consumeNonce(5);
+    const state = await getNonceTrackerState(program, nonceTracker);
+    assert.isTrue(isNonceBitSet(state, 5));
+    assert.isFalse(isNonceBitSet(state, 6));
+  });
+
+  it("a nonce cannot be consumed twice", async () => {
+    await expect(consumeNonce(5)).to.eventually.be.rejectedWith(
+      `{"InstructionError":[0,{"Custom":15003}]}`
+    );
+  });
+
+  it("non authority cannot consume a nonce", async () => {
+    const nonAuthority = Keypair.generate();
+    await expect(consumeNonce(6, nonAuthority)).to.eventually.be.rejectedWith(
+      `{"InstructionError":[0,{"Custom":15005}]}`
+    );
+  });
+
+  it("consuming a nonce beyond the window slides the window", async () => {
+    const nonce = NONCE_TRACKER_WINDOW + 10;
+    await consumeNonce(nonce);
+    const state = await getNonceTrackerState(program, nonceTracker);
+    assert.strictEqual(state.base.toNumber(), 11, "base");
+    assert.isTrue(isNonceBitSet(state, nonce));
+    // Nonce 5 fell behind the window, so its bit was cleared
+    assert.isFalse(isNonceBitSet(state, 5));
+  });
+
+  it("sliding the window across several words only clears evicted nonces", async () => {
+    await consumeNonce(211);
+    await consumeNonce(300);
+    // Slides the base from 11 to 250, evicting partial and whole words
+    const nonce = 250 + NONCE_TRACKER_WINDOW - 1;
+    await consumeNonce(nonce);
+    const state = await getNonceTrackerState(program, nonceTracker);
+    assert.strictEqual(state.base.toNumber(), 250, "base");
+    assert.isFalse(isNonceBitSet(state, 211));
+    assert.isTrue(isNonceBitSet(state, 300));
+    assert.isTrue(isNonceBitSet(state, nonce));
+    // Shares a word with the evicted nonces, but is still in the window
+    assert.isTrue(isNonceBitSet(state, NONCE_TRACKER_WINDOW + 10));
+  });
+
+  it("a nonce behind the window cannot be consumed", async () => {
+    await expect(consumeNonce(10)).to.eventually.be.rejectedWith(
+      `{"InstructionError":[0,{"Custom":15004}]}`
+    );
+  });
+});
@@ -0,0 +1,132 @@
+import { Idl, Program } from "@coral-xyz/anchor";
+import { Connection, Keypair, PublicKey, Transaction } from "@solana/web3.js";
+import chai from "chai";
+import { assert, expect } from "chai";
+import chaiAsPromised from "chai-as-promised";
+import {
+  addOverlayEntryInstruction,
+  checkArrayContains,
+  checkPublicKey,
+  createOverlayInstruction,
+  getOverlayAccount,
+  getOverlayState,
+  removeOverlayEntryInstruction,
+} from "./helpers";
+import { MockWallet } from "./mock/mockWallet";
+import { SegmenterRegistry } from "../target/types/segmenter_registry";
+import idl from "../target/idl/segmenter_registry.json";
+import { sendAndConfirm } from "./transaction";
+
+chai.config.includeStack = true;
+chai.use(chaiAsPromised);
+
+describe("Overlay", () => {
+  const program = new Program(idl as Idl) as unknown as Program<SegmenterRegistry>;
+  const connection = new Connection("http://localhost:8899", "confirmed");
+
+  let payer: MockWallet;
+
+  // Loaded by the test validator from tests/fixtures/overlay-registry.json, as creating a registry
+  // needs the config that the segmenter registry tests initialize
+  const registry = new PublicKey(
+    "GyGKxMyg1p9SsHfm15MkNUu1u9TN2JtTspcdmrtGUdse"
+  );
+  const authority = Keypair.generate();
+  const overlay = getOverlayAccount(
+    program.programId,
+    authority.publicKey,
+    registry
+  );
+  const distrusted = Keypair.generate().publicKey;
+
+  const updateOverlay = async (
+    instruction: typeof addOverlayEntryInstruction,
+    list: { denied: {} } | { allowed: {} },
+    key: PublicKey,
+    signer: Keypair = authority
+  ) => {
+    const tx = new Transaction().add(
+      await instruction({
+        program,
+        overlay,
+        authority: signer.publicKey,
+        list,
+        key,
+      })
+    );
+    return await sendAndConfirm(tx, {
+      feePayer: payer,
+      signers: [payer, signer],
+      connection,
+    });
+  };
+
+  const isEqual = (a: PublicKey, b: PublicKey) => a.equals(b);
+  const asString = (key: PublicKey) => key.toBase58();
+
+  before(async () => {
+    payer = await MockWallet.createWithBalance(connection);
+  });
+
+  it("create overlay", async () => {
+    const tx = new Transaction().add(
+      await createOverlayInstruction({
+        program,
+        authority: authority.publicKey,
+        registry,
+        payer: payer.publicKey,
+      })
+    );
+    await sendAndConfirm(tx, {
+      feePayer: payer,
+      signers: [payer, authority],
+      connection,
+    });
+    const state = await getOverlayState(program, overlay);
+    checkPublicKey(state.authority, authority.publicKey, "authority");
+    checkPublicKey(state.registry, registry, "registry");
+  });
+
+  it("authority can deny a segmenter", async () => {
+    await updateOverlay(addOverlayEntryInstruction, { denied: {} }, distrusted);
+    const state = await getOverlayState(program, overlay);
+    checkArrayContains(
+      state.deniedSegmenters,
+      distrusted,
+      "deniedSegmenters",
+      isEqual,
+      asString
+    );
+  });
+
+  it("a segmenter cannot be denied twice", async () => {
+    await expect(
+      updateOverlay(addOverlayEntryInstruction, { denied: {} }, distrusted)
+    ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15001}]}`);
+  });
+
+  it("non authority cannot update the overlay", async () => {
+    const nonAuthority = Keypair.generate();
+    await expect(
+      updateOverlay(
+        addOverlayEntryInstruction,
+        { allowed: {} },
+        nonAuthority.publicKey,
+        nonAuthority
+      )
+    ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15010}]}`);
+  });
+
+  it("authority can remove a denied segmenter", async () => {
+    await updateOverlay(
+      removeOverlayEntryInstruction,
+      { denied: {} },
+      distrusted
+    );
+    const state = await getOverlayState(program, overlay);
+    assert.isFalse(
+      state.deniedSegmenters.some((key) => key.equals(distrusted)),
+      "deniedSegmenters"
+    );
+  });
+});
@@ -1,4 +1,4 @@
-import { Idl, Program } from "@coral-xyz/anchor";
+import { BN, Idl, Program } from "@coral-xyz/anchor";
 import {
   Connection,
   Keypair,
@@ -7,18 +7,70 @@ import {
   TransactionInstruction,
 } from "@solana/web3.js";
 import chai from "chai";
-import { expect } from "chai";
+import { assert, expect } from "chai";
 import chaiAsPromised from "chai-as-promised";
 import {
+  addProgramSegmenterInstruction,
   addSegmenterInstruction,
+  auditLogEntries,
   changeAdminInstruction,
+  changeFrontendKeyInstruction,
   checkConfig,
+  checkPublicKey,
   checkRegistry,
-  createRegistryInstruction,
+  createHeartbeatTrackerInstruction,
+  createRegistryInstructions,
+  createRegistryMetadataInstruction,
+  createSegmenterTiersInstruction,
+  CreationPolicy,
+  directoryEntryName,
+  findDirectoryPageAccount,
+  getAuditLogAccount,
+  getAuditLogState,
+  getConfigAccount,
   getConfigState,
+  getHeartbeatTrackerAccount,
+  getHeartbeatTrackerState,
+  getOriginEntryAccount,
+  getOriginEntryState,
+  getProgramSegmenterAccount,
+  getProgramSegmenterState,
+  getRegistryMetadataAccount,
+  getRegistryMetadataState,
+  getRegistrySnapshotAccount,
+  getRegistrySnapshotState,
   getRegistryState,
+  getRevocationAccount,
+  getRevocationState,
+  getSegmenterTiersAccount,
+  getSegmenterTiersState,
+  getSessionDelegationAccount,
+  getSessionDelegationState,
+  heartbeatInstruction,
   initializeInstruction,
+  listRegistries,
+  paddedString,
+  registerOriginInstruction,
+  registerSessionKeyInstruction,
+  removeProgramSegmenterInstruction,
   removeSegmenterInstruction,
+  revokeSessionKeyInstruction,
+  SegmenterTier,
+  setCreationPolicyInstruction,
+  setMaxHeartbeatAgeInstruction,
+  setRegistryMetadataInstruction,
+  setSegmenterTierInstruction,
+  snapshotRegistryInstruction,
+  AUDIT_ACTION_ADD_SEGMENTER,
+  AUDIT_ACTION_CHANGE_ADMIN,
+  AUDIT_ACTION_REMOVE_SEGMENTER,
+  AUDIT_ACTION_SET_DIRECTORY_AUTHORITY,
+  AUDIT_ACTION_SET_METADATA_AUTHORITY,
+  REVOCATION_REASON_COMPROMISED,
+  REVOCATION_REASON_MISBEHAVIOR,
+  SEGMENTER_TIER_GOLD,
+  SEGMENTER_TIER_PROBATION,
+  SESSION_MAX_DURATION_SLOTS,
 } from "./helpers";
 import { MockWallet } from "./mock/mockWallet";
 import { SegmenterRegistry } from "../target/types/segmenter_registry";
@@ -74,11 +126,12 @@ describe("Segmenter Registry", () => {
     // Anyone should be able to create a registry
     const anyRandomPayer = await MockWallet.createWithBalance(connection);
     const tx = new Transaction().add(
-      await createRegistryInstruction({
+      ...(await createRegistryInstructions({
         program: program,
         payer: anyRandomPayer.publicKey,
         registry: registryAccount.publicKey,
-      })
+        name: "test",
+      }))
     );
     await sendAndConfirm(tx, {
       feePayer: anyRandomPayer,
@@ -87,6 +140,55 @@ describe("Segmenter Registry", () => {
     });
     const registry = await getRegistryState(program, registryAccount.publicKey);
     checkRegistry(registry.registeredSegmenters, []);
+
+    // The registry is listed in the directory
+    const entry = (await listRegistries(program)).find((entry) =>
+      entry.registry.equals(registryAccount.publicKey)
+    );
+    assert.isDefined(entry, "directory entry");
+    checkPublicKey(entry.authority, anyRandomPayer.publicKey, "authority");
+    assert.strictEqual(directoryEntryName(entry), "test", "name");
+  });
+
+  it("registry name cannot exceed 32 bytes", async () => {
+    const tooLongName = Keypair.generate();
+    const tx = new Transaction().add(
+      ...(await createRegistryInstructions({
+        program,
+        payer: admin.publicKey,
+        registry: tooLongName.publicKey,
+        name: "x".repeat(33),
+      }))
+    );
+    const create = sendAndConfirm(tx, {
+      feePayer: admin,
+      signers: [admin, tooLongName],
+      connection,
+    });
+    await expect(create).to.eventually.be.rejectedWith(
+      `{"InstructionError":[0,{"Custom":15013}]}`
+    );
+  });
+
+  it("a directory page cannot be created before the last one is full", async () => {
+    const skipped = Keypair.generate();
+    const tx = new Transaction().add(
+      ...(await createRegistryInstructions({
+        program,
+        payer: admin.publicKey,
+        registry: skipped.publicKey,
+        name: "skipped",
+        pageIndex: 1,
+      }))
+    );
+    const create = sendAndConfirm(tx, {
+      feePayer: admin,
+      signers: [admin, skipped],
+      connection,
+    });
+    await expect(create).to.eventually.be.rejectedWith(
+      `{"InstructionError":[0,{"Custom":15012}]}`
+    );
   });
 
   it("admin can add segmenter A", async () => {
@@ -274,6 +376,178 @@ describe("Segmenter Registry", () => {
     );
   });
 
+  it("anyone can snapshot a registry", async () => {
+    const addTx = new Transaction().add(
+      await addSegmenterInstruction({
+        program,
+        admin: admin.publicKey,
+        registry: registryAccount.publicKey,
+        addKey: segmenterB.publicKey,
+      })
+    );
+    await sendAndConfirm(addTx, {
+      feePayer: admin,
+      signers: [admin],
+      connection,
+    });
+
+    const { head } = await getAuditLogState(
+      program,
+      getAuditLogAccount(program.programId, registryAccount.publicKey)
+    );
+    const anyRandomPayer = await MockWallet.createWithBalance(connection);
+    const snapshot = async (version: BN) => {
+      const tx = new Transaction().add(
+        await snapshotRegistryInstruction({
+          program,
+          registry: registryAccount.publicKey,
+          version,
+          payer: anyRandomPayer.publicKey,
+        })
+      );
+      return await sendAndConfirm(tx, {
+        feePayer: anyRandomPayer,
+        signers: [anyRandomPayer],
+        connection,
+      });
+    };
+
+    // Only the current version can be snapshotted
+    await expect(snapshot(head.addn(1))).to.eventually.be.rejectedWith(
+      `{"InstructionError":[0,{"Custom":15019}]}`
+    );
+
+    await snapshot(head);
+    const state = await getRegistrySnapshotState(
+      program,
+      getRegistrySnapshotAccount(
+        program.programId,
+        registryAccount.publicKey,
+        head
+      )
+    );
+    checkPublicKey(state.registry, registryAccount.publicKey, "registry");
+    assert.isTrue(state.version.eq(head), "version");
+    checkRegistry(state.registeredSegmenters, [segmenterB.publicKey]);
+
+    // Remove segmenter B, leaving the snapshot unchanged
+    const removeTx = new Transaction().add(
+      await removeSegmenterInstruction({
+        program,
+        admin: admin.publicKey,
+        registry: registryAccount.publicKey,
+        removeKey: segmenterB.publicKey,
+      })
+    );
+    await sendAndConfirm(removeTx, {
+      feePayer: admin,
+      signers: [admin],
+      connection,
+    });
+  });
+
+  it("audit log records segmenter changes", async () => {
+    const auditLog = await getAuditLogState(
+      program,
+      getAuditLogAccount(program.programId, registryAccount.publicKey)
+    );
+    checkPublicKey(auditLog.registry, registryAccount.publicKey, "registry");
+
+    const entries = auditLogEntries(auditLog);
+    assert.strictEqual(entries.length, auditLog.head.toNumber(), "entries");
+    const latest = entries[entries.length - 1];
+    checkPublicKey(latest.actor, admin.publicKey, "actor");
+    assert.strictEqual(latest.action, AUDIT_ACTION_REMOVE_SEGMENTER, "action");
+    // The registry's creation is logged first, with the authority it is listed with
+    const first = entries[0];
+    checkPublicKey(first.key, first.actor, "key");
+    assert.strictEqual(
+      first.action,
+      AUDIT_ACTION_SET_DIRECTORY_AUTHORITY,
+      "action"
+    );
+    const firstAdd = entries[1];
+    checkPublicKey(firstAdd.key, segmenterA.publicKey, "key");
+    assert.strictEqual(firstAdd.action, AUDIT_ACTION_ADD_SEGMENTER, "action");
+    assert.isTrue(
+      entries.every(
+        (entry, i) => i === 0 || entry.slot.gte(entries[i - 1].slot)
+      ),
+      "entries are in slot order"
+    );
+  });
+
+  it("revocation records removed segmenters with their reasons", async () => {
+    const revoked = Keypair.generate();
+    const tx = new Transaction()
+      .add(
+        await addSegmenterInstruction({
+          program,
+          admin: admin.publicKey,
+          registry: registryAccount.publicKey,
+          addKey: revoked.publicKey,
+        })
+      )
+      .add(
+        await removeSegmenterInstruction({
+          program,
+          admin: admin.publicKey,
+          registry: registryAccount.publicKey,
+          removeKey: revoked.publicKey,
+          reason: { compromised: {} },
+        })
+      );
+    await sendAndConfirm(tx, {
+      feePayer: admin,
+      signers: [admin],
+      connection,
+    });
+
+    const revocationAccount = getRevocationAccount(
+      program.programId,
+      registryAccount.publicKey,
+      revoked.publicKey
+    );
+    const revocation = await getRevocationState(program, revocationAccount);
+    checkPublicKey(revocation.registry, registryAccount.publicKey, "registry");
+    checkPublicKey(revocation.key, revoked.publicKey, "key");
+    assert.strictEqual(
+      revocation.reason,
+      REVOCATION_REASON_COMPROMISED,
+      "reason"
+    );
+    assert.strictEqual(revocation.count.toNumber(), 1, "count");
+    assert.isTrue(revocation.slot.gtn(0), "slot");
+
+    // Removing the segmenter again after adding it back keeps its revocation
+    const tx2 = new Transaction()
+      .add(
+        await addSegmenterInstruction({
+          program,
+          admin: admin.publicKey,
+          registry: registryAccount.publicKey,
+          addKey: revoked.publicKey,
+        })
+      )
+      .add(
+        await removeSegmenterInstruction({
+          program,
+          admin: admin.publicKey,
+          registry: registryAccount.publicKey,
+          removeKey: revoked.publicKey,
+          reason: { misbehavior: {} },
+        })
+      );
+    await sendAndConfirm(tx2, {
+      feePayer: admin,
+      signers: [admin],
+      connection,
+    });
+    const updated = await getRevocationState(program, revocationAccount);
+    assert.strictEqual(updated.reason, REVOCATION_REASON_MISBEHAVIOR, "reason");
+    assert.strictEqual(updated.count.toNumber(), 2, "count");
+  });
+
   it("current admin can change admin", async () => {
     const newAdmin = await MockWallet.createWithBalance(connection);
     const tx = new Transaction().add(
@@ -312,6 +586,50 @@ describe("Segmenter Registry", () => {
     }
   });
 
+  it("deployment audit log records admin changes", async () => {
+    const auditLog = await getAuditLogState(
+      program,
+      getAuditLogAccount(program.programId, getConfigAccount(program.programId))
+    );
+    const entries = auditLogEntries(auditLog);
+    assert.strictEqual(entries.length, 2, "entries");
+    const latest = entries[entries.length - 1];
+    assert.strictEqual(latest.action, AUDIT_ACTION_CHANGE_ADMIN, "action");
+    checkPublicKey(latest.key, admin.publicKey, "key");
+  });
+
+  it("admin can change the segmenters of a registry created before audit logs", async () => {
+    // Loaded by the test validator from tests/fixtures/legacy-registry.json
+    const legacyRegistry = new PublicKey(
+      "EdmxWPmx2WH6WgFfTdu9xfkYf3k1g5wD1zccTVySEEh1"
+    );
+    const segmenter = Keypair.generate().publicKey;
+    const tx = new Transaction().add(
+      await addSegmenterInstruction({
+        program,
+        admin: admin.publicKey,
+        registry: legacyRegistry,
+        addKey: segmenter,
+      })
+    );
+    await sendAndConfirm(tx, {
+      feePayer: admin,
+      signers: [admin],
+      connection,
+    });
+
+    const registry = await getRegistryState(program, legacyRegistry);
+    checkRegistry(registry.registeredSegmenters, [segmenter]);
+    const auditLog = await getAuditLogState(
+      program,
+      getAuditLogAccount(program.programId, legacyRegistry)
+    );
+    checkPublicKey(auditLog.registry, legacyRegistry, "registry");
+    const entries = auditLogEntries(auditLog);
+    assert.strictEqual(entries.length, 1, "entries");
+    assert.strictEqual(entries[0].action, AUDIT_ACTION_ADD_SEGMENTER, "action");
+  });
+
   it("non admin cannot change admin", async () => {
     const nonAdmin = await MockWallet.createWithBalance(connection);
     const tx = new Transaction().add(
@@ -349,4 +667,1042 @@ describe("Segmenter Registry", () => {
       `Signature verification failed`
     );
   });
+
+  it("admin can change admin on a config initialized before creation policies", async () => {
+    // Loaded by the test validator from tests/fixtures/legacy-config.json
+    const legacyConfig = new PublicKey(
+      "9hSR6S7WPtxmTojgo6GG3k4yDPecgJY292j7xrsUGWBu"
+    );
+    const legacyAdmin = new MockWallet(
+      connection,
+      Keypair.fromSeed(new Uint8Array(32).fill(1))
+    );
+    await legacyAdmin.requestAirdrop();
+    assert.strictEqual(
+      (await connection.getAccountInfo(legacyConfig))?.data.length,
+      40,
+      "legacy config length"
+    );
+
+    const newAdmin = Keypair.generate();
+    const tx = new Transaction().add(
+      await changeAdminInstruction({
+        program,
+        admin: legacyAdmin.publicKey,
+        newAdmin: newAdmin.publicKey,
+        config: legacyConfig,
+      })
+    );
+    await sendAndConfirm(tx, {
+      feePayer: legacyAdmin,
+      signers: [legacyAdmin],
+      connection,
+    });
+
+    const config = await program.account.config.fetch(legacyConfig);
+    checkConfig(config, { admin: newAdmin.publicKey });
+    assert.deepEqual(config.creationPolicy, { open: {} });
+    assert.strictEqual(
+      (await connection.getAccountInfo(legacyConfig))?.data.length,
+      program.account.config.size,
+      "grown config length"
+    );
+  });
+
+  const origin = 1;
+  const frontendKey = Keypair.generate();
+
+  it("admin can register an origin", async () => {
+    const tx = new Transaction().add(
+      await registerOriginInstruction({
+        program,
+        admin: admin.publicKey,
+        origin,
+        frontendKey: frontendKey.publicKey,
+      })
+    );
+    await sendAndConfirm(tx, {
+      feePayer: admin,
+      signers: [admin],
+      connection,
+    });
+    const originEntry = await getOriginEntryState(
+      program,
+      getOriginEntryAccount(program.programId, origin)
+    );
+    expect(originEntry.origin).to.equal(origin);
+    checkPublicKey(
+      originEntry.frontendKey,
+      frontendKey.publicKey,
+      "frontendKey"
+    );
+  });
+
+  it("admin cannot register an origin again", async () => {
+    const tx = new Transaction().add(
+      await registerOriginInstruction({
+        program,
+        admin: admin.publicKey,
+        origin,
+        frontendKey: Keypair.generate().publicKey,
+      })
+    );
+    const registerOrigin = sendAndConfirm(tx, {
+      feePayer: admin,
+      signers: [admin],
+      connection,
+    });
+    await expect(registerOrigin).to.eventually.be.rejectedWith(
+      `{"InstructionError":[0,{"Custom":0}]}`
+    );
+  });
+
+  it("non admin cannot register an origin", async () => {
+    const nonAdmin = await MockWallet.createWithBalance(connection);
+    const tx = new Transaction().add(
+      await registerOriginInstruction({
+        program,
+        admin: nonAdmin.publicKey,
+        origin: 2,
+        frontendKey: nonAdmin.publicKey,
+      })
+    );
+    const registerOrigin = sendAndConfirm(tx, {
+      feePayer: nonAdmin,
+      signers: [nonAdmin],
+      connection,
+    });
+    await expect(registerOrigin).to.eventually.be.rejectedWith(
+      `{"InstructionError":[0,{"Custom":15002}]}`
+    );
+  });
+
+  it("admin can change a frontend key", async () => {
+    const newFrontendKey = Keypair.generate();
+    const tx = new Transaction().add(
+      await changeFrontendKeyInstruction({
+        program,
+        admin: admin.publicKey,
+        origin,
+        newFrontendKey: newFrontendKey.publicKey,
+      })
+    );
+    await sendAndConfirm(tx, {
+      feePayer: admin,
+      signers: [admin],
+      connection,
+    });
+    const originEntry = await getOriginEntryState(
+      program,
+      getOriginEntryAccount(program.programId, origin)
+    );
+    expect(originEntry.origin).to.equal(origin);
+    checkPublicKey(
+      originEntry.frontendKey,
+      newFrontendKey.publicKey,
+      "frontendKey"
+    );
+  });
+
+  it("non admin cannot change a frontend key", async () => {
+    const nonAdmin = await MockWallet.createWithBalance(connection);
+    const tx = new Transaction().add(
+      await changeFrontendKeyInstruction({
+        program,
+        admin: nonAdmin.publicKey,
+        origin,
+        newFrontendKey: nonAdmin.publicKey,
+      })
+    );
+    const changeFrontendKey = sendAndConfirm(tx, {
+      feePayer: nonAdmin,
+      signers: [nonAdmin],
+      connection,
+    });
+    await expect(changeFrontendKey).to.eventually.be.rejectedWith(
+      `{"InstructionError":[0,{"Custom":15002}]}`
+    );
+  });
+
+  describe("program segmenters", () => {
+    const router = Keypair.generate().publicKey;
+    const programSegmenter = getProgramSegmenterAccount(
+      program.programId,
+      registryAccount.publicKey,
+      router
+    );
+    const [segmenterPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("segmenter")],
+      router
+    );
+    const isPdaRegistered = async () =>
+      (
+        await getRegistryState(program, registryAccount.publicKey)
+      ).registeredSegmenters.some((key) => key.equals(segmenterPda));
+    const latestAuditEntry = async () => {
+      const entries = auditLogEntries(
+        await getAuditLogState(
+          program,
+          getAuditLogAccount(program.programId, registryAccount.publicKey)
+        )
+      );
+      return entries[entries.length - 1];
+    };
+
+    const addProgramSegmenter = async (signer: MockWallet, seed: Buffer) => {
+      const tx = new Transaction().add(
+        await addProgramSegmenterInstruction({
+          program,
+          admin: signer.publicKey,
+          registry: registryAccount.publicKey,
+          programId: router,
+          seed,
+        })
+      );
+      return await sendAndConfirm(tx, {
+        feePayer: signer,
+        signers: [signer],
+        connection,
+      });
+    };
+
+    it("admin can add a program segmenter", async () => {
+      await addProgramSegmenter(admin, Buffer.from("segmenter"));
+      const state = await getProgramSegmenterState(program, programSegmenter);
+      checkPublicKey(state.registry, registryAccount.publicKey, "registry");
+      checkPublicKey(state.programId, router, "programId");
+      checkPublicKey(state.segmenter, segmenterPda, "segmenter");
+      expect(
+        Buffer.from(state.seed.slice(0, state.seedLen)).toString()
+      ).to.equal("segmenter");
+      // The PDA is registered, so it counts as a segmenter when the router signs for it
+      assert.isTrue(await isPdaRegistered());
+
+      const latest = await latestAuditEntry();
+      checkPublicKey(latest.actor, admin.publicKey, "actor");
+      checkPublicKey(latest.key, segmenterPda, "key");
+      assert.strictEqual(latest.action, AUDIT_ACTION_ADD_SEGMENTER, "action");
+    });
+
+    it("non admin cannot remove a program segmenter", async () => {
+      const nonAdmin = await MockWallet.createWithBalance(connection);
+      const tx = new Transaction().add(
+        await removeProgramSegmenterInstruction({
+          program,
+          admin: nonAdmin.publicKey,
+          registry: registryAccount.publicKey,
+          programId: router,
+          segmenter: segmenterPda,
+        })
+      );
+      const remove = sendAndConfirm(tx, {
+        feePayer: nonAdmin,
+        signers: [nonAdmin],
+        connection,
+      });
+      await expect(remove).to.eventually.be.rejectedWith(
+        `{"InstructionError":[0,{"Custom":15002}]}`
+      );
+    });
+
+    it("admin can remove a program segmenter", async () => {
+      const tx = new Transaction().add(
+        await removeProgramSegmenterInstruction({
+          program,
+          admin: admin.publicKey,
+          registry: registryAccount.publicKey,
+          programId: router,
+          segmenter: segmenterPda,
+          reason: { compromised: {} },
+        })
+      );
+      await sendAndConfirm(tx, {
+        feePayer: admin,
+        signers: [admin],
+        connection,
+      });
+      expect(await connection.getAccountInfo(programSegmenter)).to.be.null;
+      assert.isFalse(await isPdaRegistered());
+
+      const revocation = await getRevocationState(
+        program,
+        getRevocationAccount(
+          program.programId,
+          registryAccount.publicKey,
+          segmenterPda
+        )
+      );
+      checkPublicKey(revocation.key, segmenterPda, "revocation key");
+      assert.strictEqual(
+        revocation.reason,
+        REVOCATION_REASON_COMPROMISED,
+        "reason"
+      );
+      assert.strictEqual(revocation.count.toNumber(), 1, "count");
+
+      const latest = await latestAuditEntry();
+      checkPublicKey(latest.key, segmenterPda, "key");
+      assert.strictEqual(
+        latest.action,
+        AUDIT_ACTION_REMOVE_SEGMENTER,
+        "action"
+      );
+    });
+
+    it("seed cannot be longer than the maximum seed length", async () => {
+      const add = addProgramSegmenter(admin, Buffer.alloc(33));
+      await expect(add).to.eventually.be.rejectedWith(
+        `{"InstructionError":[0,{"Custom":15008}]}`
+      );
+    });
+
+    it("non admin cannot add a program segmenter", async () => {
+      const nonAdmin = await MockWallet.createWithBalance(connection);
+      const add = addProgramSegmenter(nonAdmin, Buffer.from("segmenter"));
+      await expect(add).to.eventually.be.rejectedWith(
+        `{"InstructionError":[0,{"Custom":15002}]}`
+      );
+    });
+
+    it("program segmenter changes are snapshotted as new versions", async () => {
+      const otherRouter = Keypair.generate().publicKey;
+      const auditLog = getAuditLogAccount(
+        program.programId,
+        registryAccount.publicKey
+      );
+      const snapshot = async () => {
+        const { head } = await getAuditLogState(program, auditLog);
+        const tx = new Transaction().add(
+          await snapshotRegistryInstruction({
+            program,
+            registry: registryAccount.publicKey,
+            version: head,
+            payer: admin.publicKey,
+          })
+        );
+        await sendAndConfirm(tx, {
+          feePayer: admin,
+          signers: [admin],
+          connection,
+        });
+        return head;
+      };
+      const sendAsAdmin = async (instruction: TransactionInstruction) =>
+        await sendAndConfirm(new Transaction().add(instruction), {
+          feePayer: admin,
+          signers: [admin],
+          connection,
+        });
+
+      const before = await snapshot();
+      await sendAsAdmin(
+        await addProgramSegmenterInstruction({
+          program,
+          admin: admin.publicKey,
+          registry: registryAccount.publicKey,
+          programId: otherRouter,
+          seed: Buffer.from("segmenter"),
+        })
+      );
+      const afterAdd = await snapshot();
+      assert.isFalse(afterAdd.eq(before), "version after add");
+
+      const [otherPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("segmenter")],
+        otherRouter
+      );
+      const state = await getRegistrySnapshotState(
+        program,
+        getRegistrySnapshotAccount(
+          program.programId,
+          registryAccount.publicKey,
+          afterAdd
+        )
+      );
+      assert.isTrue(
+        state.registeredSegmenters.some((key) => key.equals(otherPda)),
+        "snapshot contains the program segmenter"
+      );
+
+      await sendAsAdmin(
+        await removeProgramSegmenterInstruction({
+          program,
+          admin: admin.publicKey,
+          registry: registryAccount.publicKey,
+          programId: otherRouter,
+          segmenter: otherPda,
+        })
+      );
+      const afterRemove = await snapshot();
+      assert.isFalse(afterRemove.eq(afterAdd), "version after remove");
+    });
+  });
+
+  describe("session keys", () => {
+    const sessionRegistry = Keypair.generate();
+    const segmenter = Keypair.generate();
+    const sessionKey = Keypair.generate();
+    const programScope = Keypair.generate().publicKey;
+
+    const registerSessionKey = async (
+      signer: Keypair,
+      key: PublicKey,
+      expirySlot: number
+    ) => {
+      const tx = new Transaction().add(
+        await registerSessionKeyInstruction({
+          program,
+          registry: sessionRegistry.publicKey,
+          segmenter: signer.publicKey,
+          payer: admin.publicKey,
+          sessionKey: key,
+          expirySlot: new BN(expirySlot),
+          programScope,
+        })
+      );
+      return await sendAndConfirm(tx, {
+        feePayer: admin,
+        signers: [admin, signer],
+        connection,
+      });
+    };
+
+    before(async () => {
+      const tx = new Transaction()
+        .add(
+          ...(await createRegistryInstructions({
+            program,
+            payer: admin.publicKey,
+            registry: sessionRegistry.publicKey,
+            name: "session",
+          }))
+        )
+        .add(
+          await addSegmenterInstruction({
+            program,
+            admin: admin.publicKey,
+            registry: sessionRegistry.publicKey,
+            addKey: segmenter.publicKey,
+          })
+        );
+      await sendAndConfirm(tx, {
+        feePayer: admin,
+        signers: [admin, sessionRegistry],
+        connection,
+      });
+    });
+
+    it("registered segmenter can register a session key", async () => {
+      const expirySlot = (await connection.getSlot()) + 1000;
+      await registerSessionKey(segmenter, sessionKey.publicKey, expirySlot);
+      const sessionDelegation = await getSessionDelegationState(
+        program,
+        getSessionDelegationAccount(
+          program.programId,
+          segmenter.publicKey,
+          sessionKey.publicKey
+        )
+      );
+      checkPublicKey(
+        sessionDelegation.segmenter,
+        segmenter.publicKey,
+        "segmenter"
+      );
+      checkPublicKey(
+        sessionDelegation.sessionKey,
+        sessionKey.publicKey,
+        "sessionKey"
+      );
+      checkPublicKey(
+        sessionDelegation.programScope,
+        programScope,
+        "programScope"
+      );
+      expect(sessionDelegation.expirySlot.toNumber()).to.equal(expirySlot);
+    });
+
+    it("unregistered segmenter cannot register a session key", async () => {
+      const expirySlot = (await connection.getSlot()) + 1000;
+      const register = registerSessionKey(
+        Keypair.generate(),
+        Keypair.generate().publicKey,
+        expirySlot
+      );
+      await expect(register).to.eventually.be.rejectedWith(
+        `{"InstructionError":[0,{"Custom":15006}]}`
+      );
+    });
+
+    it("session key cannot expire in the past", async () => {
+      const expirySlot = (await connection.getSlot()) - 1;
+      const register = registerSessionKey(
+        segmenter,
+        Keypair.generate().publicKey,
+        expirySlot
+      );
+      await expect(register).to.eventually.be.rejectedWith(
+        `{"InstructionError":[0,{"Custom":15007}]}`
+      );
+    });
+
+    it("session key cannot outlive the maximum session duration", async () => {
+      const expirySlot =
+        (await connection.getSlot()) + SESSION_MAX_DURATION_SLOTS + 1000;
+      const register = registerSessionKey(
+        segmenter,
+        Keypair.generate().publicKey,
+        expirySlot
+      );
+      await expect(register).to.eventually.be.rejectedWith(
+        `{"InstructionError":[0,{"Custom":15007}]}`
+      );
+    });
+
+    it("other signer cannot revoke a segmenter's session key", async () => {
+      const other = Keypair.generate();
+      const ix = await revokeSessionKeyInstruction({
+        program,
+        segmenter: other.publicKey,
+        sessionKey: sessionKey.publicKey,
+      });
+      // Point the instruction at the segmenter's session delegation
+      ix.keys[0].pubkey = getSessionDelegationAccount(
+        program.programId,
+        segmenter.publicKey,
+        sessionKey.publicKey
+      );
+      const revoke = sendAndConfirm(new Transaction().add(ix), {
+        feePayer: admin,
+        signers: [admin, other],
+        connection,
+      });
+      // ConstraintSeeds
+      await expect(revoke).to.eventually.be.rejectedWith(
+        `{"InstructionError":[0,{"Custom":2006}]}`
+      );
+    });
+
+    it("segmenter can revoke a session key", async () => {
+      const sessionDelegation = getSessionDelegationAccount(
+        program.programId,
+        segmenter.publicKey,
+        sessionKey.publicKey
+      );
+      const rent = await connection.getBalance(sessionDelegation);
+      const balance = await connection.getBalance(segmenter.publicKey);
+      const tx = new Transaction().add(
+        await revokeSessionKeyInstruction({
+          program,
+          segmenter: segmenter.publicKey,
+          sessionKey: sessionKey.publicKey,
+        })
+      );
+      await sendAndConfirm(tx, {
+        feePayer: admin,
+        signers: [admin, segmenter],
+        connection,
+      });
+      expect(await connection.getAccountInfo(sessionDelegation)).to.be.null;
+      expect(await connection.getBalance(segmenter.publicKey)).to.equal(
+        balance + rent
+      );
+    });
+  });
+
+  describe("heartbeats", () => {
+    const registry = Keypair.generate();
+    const segmenter = Keypair.generate();
+    const heartbeatTracker = () =>
+      getHeartbeatTrackerAccount(program.programId, registry.publicKey);
+
+    const heartbeat = async (signer: Keypair) => {
+      const tx = new Transaction().add(
+        await heartbeatInstruction({
+          program,
+          registry: registry.publicKey,
+          segmenter: signer.publicKey,
+        })
+      );
+      return await sendAndConfirm(tx, {
+        feePayer: admin,
+        signers: [admin, signer],
+        connection,
+      });
+    };
+
+    const setMaxHeartbeatAge = async (
+      signer: MockWallet,
+      maxHeartbeatAgeSlots: number
+    ) => {
+      const tx = new Transaction().add(
+        await setMaxHeartbeatAgeInstruction({
+          program,
+          registry: registry.publicKey,
+          admin: signer.publicKey,
+          maxHeartbeatAgeSlots: new BN(maxHeartbeatAgeSlots),
+        })
+      );
+      return await sendAndConfirm(tx, {
+        feePayer: signer,
+        signers: [signer],
+        connection,
+      });
+    };
+
+    before(async () => {
+      const tx = new Transaction()
+        .add(
+          ...(await createRegistryInstructions({
+            program,
+            payer: admin.publicKey,
+            registry: registry.publicKey,
+            name: "heartbeat",
+          }))
+        )
+        .add(
+          await createHeartbeatTrackerInstruction({
+            program,
+            registry: registry.publicKey,
+            payer: admin.publicKey,
+          })
+        )
+        .add(
+          await addSegmenterInstruction({
+            program,
+            admin: admin.publicKey,
+            registry: registry.publicKey,
+            addKey: segmenter.publicKey,
+          })
+        );
+      await sendAndConfirm(tx, {
+        feePayer: admin,
+        signers: [admin, registry],
+        connection,
+      });
+    });
+
+    it("registered segmenter can send a heartbeat", async () => {
+      const slot = await connection.getSlot();
+      await heartbeat(segmenter);
+      const state = await getHeartbeatTrackerState(program, heartbeatTracker());
+      checkPublicKey(state.registry, registry.publicKey, "registry");
+      const entry = state.entries.find((entry) =>
+        entry.segmenter.equals(segmenter.publicKey)
+      );
+      assert.isDefined(entry, "entry");
+      assert.isTrue(entry.lastSeenSlot.gten(slot), "lastSeenSlot");
+    });
+
+    it("unregistered segmenter cannot send a heartbeat", async () => {
+      await expect(
+        heartbeat(Keypair.generate())
+      ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15006}]}`);
+    });
+
+    it("admin can set the maximum heartbeat age", async () => {
+      await setMaxHeartbeatAge(admin, 150);
+      const state = await getHeartbeatTrackerState(program, heartbeatTracker());
+      assert.strictEqual(
+        state.maxHeartbeatAgeSlots.toNumber(),
+        150,
+        "maxHeartbeatAgeSlots"
+      );
+    });
+
+    it("non admin cannot set the maximum heartbeat age", async () => {
+      const nonAdmin = await MockWallet.createWithBalance(connection);
+      await expect(
+        setMaxHeartbeatAge(nonAdmin, 0)
+      ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15002}]}`);
+    });
+  });
+
+  describe("segmenter tiers", () => {
+    const registry = Keypair.generate();
+    const segmenter = Keypair.generate();
+    const segmenterTiers = () =>
+      getSegmenterTiersAccount(program.programId, registry.publicKey);
+
+    const setSegmenterTier = async (
+      signer: MockWallet,
+      key: PublicKey,
+      tier: SegmenterTier | null
+    ) => {
+      const tx = new Transaction().add(
+        await setSegmenterTierInstruction({
+          program,
+          registry: registry.publicKey,
+          admin: signer.publicKey,
+          segmenter: key,
+          tier,
+        })
+      );
+      return await sendAndConfirm(tx, {
+        feePayer: signer,
+        signers: [signer],
+        connection,
+      });
+    };
+
+    const tierOf = async (key: PublicKey) => {
+      const state = await getSegmenterTiersState(program, segmenterTiers());
+      return state.entries.find((entry) => entry.segmenter.equals(key))?.tier;
+    };
+
+    before(async () => {
+      const tx = new Transaction()
+        .add(
+          ...(await createRegistryInstructions({
+            program,
+            payer: admin.publicKey,
+            registry: registry.publicKey,
+            name: "tiers",
+          }))
+        )
+        .add(
+          await createSegmenterTiersInstruction({
+            program,
+            registry: registry.publicKey,
+            payer: admin.publicKey,
+          })
+        )
+        .add(
+          await addSegmenterInstruction({
+            program,
+            admin: admin.publicKey,
+            registry: registry.publicKey,
+            addKey: segmenter.publicKey,
+          })
+        );
+      await sendAndConfirm(tx, {
+        feePayer: admin,
+        signers: [admin, registry],
+        connection,
+      });
+    });
+
+    it("admin can set and clear a segmenter's tier", async () => {
+      await setSegmenterTier(admin, segmenter.publicKey, { probation: {} });
+      assert.strictEqual(
+        await tierOf(segmenter.publicKey),
+        SEGMENTER_TIER_PROBATION,
+        "tier"
+      );
+
+      await setSegmenterTier(admin, segmenter.publicKey, { gold: {} });
+      assert.strictEqual(
+        await tierOf(segmenter.publicKey),
+        SEGMENTER_TIER_GOLD,
+        "tier"
+      );
+
+      await setSegmenterTier(admin, segmenter.publicKey, null);
+      assert.isUndefined(await tierOf(segmenter.publicKey), "tier");
+    });
+
+    it("unregistered segmenter cannot be assigned a tier", async () => {
+      await expect(
+        setSegmenterTier(admin, Keypair.generate().publicKey, { silver: {} })
+      ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15006}]}`);
+    });
+
+    it("non admin cannot set a segmenter's tier", async () => {
+      const nonAdmin = await MockWallet.createWithBalance(connection);
+      await expect(
+        setSegmenterTier(nonAdmin, segmenter.publicKey, { gold: {} })
+      ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15002}]}`);
+    });
+
+    it("removing a segmenter clears its tier", async () => {
+      await setSegmenterTier(admin, segmenter.publicKey, { gold: {} });
+
+      const tx = new Transaction()
+        .add(
+          await removeSegmenterInstruction({
+            program,
+            admin: admin.publicKey,
+            registry: registry.publicKey,
+            removeKey: segmenter.publicKey,
+          })
+        )
+        .add(
+          await addSegmenterInstruction({
+            program,
+            admin: admin.publicKey,
+            registry: registry.publicKey,
+            addKey: segmenter.publicKey,
+          })
+        );
+      await sendAndConfirm(tx, {
+        feePayer: admin,
+        signers: [admin],
+        connection,
+      });
+
+      assert.isUndefined(await tierOf(segmenter.publicKey), "tier");
+    });
+
+    it("removing a program segmenter clears its PDA's tier", async () => {
+      const router = Keypair.generate().publicKey;
+      const seed = Buffer.from("segmenter");
+      const [segmenterPda] = PublicKey.findProgramAddressSync([seed], router);
+      const sendAsAdmin = async (instruction: TransactionInstruction) =>
+        await sendAndConfirm(new Transaction().add(instruction), {
+          feePayer: admin,
+          signers: [admin],
+          connection,
+        });
+      const addRouter = async () =>
+        await sendAsAdmin(
+          await addProgramSegmenterInstruction({
+            program,
+            admin: admin.publicKey,
+            registry: registry.publicKey,
+            programId: router,
+            seed,
+          })
+        );
+
+      await addRouter();
+      await setSegmenterTier(admin, segmenterPda, { gold: {} });
+      await sendAsAdmin(
+        await removeProgramSegmenterInstruction({
+          program,
+          admin: admin.publicKey,
+          registry: registry.publicKey,
+          programId: router,
+          segmenter: segmenterPda,
+        })
+      );
+      await addRouter();
+
+      assert.isUndefined(await tierOf(segmenterPda), "tier");
+    });
+  });
+
+  describe("creation policy", () => {
+    const treasury = Keypair.generate().publicKey;
+    const fee = 1_000_000;
+
+    const setCreationPolicy = async (
+      creationPolicy: CreationPolicy,
+      signer: MockWallet = admin
+    ) => {
+      const tx = new Transaction().add(
+        await setCreationPolicyInstruction({
+          program,
+          admin: signer.publicKey,
+          creationPolicy,
+        })
+      );
+      return await sendAndConfirm(tx, {
+        feePayer: signer,
+        signers: [signer],
+        connection,
+      });
+    };
+
+    const createRegistry = async (payer: MockWallet) => {
+      const registry = Keypair.generate();
+      const tx = new Transaction().add(
+        ...(await createRegistryInstructions({
+          program,
+          payer: payer.publicKey,
+          registry: registry.publicKey,
+          name: "policy",
+        }))
+      );
+      return await sendAndConfirm(tx, {
+        feePayer: payer,
+        signers: [payer, registry],
+        connection,
+      });
+    };
+
+    after(async () => {
+      await setCreationPolicy({ open: {} });
+    });
+
+    it("non admin cannot set the creation policy", async () => {
+      const nonAdmin = await MockWallet.createWithBalance(connection);
+      await expect(
+        setCreationPolicy({ adminOnly: {} }, nonAdmin)
+      ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15002}]}`);
+    });
+
+    it("only the admin can create registries when admin only", async () => {
+      await setCreationPolicy({ adminOnly: {} });
+      const config = await getConfigState(program);
+      assert.deepEqual(config.creationPolicy, { adminOnly: {} });
+
+      const nonAdmin = await MockWallet.createWithBalance(connection);
+      await expect(createRegistry(nonAdmin)).to.eventually.be.rejectedWith(
+        `{"InstructionError":[0,{"Custom":15014}]}`
+      );
+      await createRegistry(admin);
+    });
+
+    it("creating a registry pays the fee to the treasury when fee required", async () => {
+      await setCreationPolicy({
+        feeRequired: { feeLamports: new BN(fee), treasury },
+      });
+
+      const creator = await MockWallet.createWithBalance(connection);
+      await createRegistry(creator);
+      assert.strictEqual(await connection.getBalance(treasury), fee, "treasury");
+    });
+
+    it("the fee cannot be paid to another treasury", async () => {
+      const creator = await MockWallet.createWithBalance(connection);
+      const registry = Keypair.generate();
+      const tx = new Transaction().add(
+        ...(await createRegistryInstructions({
+          program,
+          payer: creator.publicKey,
+          registry: registry.publicKey,
+          name: "policy",
+        }))
+      );
+      const treasuryMeta = tx.instructions[
+        tx.instructions.length - 1
+      ].keys.find((meta) => meta.pubkey.equals(treasury));
+      treasuryMeta.pubkey = creator.publicKey;
+      const create = sendAndConfirm(tx, {
+        feePayer: creator,
+        signers: [creator, registry],
+        connection,
+      });
+      const index = tx.instructions.length - 1;
+      await expect(create).to.eventually.be.rejectedWith(
+        `{"InstructionError":[${index},{"Custom":15015}]}`
+      );
+    });
+  });
+
+  describe("registry metadata", () => {
+    const registry = Keypair.generate();
+    let creator: MockWallet;
+
+    const setRegistryMetadata = async (
+      signer: MockWallet,
+      tags: string[] = ["origin", "spot"]
+    ) => {
+      const tx = new Transaction().add(
+        await setRegistryMetadataInstruction({
+          program,
+          registry: registry.publicKey,
+          authority: signer.publicKey,
+          name: "dflow",
+          descriptionUri: "https://dflow.net/registry.json",
+          tags,
+        })
+      );
+      return await sendAndConfirm(tx, {
+        feePayer: signer,
+        signers: [signer],
+        connection,
+      });
+    };
+
+    before(async () => {
+      creator = await MockWallet.createWithBalance(connection);
+      const tx = new Transaction().add(
+        ...(await createRegistryInstructions({
+          program,
+          payer: creator.publicKey,
+          registry: registry.publicKey,
+          name: "dflow",
+        }))
+      );
+      await sendAndConfirm(tx, {
+        feePayer: creator,
+        signers: [creator, registry],
+        connection,
+      });
+    });
+
+    it("only the registry's creator can create its metadata", async () => {
+      const directoryPage = await findDirectoryPageAccount(
+        program,
+        registry.publicKey
+      );
+      const createRegistryMetadata = async (authority: MockWallet) => {
+        const tx = new Transaction().add(
+          await createRegistryMetadataInstruction({
+            program,
+            registry: registry.publicKey,
+            directoryPage,
+            authority: authority.publicKey,
+            payer: authority.publicKey,
+          })
+        );
+        return await sendAndConfirm(tx, {
+          feePayer: authority,
+          signers: [authority],
+          connection,
+        });
+      };
+
+      const nonCreator = await MockWallet.createWithBalance(connection);
+      await expect(
+        createRegistryMetadata(nonCreator)
+      ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15016}]}`);
+
+      await createRegistryMetadata(creator);
+      const state = await getRegistryMetadataState(
+        program,
+        getRegistryMetadataAccount(program.programId, registry.publicKey)
+      );
+      checkPublicKey(state.registry, registry.publicKey, "registry");
+      checkPublicKey(state.authority, creator.publicKey, "authority");
+
+      // The authority is recorded in the registry's audit log
+      const entries = auditLogEntries(
+        await getAuditLogState(
+          program,
+          getAuditLogAccount(program.programId, registry.publicKey)
+        )
+      );
+      const latest = entries[entries.length - 1];
+      assert.strictEqual(
+        latest.action,
+        AUDIT_ACTION_SET_METADATA_AUTHORITY,
+        "action"
+      );
+      checkPublicKey(latest.key, creator.publicKey, "key");
+    });
+
+    it("authority can set the metadata", async () => {
+      await setRegistryMetadata(creator);
+      const state = await getRegistryMetadataState(
+        program,
+        getRegistryMetadataAccount(program.programId, registry.publicKey)
+      );
+      assert.strictEqual(paddedString(state.name), "dflow", "name");
+      assert.strictEqual(
+        paddedString(state.descriptionUri),
+        "https://dflow.net/registry.json",
+        "descriptionUri"
+      );
+      assert.deepEqual(
+        state.tags.map(paddedString).filter((tag) => tag !== ""),
+        ["origin", "spot"],
+        "tags"
+      );
+    });
+
+    it("non authority cannot set the metadata", async () => {
+      const nonAuthority = await MockWallet.createWithBalance(connection);
+      await expect(
+        setRegistryMetadata(nonAuthority)
+      ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15016}]}`);
+    });
+
+    it("there cannot be more than 8 tags", async () => {
+      const tags = Array.from({ length: 9 }, (_, i) => `tag${i}`);
+      await expect(
+        setRegistryMetadata(creator, tags)
+      ).to.eventually.be.rejectedWith(`{"InstructionError":[0,{"Custom":15018}]}`);
+    });
+  });
 });